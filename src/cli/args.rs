@@ -1,19 +1,24 @@
+use std::path::PathBuf;
+
 use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use crate::plugin::prd_runner::{parse_duration_arg, OutputFormat};
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum ProviderArg {
     Auto,
     Claude,
     Opencode,
+    Ollama,
 }
 
 #[derive(Debug, Parser)]
 #[command(name = "autocode")]
 #[command(about = "Autocode terminal agent (Rust), with plugin-based PRD runner")]
 pub struct Cli {
-    /// Provider backend (auto/claude/opencode)
-    #[arg(global = true, long, value_enum, default_value_t = ProviderArg::Auto)]
-    pub provider: ProviderArg,
+    /// Provider backend (auto/claude/opencode); defaults to $AUTOCODE_PROVIDER, then auto
+    #[arg(global = true, long, value_enum)]
+    pub provider: Option<ProviderArg>,
 
     /// Enable verbose logs
     #[arg(global = true, long)]
@@ -27,10 +32,22 @@ pub struct Cli {
     #[arg(global = true, long, short = 's')]
     pub session: Option<String>,
 
-    /// Fork from resumed session (compatible surface, reserved for future use)
+    /// Chat response timeout for interactive/TUI/one-shot modes (e.g. 20m)
+    #[arg(global = true, long, value_name = "DURATION")]
+    pub chat_timeout: Option<String>,
+
+    /// Config file to use instead of .autocode/config.toml (must exist)
+    #[arg(global = true, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Copy the resumed session (--continue/--session) into a new one and continue there
     #[arg(global = true, long)]
     pub fork: bool,
 
+    /// Read a prompt from file, send it once to the provider, print the reply and exit
+    #[arg(long, value_name = "PATH", conflicts_with = "prompt")]
+    pub prompt_file: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 
@@ -48,19 +65,67 @@ pub enum Command {
     /// Built-in alias for prd-runner plugin: autocode prd <cmd> ...
     Prd(PrdArgs),
     /// Inspect environment and provider availability
-    Doctor,
+    Doctor(DoctorArgs),
     /// Manage local chat sessions
     Session(SessionArgs),
+    /// Provider utilities
+    Provider(ProviderArgs),
+    /// Send one prompt to the provider, print the reply and exit (no session unless --save-session)
+    Ask(AskArgs),
 }
 
 #[derive(Debug, Args)]
-pub struct RunArgs {
-    #[arg(long, default_value = "10m")]
-    pub max_runtime: String,
+pub struct AskArgs {
+    /// Record the exchange in a session (honours --continue/--session)
     #[arg(long)]
+    pub save_session: bool,
+    /// Prompt tokens, joined with spaces
+    #[arg(value_name = "PROMPT", required = true, num_args = 1.., trailing_var_arg = true)]
+    pub prompt: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Arbitrary provider CLI run as `<cmd> <prompt>`; takes precedence over --provider
+    #[arg(long, value_name = "CMD")]
+    pub provider_command: Option<String>,
+    /// Overrides convergence.max_runtime from .autocode/config.toml (default 10m)
+    #[arg(long, value_parser = parse_duration_arg)]
+    pub max_runtime: Option<String>,
+    #[arg(long, value_parser = parse_duration_arg)]
     pub provider_timeout: Option<String>,
+    /// Overrides provider.model (e.g. claude --model)
+    #[arg(long)]
+    pub model: Option<String>,
     #[arg(long)]
     pub dry_run: bool,
+    /// PRD file to run instead of PRD.md (`-` reads stdin)
+    #[arg(long, value_name = "PATH")]
+    pub prd: Option<PathBuf>,
+    /// Stable run directory name instead of the run_YYYYMMDD_HHMMSS timestamp
+    #[arg(long)]
+    pub run_id: Option<String>,
+    /// Dry run that still queries the provider and logs the parsed commands (and whether the
+    /// guards would block them) without executing commands or acceptance checks
+    #[arg(long)]
+    pub preview_guards: bool,
+    /// Ask the provider once per requirement and log the planned commands; runs a single
+    /// pass with nothing executed and no checkpoints
+    #[arg(long, conflicts_with_all = ["dry_run", "preview_guards"])]
+    pub plan_only: bool,
+    /// Final summary format on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// Scaffold missing .autocode directories, config.toml and PRD.md
+    #[arg(long)]
+    pub fix: bool,
+    /// Apply fixes without asking for confirmation
+    #[arg(long, short = 'y', requires = "fix")]
+    pub yes: bool,
 }
 
 #[derive(Debug, Args)]
@@ -77,6 +142,25 @@ pub struct PrdArgs {
     pub tokens: Vec<String>,
 }
 
+#[derive(Debug, Args)]
+pub struct ProviderArgs {
+    #[command(subcommand)]
+    pub command: ProviderCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProviderCommand {
+    /// Send a trivial prompt to check the provider is reachable and logged in
+    Ping(ProviderPingArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ProviderPingArgs {
+    /// Reply timeout (e.g. 30s)
+    #[arg(long, default_value = "30s")]
+    pub timeout: String,
+}
+
 #[derive(Debug, Args)]
 pub struct SessionArgs {
     #[command(subcommand)]
@@ -89,6 +173,27 @@ pub enum SessionCommand {
     List(SessionListArgs),
     /// Delete session by id
     Delete(SessionDeleteArgs),
+    /// Find sessions whose messages contain text (case-insensitive)
+    Search(SessionSearchArgs),
+    /// Give a session a fixed title
+    Rename(SessionRenameArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SessionRenameArgs {
+    /// Session id
+    pub session_id: String,
+    /// New title; later messages no longer change it
+    pub title: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SessionSearchArgs {
+    /// Text to look for
+    pub query: String,
+    /// Show at most N matching sessions (0 = all)
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub max_count: usize,
 }
 
 #[derive(Debug, Args)]
@@ -132,7 +237,9 @@ fn rewrite_legacy_args(args: Vec<String>) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::rewrite_legacy_args;
+    use clap::Parser;
+
+    use super::{rewrite_legacy_args, Cli, Command};
 
     #[test]
     fn rewrites_legacy_provider_entrypoint() {
@@ -157,6 +264,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_ask_prompt_tokens() {
+        let cli = Cli::parse_from(["autocode", "ask", "--save-session", "what", "is", "this?"]);
+        match cli.command {
+            Some(Command::Ask(args)) => {
+                assert!(args.save_session);
+                assert_eq!(args.prompt, vec!["what", "is", "this?"]);
+            }
+            other => panic!("expected ask command, got {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["autocode", "ask"]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_run_durations_at_parse_time() {
+        let err = Cli::try_parse_from(["autocode", "run", "--max-runtime", "soon"])
+            .expect_err("invalid duration should be rejected");
+        assert!(err.to_string().contains("--max-runtime"), "{}", err);
+        assert!(Cli::try_parse_from(["autocode", "run", "--provider-timeout", "5x"]).is_err());
+        assert!(Cli::try_parse_from(["autocode", "run", "--max-runtime", "4h30m"]).is_ok());
+    }
+
     #[test]
     fn keeps_normal_commands_unchanged() {
         let input = vec![
@@ -0,0 +1,116 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use humantime::parse_duration;
+use serde::Deserialize;
+
+use crate::cli::theme::ThemeSection;
+use crate::plugin::prd_runner::logger::redact::default_redact_patterns;
+
+const DEFAULT_CHAT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// CLI-level settings read from `.autocode/config.toml`; unknown tables are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct CliConfig {
+    #[serde(default)]
+    pub tui: TuiSection,
+    #[serde(default)]
+    pub chat: ChatSection,
+    #[serde(default)]
+    pub logging: LoggingSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TuiSection {
+    #[serde(default)]
+    pub theme: ThemeSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChatSection {
+    pub timeout: Option<String>,
+}
+
+/// Only the redaction list is shared with the PRD runner's `[logging]` table.
+#[derive(Debug, Deserialize)]
+pub struct LoggingSection {
+    #[serde(default = "default_redact_patterns")]
+    pub redact_patterns: Vec<String>,
+}
+
+impl Default for LoggingSection {
+    fn default() -> Self {
+        Self {
+            redact_patterns: default_redact_patterns(),
+        }
+    }
+}
+
+impl CliConfig {
+    pub fn load_from_dir(workdir: &Path) -> Result<Self> {
+        let path = workdir.join(".autocode").join("config.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::load_from_file(&path)
+    }
+
+    /// Same lookup as [`AppConfig::load`]: an explicit `--config` path must exist.
+    ///
+    /// [`AppConfig::load`]: crate::plugin::prd_runner::config::AppConfig::load
+    pub fn load(workdir: &Path, config_path: Option<&Path>) -> Result<Self> {
+        match config_path {
+            Some(path) => Self::load_from_file(&workdir.join(path)),
+            None => Self::load_from_dir(workdir),
+        }
+    }
+
+    fn load_from_file(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            bail!("config file not found: {}", path.display());
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Flag wins over `[chat] timeout`, which wins over the built-in 600s.
+    pub fn chat_timeout(&self, flag: Option<&str>) -> Result<Duration> {
+        if let Some(raw) = flag {
+            return parse_duration(raw).with_context(|| format!("invalid --chat-timeout: {}", raw));
+        }
+        if let Some(raw) = self.chat.timeout.as_deref() {
+            return parse_duration(raw).with_context(|| format!("invalid chat.timeout: {}", raw));
+        }
+        Ok(DEFAULT_CHAT_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::CliConfig;
+
+    #[test]
+    fn chat_timeout_prefers_flag_then_config() {
+        let mut config = CliConfig::default();
+        assert_eq!(
+            config.chat_timeout(None).expect("default"),
+            Duration::from_secs(600)
+        );
+
+        config.chat.timeout = Some("20m".to_string());
+        assert_eq!(
+            config.chat_timeout(None).expect("config"),
+            Duration::from_secs(1200)
+        );
+        assert_eq!(
+            config.chat_timeout(Some("30s")).expect("flag"),
+            Duration::from_secs(30)
+        );
+        assert!(config.chat_timeout(Some("soon")).is_err());
+    }
+}
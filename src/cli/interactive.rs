@@ -1,22 +1,31 @@
 use std::io;
 use std::path::Path;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 
 use crate::cli::session_store::{OpenSessionOptions, SessionStore, SessionTranscript, StoredRole};
 use crate::cli::ui;
-use crate::cli::utils::split_command_tokens;
+use crate::cli::utils::{provider_error_message, split_command_tokens};
+use crate::plugin::prd_runner::app::{run_with_observers, PrdRunOptions};
 use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+use crate::plugin::prd_runner::logger::event::{EngineEvent, EventObserver, EventObservers};
 use crate::plugin::prd_runner::PluginDispatchContext;
 use crate::plugin::registry::PluginRegistry;
 use crate::provider::{resolve_provider, ProviderKind, ProviderSelection};
 use crate::runtime::signal;
 
-const CHAT_TIMEOUT: Duration = Duration::from_secs(600);
 const HISTORY_LIMIT: usize = 12;
 const WAIT_FRAMES: [&str; 4] = ["-", "\\", "|", "/"];
+const PROGRESS_EVENTS: [&str; 6] = [
+    "ITERATION_START",
+    "ITERATION_END",
+    "REQ_FAILED",
+    "ACCEPTANCE_STATUS",
+    "CHECKPOINT",
+    "STOP",
+];
 
 pub struct InteractiveSession<'a> {
     pub workdir: &'a Path,
@@ -25,6 +34,8 @@ pub struct InteractiveSession<'a> {
     pub session_store: &'a SessionStore,
     pub session_options: OpenSessionOptions<'a>,
     pub initial_prompt: Option<&'a str>,
+    pub chat_timeout: Duration,
+    pub config_path: Option<&'a Path>,
 }
 
 pub fn run(session: InteractiveSession<'_>) -> Result<()> {
@@ -60,6 +71,7 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
             session.session_store,
             &current.id,
             initial_prompt,
+            session.chat_timeout,
         )?;
     }
 
@@ -172,7 +184,7 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
         if let Some(rest) = input.strip_prefix("/provider ") {
             let value = rest.trim();
             let Some(selection) = ProviderSelection::parse(value) else {
-                ui::print_error("invalid provider, expected auto|claude|opencode");
+                ui::print_error("invalid provider, expected auto|claude|opencode|ollama");
                 continue;
             };
 
@@ -202,6 +214,7 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
             let args = tokens[1..].to_vec();
             let context = PluginDispatchContext {
                 default_provider: provider_selection,
+                config_path: session.config_path.map(Path::to_path_buf),
             };
             if let Err(err) =
                 session
@@ -213,6 +226,11 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
             continue;
         }
 
+        if input == "/run" {
+            run_prd_loop(session.workdir, provider_selection, session.config_path);
+            continue;
+        }
+
         if let Some(rest) = input.strip_prefix("/prd ") {
             let tokens = match split_command_tokens(rest) {
                 Ok(tokens) => tokens,
@@ -227,6 +245,7 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
             }
             let context = PluginDispatchContext {
                 default_provider: provider_selection,
+                config_path: session.config_path.map(Path::to_path_buf),
             };
             if let Err(err) =
                 session
@@ -245,6 +264,7 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
             session.session_store,
             &current.id,
             input,
+            session.chat_timeout,
         )?;
     }
 
@@ -252,6 +272,38 @@ pub fn run(session: InteractiveSession<'_>) -> Result<()> {
     Ok(())
 }
 
+fn run_prd_loop(workdir: &Path, provider: ProviderSelection, config_path: Option<&Path>) {
+    let mut observers = EventObservers::default();
+    observers.push(progress_observer(Arc::new(|line: String| {
+        ui::print_system(&line)
+    })));
+    ui::print_system("prd loop started (Ctrl+C stops the loop)");
+
+    let options = PrdRunOptions {
+        provider,
+        config_path: config_path.map(Path::to_path_buf),
+        ..PrdRunOptions::default()
+    };
+    match run_with_observers(workdir, options, observers) {
+        Ok(output) => ui::print_system(&format!(
+            "prd loop finished: completed={} iterations={} stop_reason={}",
+            output.summary.completed,
+            output.summary.iterations,
+            output.summary.stop_reason.as_deref().unwrap_or("-")
+        )),
+        Err(err) => ui::print_error(&format!("prd error: {:#}", err)),
+    }
+    signal::reset_interrupted();
+}
+
+fn progress_observer(sink: Arc<dyn Fn(String) + Send + Sync>) -> EventObserver {
+    Arc::new(move |event: &EngineEvent| {
+        if PROGRESS_EVENTS.contains(&event.kind.as_str()) {
+            sink(format!("[{}] {}", event.kind, event.message));
+        }
+    })
+}
+
 fn submit_chat_prompt(
     provider: &mut CliPrintProvider,
     provider_kind: ProviderKind,
@@ -259,6 +311,7 @@ fn submit_chat_prompt(
     session_store: &SessionStore,
     session_id: &str,
     input: &str,
+    timeout: Duration,
 ) -> Result<()> {
     let prompt = build_prompt(history, input);
     provider
@@ -267,11 +320,18 @@ fn submit_chat_prompt(
 
     session_store.append_message(session_id, StoredRole::User, input)?;
     let started = Instant::now();
-    let output = read_provider_with_wait(provider, CHAT_TIMEOUT, provider_kind.as_str())
-        .context("failed to read provider output")?;
-    ui::clear_wait();
-    println!();
-    ui::print_assistant(provider_kind.as_str(), output.trim(), started.elapsed());
+    let output = match read_provider_with_wait(provider, timeout, provider_kind.as_str()) {
+        Ok(output) => output,
+        Err(err) => {
+            ui::clear_wait();
+            println!();
+            let message = provider_error_message(&err);
+            ui::print_error(&message);
+            session_store.append_message(session_id, StoredRole::Error, &message)?;
+            return Ok(());
+        }
+    };
+    ui::print_assistant_end(started.elapsed());
 
     session_store.append_message(session_id, StoredRole::Assistant, output.trim())?;
     history.push(("user".to_string(), input.to_string()));
@@ -296,9 +356,10 @@ fn read_provider_with_wait(
     provider_name: &str,
 ) -> Result<String> {
     let (done_tx, done_rx) = mpsc::channel::<()>();
+    let provider_label = provider_name.to_string();
     let provider_name = provider_name.to_string();
 
-    let spinner = std::thread::spawn(move || {
+    let mut spinner = Some(std::thread::spawn(move || {
         let started = Instant::now();
         let mut frame_index = 0usize;
         loop {
@@ -312,15 +373,36 @@ fn read_provider_with_wait(
                 }
             }
         }
-    });
+    }));
+    let mut stop_spinner = || {
+        if let Some(spinner) = spinner.take() {
+            let _ = done_tx.send(());
+            let _ = spinner.join();
+            ui::clear_wait();
+            true
+        } else {
+            false
+        }
+    };
 
-    let output = provider.read_output(timeout);
-    let _ = done_tx.send(());
-    let _ = spinner.join();
-    output
+    let mut streamed = false;
+    let output = provider.read_output_streaming(timeout, &mut |chunk| {
+        if stop_spinner() {
+            ui::print_assistant_start(&provider_label);
+        }
+        streamed = true;
+        ui::print_assistant_chunk(chunk);
+    });
+    stop_spinner();
+    let output = output?;
+    if !streamed {
+        ui::print_assistant_start(&provider_label);
+        ui::print_assistant_chunk(&format!("{}\n", output.trim()));
+    }
+    Ok(output)
 }
 
-fn build_prompt(history: &[(String, String)], input: &str) -> String {
+pub fn build_prompt(history: &[(String, String)], input: &str) -> String {
     let mut prompt = String::from(
         "You are autocode interactive coding assistant. Respond concisely and with executable guidance when needed.\n\n",
     );
@@ -335,7 +417,7 @@ fn build_prompt(history: &[(String, String)], input: &str) -> String {
     prompt
 }
 
-fn history_from_transcript(transcript: &SessionTranscript) -> Vec<(String, String)> {
+pub fn history_from_transcript(transcript: &SessionTranscript) -> Vec<(String, String)> {
     transcript
         .messages
         .iter()
@@ -346,3 +428,117 @@ fn history_from_transcript(transcript: &SessionTranscript) -> Vec<(String, Strin
         })
         .collect()
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use std::sync::{Arc, Mutex};
+
+    use super::{progress_observer, read_provider_with_wait};
+    use crate::plugin::prd_runner::config::prd::{
+        AcceptanceCriterion, AcceptancePhase, PrdDocument, Requirement,
+    };
+    use crate::plugin::prd_runner::config::AppConfig;
+    use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+    use crate::plugin::prd_runner::core::scripted::ScriptedProvider;
+    use crate::plugin::prd_runner::logger::event::EventObservers;
+    use crate::plugin::prd_runner::loop_engine::driver::EngineRuntime;
+
+    fn slow_provider(tmp: &TempDir) -> CliPrintProvider {
+        let script = tmp.path().join("slow-provider.sh");
+        std::fs::write(&script, "#!/bin/sh\nsleep 1\necho \"reply to: $1\"\n").expect("script");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+        CliPrintProvider::new(script.display().to_string(), tmp.path())
+    }
+
+    #[test]
+    fn raised_chat_timeout_allows_slow_provider() {
+        let tmp = TempDir::new().expect("tmp");
+
+        let mut provider = slow_provider(&tmp);
+        provider.send("hello").expect("send");
+        let err = read_provider_with_wait(&mut provider, Duration::from_millis(200), "slow")
+            .expect_err("short timeout should fail");
+        assert!(format!("{:#}", err).contains("timed out"));
+
+        let mut provider = slow_provider(&tmp);
+        provider.send("hello").expect("send");
+        let output = read_provider_with_wait(&mut provider, Duration::from_secs(10), "slow")
+            .expect("raised timeout should succeed");
+        assert!(output.contains("reply to: hello"));
+    }
+
+    #[test]
+    fn run_loop_streams_progress_lines() {
+        let tmp = TempDir::new().expect("tmp");
+        let prd = PrdDocument {
+            project_name: Some("demo".to_string()),
+            project_context: "ctx".to_string(),
+            requirements: vec![Requirement {
+                id: "REQ-001".to_string(),
+                title: "echo".to_string(),
+                priority: None,
+                description: "run echo".to_string(),
+                validate_command: "echo ok".to_string(),
+                validate_commands: Vec::new(),
+                pass_condition: "退出码 = 0".to_string(),
+                tasks: Vec::new(),
+                artifacts: Vec::new(),
+                provider_timeout: None,
+                depends_on: Vec::new(),
+                workdir: None,
+            }],
+            acceptance_criteria: vec![AcceptanceCriterion {
+                name: "echo".to_string(),
+                validate_command: "echo ok".to_string(),
+                pass_condition: "退出码 = 0".to_string(),
+                phase: AcceptancePhase::PerIteration,
+            }],
+            raw_markdown: "# PRD".to_string(),
+        };
+        let prd_path = tmp.path().join("PRD.md");
+        std::fs::write(&prd_path, "# PRD").expect("prd");
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let mut observers = EventObservers::default();
+        observers.push(progress_observer(Arc::new(move |line: String| {
+            sink.lock().expect("lines lock").push(line)
+        })));
+
+        let mut config = AppConfig::default();
+        config.convergence.max_runtime = "1s".to_string();
+        config.checkpoint.enabled = false;
+        let runtime = EngineRuntime {
+            prd,
+            prd_path,
+            config,
+            workdir: tmp.path().to_path_buf(),
+            log_dir: tmp.path().join("logs"),
+            checkpoint_root: tmp.path().join("checkpoints"),
+            provider_command: "scripted".to_string(),
+            provider_timeout: Duration::from_secs(1),
+            dry_run: false,
+            reverify_done: false,
+            observers,
+        };
+        let provider = ScriptedProvider::new(&["```bash\necho done\n```"]);
+        let summary = runtime
+            .run_with_provider(Box::new(provider), None)
+            .expect("run");
+        assert!(summary.completed);
+
+        let lines = lines.lock().expect("lines lock");
+        assert!(lines
+            .iter()
+            .any(|line| line == "[ITERATION_START] iteration=1"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("[ACCEPTANCE_STATUS]")));
+        assert!(!lines.iter().any(|line| line.starts_with("[AI_CMD_EXEC]")));
+    }
+}
@@ -1,12 +1,18 @@
 mod args;
+mod config;
 mod interactive;
+mod oneshot;
+mod ping;
 mod session_store;
+mod theme;
 mod tui;
 mod ui;
 mod utils;
 
-use std::io::IsTerminal;
+use std::io::{self, IsTerminal, Write};
 use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::{bail, Context, Result};
 use clap::CommandFactory;
@@ -14,36 +20,100 @@ use serde_json::json;
 use tracing::info;
 
 use crate::cli::args::{
-    parse_cli, Cli, Command, PluginArgs, ProviderArg, RunArgs, SessionArgs, SessionCommand,
+    parse_cli, Cli, Command, DoctorArgs, PluginArgs, ProviderArg, ProviderCommand, RunArgs,
+    SessionArgs, SessionCommand,
 };
+use crate::cli::config::CliConfig;
 use crate::cli::session_store::{OpenSessionOptions, SessionStore};
-use crate::plugin::prd_runner::PluginDispatchContext;
+use crate::cli::theme::Theme;
+use crate::plugin::prd_runner::config::parser::parse_prd_file_with_warnings;
+use crate::plugin::prd_runner::config::AppConfig;
+use crate::plugin::prd_runner::logger::redact::Redactor;
+use crate::plugin::prd_runner::{init_prd_template, OutputFormat, PluginDispatchContext};
 use crate::plugin::registry::PluginRegistry;
-use crate::provider::{provider_available, ProviderKind, ProviderSelection};
-use crate::runtime::signal::install_ctrlc_handler;
+use crate::provider::{CommandProbe, ProviderKind, ProviderProbe, ProviderSelection};
+use crate::runtime::signal::install_termination_handlers;
 
-pub fn run() -> Result<()> {
+const PROVIDER_ENV: &str = "AUTOCODE_PROVIDER";
+
+/// Set while the TUI owns the terminal; warnings from a `/run` thread would draw over it.
+static TRACING_MUTED: AtomicBool = AtomicBool::new(false);
+
+pub fn run() -> Result<ExitCode> {
     let cli = parse_cli();
     init_tracing(cli.verbose)?;
-    install_ctrlc_handler()?;
+    install_termination_handlers()?;
 
     let workdir = std::env::current_dir().context("failed to resolve current directory")?;
-    let session_store = SessionStore::new(&workdir)?;
-    let plugin_registry = PluginRegistry::new();
-    let provider = to_provider_selection(cli.provider);
-    let initial_prompt = join_prompt_tokens(&cli.prompt);
+    let config_path = cli.config.as_deref().map(|path| workdir.join(path));
+    if let Some(path) = config_path.as_deref() {
+        if !path.is_file() {
+            bail!("config file not found: {}", path.display());
+        }
+    }
+    let cli_config = CliConfig::load(&workdir, config_path.as_deref())?;
+    let provider =
+        default_provider_selection(cli.provider, std::env::var(PROVIDER_ENV).ok().as_deref())?;
     let session_options = OpenSessionOptions {
         continue_last: cli.continue_last,
         session_id: cli.session.as_deref(),
     };
+    let redactor = Redactor::new(&cli_config.logging.redact_patterns)?;
 
-    if cli.fork {
-        info!("--fork accepted for compatibility (current build does not fork sessions)");
+    if let Some(Command::Ask(args)) = &cli.command {
+        // Opening the session store creates .autocode/sessions, so only do it when asked to.
+        let session_store = if args.save_session {
+            Some(SessionStore::new(&workdir)?.with_redactor(redactor))
+        } else {
+            None
+        };
+        oneshot::run_ask(oneshot::AskSession {
+            workdir: &workdir,
+            default_provider: provider,
+            session: session_store.as_ref().map(|store| (store, session_options)),
+            prompt: &args.prompt.join(" "),
+            chat_timeout: cli_config.chat_timeout(cli.chat_timeout.as_deref())?,
+        })?;
+        return Ok(ExitCode::SUCCESS);
     }
 
+    let session_store = SessionStore::new(&workdir)?.with_redactor(redactor);
+    let plugin_registry = PluginRegistry::new();
+    let initial_prompt = join_prompt_tokens(&cli.prompt);
+
+    let forked_id = if cli.fork {
+        Some(fork_session(&session_store, session_options)?)
+    } else {
+        None
+    };
+    let session_options = match forked_id.as_deref() {
+        Some(id) => OpenSessionOptions {
+            continue_last: false,
+            session_id: Some(id),
+        },
+        None => session_options,
+    };
+
+    let dispatch_context = PluginDispatchContext {
+        default_provider: provider,
+        config_path: config_path.clone(),
+    };
     match cli.command {
         None => {
-            if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            let chat_timeout = cli_config.chat_timeout(cli.chat_timeout.as_deref())?;
+            if let Some(prompt_file) = cli.prompt_file.as_deref() {
+                oneshot::run_prompt_file(oneshot::PromptFileSession {
+                    workdir: &workdir,
+                    default_provider: provider,
+                    session_store: &session_store,
+                    session_options,
+                    prompt_file,
+                    chat_timeout,
+                })?;
+            } else if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+                let theme = Theme::default()
+                    .with_overrides(&cli_config.tui.theme)
+                    .context("invalid [tui.theme] in config")?;
                 tui::run(tui::TuiSession {
                     workdir: &workdir,
                     default_provider: provider,
@@ -51,6 +121,9 @@ pub fn run() -> Result<()> {
                     session_store: &session_store,
                     session_options,
                     initial_prompt: initial_prompt.as_deref(),
+                    chat_timeout,
+                    config_path: config_path.as_deref(),
+                    theme,
                 })?;
             } else {
                 interactive::run(interactive::InteractiveSession {
@@ -60,71 +133,101 @@ pub fn run() -> Result<()> {
                     session_store: &session_store,
                     session_options,
                     initial_prompt: initial_prompt.as_deref(),
+                    chat_timeout,
+                    config_path: config_path.as_deref(),
                 })?;
             }
         }
         Some(Command::Run(args)) => {
-            run_alias_prd(&plugin_registry, &workdir, provider, args)?;
+            return run_alias_prd(&plugin_registry, &workdir, dispatch_context, args);
         }
         Some(Command::Plugin(args)) => {
-            dispatch_plugin_tokens(&plugin_registry, &workdir, provider, args)?;
+            return dispatch_plugin_tokens(&plugin_registry, &workdir, dispatch_context, args);
         }
         Some(Command::Prd(args)) => {
-            let context = PluginDispatchContext {
-                default_provider: provider,
-            };
-            plugin_registry.execute(&workdir, "prd-runner", &args.tokens, context)?;
+            return plugin_registry.execute(&workdir, "prd-runner", &args.tokens, dispatch_context);
         }
-        Some(Command::Doctor) => {
-            run_doctor(&workdir)?;
+        Some(Command::Doctor(args)) => {
+            run_doctor(&workdir, args, &CommandProbe)?;
         }
         Some(Command::Session(args)) => {
             run_session_command(&session_store, args)?;
         }
+        Some(Command::Provider(args)) => match args.command {
+            ProviderCommand::Ping(ping_args) => {
+                let timeout = humantime::parse_duration(&ping_args.timeout)
+                    .with_context(|| format!("invalid --timeout: {}", ping_args.timeout))?;
+                ping::run_ping(&workdir, provider, timeout)?;
+            }
+        },
+        Some(Command::Ask(_)) => unreachable!("ask is handled before the session store opens"),
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 fn run_alias_prd(
     plugin_registry: &PluginRegistry,
     workdir: &Path,
-    provider: ProviderSelection,
+    context: PluginDispatchContext,
     args: RunArgs,
-) -> Result<()> {
-    let mut tokens = vec![
-        "run".to_string(),
-        "--max-runtime".to_string(),
-        args.max_runtime,
-    ];
+) -> Result<ExitCode> {
+    let mut tokens = vec!["run".to_string()];
+    if let Some(command) = args.provider_command {
+        tokens.push("--provider-command".to_string());
+        tokens.push(command);
+    }
+    if let Some(max_runtime) = args.max_runtime {
+        tokens.push("--max-runtime".to_string());
+        tokens.push(max_runtime);
+    }
     if let Some(timeout) = args.provider_timeout {
         tokens.push("--provider-timeout".to_string());
         tokens.push(timeout);
     }
+    if let Some(model) = args.model {
+        tokens.push("--model".to_string());
+        tokens.push(model);
+    }
     if args.dry_run {
         tokens.push("--dry-run".to_string());
     }
+    if args.preview_guards {
+        tokens.push("--preview-guards".to_string());
+    }
+    if args.plan_only {
+        tokens.push("--plan-only".to_string());
+    }
+    if let Some(prd) = args.prd {
+        tokens.push("--prd".to_string());
+        tokens.push(prd.to_string_lossy().to_string());
+    }
+    if let Some(run_id) = args.run_id {
+        tokens.push("--run-id".to_string());
+        tokens.push(run_id);
+    }
+    if args.output == OutputFormat::Json {
+        tokens.push("--output".to_string());
+        tokens.push("json".to_string());
+    }
 
-    let context = PluginDispatchContext {
-        default_provider: provider,
-    };
     plugin_registry.execute(workdir, "prd-runner", &tokens, context)
 }
 
 fn dispatch_plugin_tokens(
     plugin_registry: &PluginRegistry,
     workdir: &Path,
-    provider: ProviderSelection,
+    context: PluginDispatchContext,
     args: PluginArgs,
-) -> Result<()> {
+) -> Result<ExitCode> {
     if args.tokens.is_empty() {
         print_plugin_help();
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if args.tokens[0] == "list" {
         print_plugin_list(plugin_registry);
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if args.tokens.len() < 2 {
@@ -135,9 +238,6 @@ fn dispatch_plugin_tokens(
 
     let plugin_id = &args.tokens[0];
     let tokens = args.tokens[1..].to_vec();
-    let context = PluginDispatchContext {
-        default_provider: provider,
-    };
     plugin_registry.execute(workdir, plugin_id, &tokens, context)
 }
 
@@ -160,16 +260,31 @@ fn print_plugin_list(registry: &PluginRegistry) {
     }
 }
 
-fn run_doctor(workdir: &Path) -> Result<()> {
+fn run_doctor(workdir: &Path, args: DoctorArgs, probe: &dyn ProviderProbe) -> Result<()> {
     println!("autocode doctor");
     println!("- cwd: {}", workdir.display());
-    println!("- prd: {}", workdir.join("PRD.md").exists());
+    let prd_path = workdir.join("PRD.md");
+    println!("- prd: {}", prd_path.exists());
+    if prd_path.exists() {
+        match parse_prd_file_with_warnings(&prd_path) {
+            Ok((_, warnings)) => {
+                for warning in warnings {
+                    println!("- prd warning: {}", warning);
+                }
+            }
+            Err(err) => println!("- prd error: {:#}", err),
+        }
+    }
 
-    for provider in [ProviderKind::Claude, ProviderKind::Opencode] {
+    for provider in [
+        ProviderKind::Claude,
+        ProviderKind::Opencode,
+        ProviderKind::Ollama,
+    ] {
         println!(
             "- provider {} available: {}",
             provider.as_str(),
-            provider_available(provider)
+            probe.available(provider)
         );
     }
 
@@ -177,10 +292,91 @@ fn run_doctor(workdir: &Path) -> Result<()> {
     println!("- checkpoint root: {}", checkpoint_root.display());
     println!("- checkpoint root exists: {}", checkpoint_root.exists());
 
+    if args.fix {
+        let assume_yes = args.yes;
+        let actions = apply_doctor_fixes(workdir, &mut |question| {
+            assume_yes || confirm_on_terminal(question)
+        })?;
+        for action in actions {
+            println!("- fix: {}", action);
+        }
+    }
+
     info!("doctor completed");
     Ok(())
 }
 
+fn apply_doctor_fixes(
+    workdir: &Path,
+    confirm: &mut dyn FnMut(&str) -> bool,
+) -> Result<Vec<String>> {
+    let mut actions = Vec::new();
+
+    let autocode_dir = workdir.join(".autocode");
+    for dir in [autocode_dir.clone(), autocode_dir.join("checkpoints")] {
+        if dir.is_dir() {
+            actions.push(format!("kept {} (already exists)", dir.display()));
+            continue;
+        }
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        actions.push(format!("created {}", dir.display()));
+    }
+
+    let config_path = autocode_dir.join("config.toml");
+    if config_path.exists() {
+        actions.push(format!("kept {} (already exists)", config_path.display()));
+    } else {
+        let defaults = toml::to_string_pretty(&AppConfig::default())
+            .context("failed to serialize default config")?;
+        let content = format!(
+            "# autocode configuration (generated by `autocode doctor --fix`)\n\n{}",
+            defaults
+        );
+        std::fs::write(&config_path, content)
+            .with_context(|| format!("failed to write {}", config_path.display()))?;
+        actions.push(format!("created {}", config_path.display()));
+    }
+
+    let prd_path = workdir.join("PRD.md");
+    if prd_path.exists() {
+        actions.push(format!("kept {} (already exists)", prd_path.display()));
+    } else if confirm("PRD.md is missing. Scaffold a template with `prd init`?") {
+        init_prd_template(workdir)?;
+        actions.push(format!("created {}", prd_path.display()));
+    } else {
+        actions.push("skipped PRD.md scaffold (run `autocode prd init` later)".to_string());
+    }
+
+    Ok(actions)
+}
+
+fn confirm_on_terminal(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    print!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+fn fork_session(session_store: &SessionStore, options: OpenSessionOptions<'_>) -> Result<String> {
+    let source_id = match options.session_id {
+        Some(id) => id.to_string(),
+        None if options.continue_last => session_store
+            .latest_id()?
+            .context("--fork --continue: no previous session to fork")?,
+        None => bail!("--fork requires --continue or --session <id>"),
+    };
+    let forked = session_store.fork(&source_id)?;
+    info!(source = %source_id, session = %forked.id, "forked session");
+    Ok(forked.id)
+}
+
 fn run_session_command(session_store: &SessionStore, args: SessionArgs) -> Result<()> {
     match args.command {
         SessionCommand::List(list) => {
@@ -236,6 +432,25 @@ fn run_session_command(session_store: &SessionStore, args: SessionArgs) -> Resul
             session_store.delete(&delete.session_id)?;
             println!("Deleted session {}", delete.session_id);
         }
+        SessionCommand::Rename(rename) => {
+            session_store.rename(&rename.session_id, &rename.title)?;
+            println!("Renamed session {}", rename.session_id);
+        }
+        SessionCommand::Search(search) => {
+            let hits = session_store.search(&search.query, search.max_count)?;
+            if hits.is_empty() {
+                println!("No sessions match '{}'.", search.query.trim());
+                return Ok(());
+            }
+            for hit in hits {
+                println!(
+                    "{:<32}  {:<9}  {}",
+                    hit.session_id,
+                    hit.role.as_str(),
+                    hit.snippet
+                );
+            }
+        }
     }
     Ok(())
 }
@@ -250,10 +465,24 @@ fn init_tracing(verbose: bool) -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(false)
+        .with_writer(tracing_writer)
         .try_init()
         .map_err(|err| anyhow::anyhow!("failed to initialize logger: {}", err))
 }
 
+fn tracing_writer() -> Box<dyn Write> {
+    if TRACING_MUTED.load(Ordering::Relaxed) {
+        Box::new(io::sink())
+    } else {
+        // stderr, so `--output json` leaves stdout a single JSON document.
+        Box::new(io::stderr())
+    }
+}
+
+fn set_tracing_muted(muted: bool) {
+    TRACING_MUTED.store(muted, Ordering::Relaxed);
+}
+
 fn join_prompt_tokens(tokens: &[String]) -> Option<String> {
     if tokens.is_empty() {
         return None;
@@ -265,11 +494,33 @@ fn join_prompt_tokens(tokens: &[String]) -> Option<String> {
     Some(joined)
 }
 
+/// Precedence: `--provider` flag > `AUTOCODE_PROVIDER` > auto. `AUTOCODE_MODEL` is applied
+/// with the rest of the provider config when a PRD run starts.
+fn default_provider_selection(
+    flag: Option<ProviderArg>,
+    env: Option<&str>,
+) -> Result<ProviderSelection> {
+    if let Some(flag) = flag {
+        return Ok(to_provider_selection(flag));
+    }
+    match env.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => ProviderSelection::parse(value).ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid {}='{}' (expected auto|claude|opencode|ollama)",
+                PROVIDER_ENV,
+                value
+            )
+        }),
+        None => Ok(ProviderSelection::Auto),
+    }
+}
+
 fn to_provider_selection(input: ProviderArg) -> ProviderSelection {
     match input {
         ProviderArg::Auto => ProviderSelection::Auto,
         ProviderArg::Claude => ProviderSelection::Claude,
         ProviderArg::Opencode => ProviderSelection::Opencode,
+        ProviderArg::Ollama => ProviderSelection::Ollama,
     }
 }
 
@@ -279,3 +530,55 @@ fn print_cli_help() {
     let _ = command.print_help();
     println!();
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::{apply_doctor_fixes, default_provider_selection};
+    use crate::cli::args::ProviderArg;
+    use crate::provider::ProviderSelection;
+
+    #[test]
+    fn doctor_fix_scaffolds_missing_prerequisites() {
+        let tmp = TempDir::new().expect("tmp");
+        let actions = apply_doctor_fixes(tmp.path(), &mut |_| true).expect("fix");
+        assert!(actions.iter().all(|action| action.starts_with("created")));
+
+        assert!(tmp.path().join(".autocode/checkpoints").is_dir());
+        let config = std::fs::read_to_string(tmp.path().join(".autocode/config.toml"))
+            .expect("config should exist");
+        assert!(config.contains("[convergence]"));
+        assert!(tmp.path().join("PRD.md").exists());
+
+        let again = apply_doctor_fixes(tmp.path(), &mut |_| true).expect("fix again");
+        assert!(again.iter().all(|action| action.starts_with("kept")));
+    }
+
+    #[test]
+    fn doctor_fix_skips_prd_without_confirmation() {
+        let tmp = TempDir::new().expect("tmp");
+        let actions = apply_doctor_fixes(tmp.path(), &mut |_| false).expect("fix");
+        assert!(!tmp.path().join("PRD.md").exists());
+        assert!(actions
+            .iter()
+            .any(|action| action.starts_with("skipped PRD.md")));
+    }
+
+    #[test]
+    fn provider_env_applies_below_flag() {
+        assert_eq!(
+            default_provider_selection(None, None).expect("default"),
+            ProviderSelection::Auto
+        );
+        assert_eq!(
+            default_provider_selection(None, Some("opencode")).expect("env"),
+            ProviderSelection::Opencode
+        );
+        assert_eq!(
+            default_provider_selection(Some(ProviderArg::Claude), Some("opencode")).expect("flag"),
+            ProviderSelection::Claude
+        );
+        assert!(default_provider_selection(None, Some("gpt")).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::cli::interactive::{build_prompt, history_from_transcript};
+use crate::cli::session_store::{OpenSessionOptions, SessionHandle, SessionStore, StoredRole};
+use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+use crate::provider::{resolve_provider, ProviderSelection};
+
+pub struct PromptFileSession<'a> {
+    pub workdir: &'a Path,
+    pub default_provider: ProviderSelection,
+    pub session_store: &'a SessionStore,
+    pub session_options: OpenSessionOptions<'a>,
+    pub prompt_file: &'a Path,
+    pub chat_timeout: Duration,
+}
+
+pub fn run_prompt_file(session: PromptFileSession<'_>) -> Result<()> {
+    let prompt = read_prompt_file(session.prompt_file)?;
+    let mut provider_kind = resolve_provider(session.default_provider)?;
+    let handle = session.session_store.open_or_create(
+        session.session_options,
+        provider_kind.as_str(),
+        session.workdir,
+    )?;
+    if let Some(sel) = ProviderSelection::parse(&handle.transcript.provider) {
+        provider_kind = resolve_provider(sel)?;
+    }
+
+    let mut provider = CliPrintProvider::new(provider_kind.command().to_string(), session.workdir);
+    provider.start().context("failed to start provider")?;
+    let output = send_once(
+        &mut provider,
+        session.session_store,
+        &handle,
+        &prompt,
+        session.chat_timeout,
+    )?;
+    println!("{}", output);
+    Ok(())
+}
+
+pub struct AskSession<'a> {
+    pub workdir: &'a Path,
+    pub default_provider: ProviderSelection,
+    /// Where to record the exchange; `None` leaves `.autocode/sessions` untouched.
+    pub session: Option<(&'a SessionStore, OpenSessionOptions<'a>)>,
+    pub prompt: &'a str,
+    pub chat_timeout: Duration,
+}
+
+pub fn run_ask(session: AskSession<'_>) -> Result<()> {
+    if session.prompt.trim().is_empty() {
+        bail!("ask prompt is empty");
+    }
+    let mut provider_kind = resolve_provider(session.default_provider)?;
+    let handle = match session.session {
+        Some((store, options)) => {
+            let handle = store.open_or_create(options, provider_kind.as_str(), session.workdir)?;
+            if let Some(sel) = ProviderSelection::parse(&handle.transcript.provider) {
+                provider_kind = resolve_provider(sel)?;
+            }
+            Some((store, handle))
+        }
+        None => None,
+    };
+
+    let mut provider = CliPrintProvider::new(provider_kind.command().to_string(), session.workdir);
+    provider.start().context("failed to start provider")?;
+    let output = match &handle {
+        Some((store, handle)) => send_once(
+            &mut provider,
+            store,
+            handle,
+            session.prompt,
+            session.chat_timeout,
+        )?,
+        None => ask_once(&mut provider, session.prompt, session.chat_timeout)?,
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+/// Sends the bare prompt without history and returns the trimmed reply.
+fn ask_once(provider: &mut dyn Provider, input: &str, timeout: Duration) -> Result<String> {
+    provider
+        .send(input.trim())
+        .context("failed to send prompt to provider")?;
+    let output = provider
+        .read_output(timeout)
+        .context("failed to read provider output")?;
+    Ok(output.trim().to_string())
+}
+
+fn read_prompt_file(path: &Path) -> Result<String> {
+    let prompt = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read prompt file {}", path.display()))?;
+    if prompt.trim().is_empty() {
+        bail!("prompt file {} is empty", path.display());
+    }
+    Ok(prompt)
+}
+
+fn send_once(
+    provider: &mut dyn Provider,
+    session_store: &SessionStore,
+    handle: &SessionHandle,
+    input: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let history = history_from_transcript(&handle.transcript);
+    let prompt = build_prompt(&history, input.trim());
+    provider
+        .send(&prompt)
+        .context("failed to send prompt to provider")?;
+
+    session_store.append_message(&handle.id, StoredRole::User, input.trim())?;
+    let output = provider
+        .read_output(timeout)
+        .context("failed to read provider output")?;
+    let output = output.trim().to_string();
+    session_store.append_message(&handle.id, StoredRole::Assistant, &output)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::{ask_once, read_prompt_file, send_once};
+    use crate::cli::session_store::{SessionStore, StoredRole};
+    use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+
+    struct EchoProvider {
+        pending: Option<String>,
+    }
+
+    impl Provider for EchoProvider {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn start(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn restart(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn send(&mut self, input: &str) -> Result<()> {
+            self.pending = Some(input.to_string());
+            Ok(())
+        }
+
+        fn read_output(&mut self, _timeout: Duration) -> Result<String> {
+            Ok(format!("echo: {}", self.pending.take().unwrap_or_default()))
+        }
+
+        fn is_waiting_for_input(&self, _output: &str) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn sends_prompt_file_once_and_persists_exchange() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let prompt_path = tmp.path().join("prompt.txt");
+        std::fs::write(&prompt_path, "line one\nline two\n")?;
+
+        let store = SessionStore::new(tmp.path())?;
+        let handle = store.create("claude", tmp.path())?;
+        let mut provider = EchoProvider { pending: None };
+
+        let prompt = read_prompt_file(&prompt_path)?;
+        let output = send_once(
+            &mut provider,
+            &store,
+            &handle,
+            &prompt,
+            Duration::from_secs(5),
+        )?;
+        assert!(output.contains("line one\nline two"));
+
+        let saved = store.load(&handle.id)?;
+        assert_eq!(saved.transcript.messages.len(), 2);
+        assert_eq!(saved.transcript.messages[0].role, StoredRole::User);
+        assert_eq!(saved.transcript.messages[0].text, "line one\nline two");
+        assert_eq!(saved.transcript.messages[1].role, StoredRole::Assistant);
+        Ok(())
+    }
+
+    #[test]
+    fn ask_prints_provider_reply_without_creating_a_session() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let reply = tmp.path().join("reply.txt");
+        std::fs::write(&reply, "\n  forty-two  \n\n")?;
+
+        let mut provider = CliPrintProvider::new("cat".to_string(), tmp.path());
+        let output = ask_once(
+            &mut provider,
+            &format!(" {} ", reply.display()),
+            Duration::from_secs(5),
+        )?;
+        assert_eq!(output, "forty-two");
+        assert!(!tmp.path().join(".autocode").exists());
+
+        let missing = tmp.path().join("missing.txt").display().to_string();
+        let err = ask_once(&mut provider, &missing, Duration::from_secs(5))
+            .expect_err("cat on a missing file should fail");
+        assert!(format!("{:#}", err).contains("failed to read provider output"));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_empty_prompt_file() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let prompt_path = tmp.path().join("prompt.txt");
+        std::fs::write(&prompt_path, "  \n")?;
+        let err = read_prompt_file(&prompt_path).expect_err("empty prompt should fail");
+        assert!(err.to_string().contains("is empty"));
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+
+use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+use crate::plugin::prd_runner::loop_engine::driver::fatal_provider_stop_reason;
+use crate::provider::{resolve_provider, ProviderSelection};
+
+const PING_PROMPT: &str = "reply OK";
+
+#[derive(Debug)]
+pub struct PingReport {
+    pub latency: Duration,
+    pub reply: Option<String>,
+    pub error: Option<String>,
+    pub fatal: Option<String>,
+}
+
+pub fn run_ping(workdir: &Path, selection: ProviderSelection, timeout: Duration) -> Result<()> {
+    let kind = resolve_provider(selection)?;
+    let mut provider = CliPrintProvider::new(kind.command().to_string(), workdir);
+    let report = ping(&mut provider, timeout);
+
+    println!("provider ping");
+    println!("- provider: {}", kind.as_str());
+    println!("- latency: {}ms", report.latency.as_millis());
+    if let Some(reply) = &report.reply {
+        println!("- reply: {}", reply);
+    }
+    if let Some(fatal) = &report.fatal {
+        println!("- auth: {}", fatal);
+    }
+    if let Some(error) = &report.error {
+        bail!("provider {} ping failed: {}", kind.as_str(), error);
+    }
+    println!("- status: ok");
+    Ok(())
+}
+
+pub fn ping(provider: &mut dyn Provider, timeout: Duration) -> PingReport {
+    let started = Instant::now();
+    let result = provider
+        .start()
+        .and_then(|_| provider.send(PING_PROMPT))
+        .and_then(|_| provider.read_output(timeout));
+    let latency = started.elapsed();
+
+    match result {
+        Ok(reply) => PingReport {
+            latency,
+            reply: Some(reply.trim().to_string()),
+            error: None,
+            fatal: None,
+        },
+        Err(err) => {
+            let err_chain = err
+                .chain()
+                .map(|cause| cause.to_string())
+                .collect::<Vec<_>>()
+                .join(" | caused by: ");
+            PingReport {
+                latency,
+                reply: None,
+                fatal: fatal_provider_stop_reason(&err_chain),
+                error: Some(err_chain),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::{bail, Result};
+
+    use super::ping;
+    use crate::plugin::prd_runner::core::provider::Provider;
+
+    struct CannedProvider {
+        reply: Result<String, String>,
+        sent: Vec<String>,
+    }
+
+    impl Provider for CannedProvider {
+        fn name(&self) -> &str {
+            "canned"
+        }
+
+        fn start(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn restart(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn send(&mut self, input: &str) -> Result<()> {
+            self.sent.push(input.to_string());
+            Ok(())
+        }
+
+        fn read_output(&mut self, _timeout: Duration) -> Result<String> {
+            match &self.reply {
+                Ok(reply) => Ok(reply.clone()),
+                Err(err) => bail!("{}", err),
+            }
+        }
+
+        fn is_waiting_for_input(&self, _output: &str) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn ping_reports_reply_and_auth_errors() {
+        let mut provider = CannedProvider {
+            reply: Ok("OK\n".to_string()),
+            sent: Vec::new(),
+        };
+        let report = ping(&mut provider, Duration::from_secs(1));
+        assert!(report.error.is_none());
+        assert_eq!(report.reply.as_deref(), Some("OK"));
+        assert_eq!(provider.sent, vec!["reply OK".to_string()]);
+
+        let mut provider = CannedProvider {
+            reply: Err("claude returned error: Not logged in · Please run /login".to_string()),
+            sent: Vec::new(),
+        };
+        let report = ping(&mut provider, Duration::from_secs(1));
+        assert!(report.error.is_some());
+        assert!(report.fatal.is_some());
+    }
+}
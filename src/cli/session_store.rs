@@ -4,6 +4,8 @@ use anyhow::{anyhow, bail, Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::plugin::prd_runner::logger::redact::Redactor;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StoredRole {
@@ -13,6 +15,17 @@ pub enum StoredRole {
     Error,
 }
 
+impl StoredRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StoredRole::User => "user",
+            StoredRole::Assistant => "assistant",
+            StoredRole::System => "system",
+            StoredRole::Error => "error",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMessage {
     pub role: StoredRole,
@@ -38,6 +51,17 @@ pub struct SessionMeta {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
+    /// Set by `rename`; appending messages no longer re-derives the title.
+    #[serde(default)]
+    pub title_locked: bool,
+}
+
+/// First message in a session whose text contains the search query.
+#[derive(Debug, Clone)]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    pub role: StoredRole,
+    pub snippet: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -56,6 +80,7 @@ pub struct SessionHandle {
 pub struct SessionStore {
     root: PathBuf,
     index_path: PathBuf,
+    redactor: Redactor,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -76,7 +101,17 @@ impl SessionStore {
             std::fs::write(&index_path, content)
                 .with_context(|| format!("failed to write {}", index_path.display()))?;
         }
-        Ok(Self { root, index_path })
+        Ok(Self {
+            root,
+            index_path,
+            redactor: Redactor::default(),
+        })
+    }
+
+    /// Masks secrets in message text before it is written to the transcript.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
     }
 
     pub fn open_or_create(
@@ -90,7 +125,7 @@ impl SessionStore {
         }
 
         if options.continue_last {
-            if let Some(last_id) = self.read_index()?.latest {
+            if let Some(last_id) = self.latest_id()? {
                 return self.load(&last_id);
             }
         }
@@ -126,11 +161,7 @@ impl SessionStore {
     }
 
     pub fn load(&self, id: &str) -> Result<SessionHandle> {
-        let path = self.session_file(id);
-        let raw = std::fs::read_to_string(&path)
-            .with_context(|| format!("failed to read session {}", path.display()))?;
-        let transcript: SessionTranscript = serde_json::from_str(&raw)
-            .with_context(|| format!("failed to parse session {}", path.display()))?;
+        let transcript = self.read_transcript(id)?;
         self.touch_latest(&transcript.id)?;
         Ok(SessionHandle {
             id: transcript.id.clone(),
@@ -138,6 +169,25 @@ impl SessionStore {
         })
     }
 
+    /// Copies a session's messages into a new session, leaving the source untouched.
+    pub fn fork(&self, source_id: &str) -> Result<SessionHandle> {
+        let source = self.read_transcript(source_id)?;
+        let mut handle = self.create(&source.provider, Path::new(&source.workdir))?;
+        handle.transcript.messages = source.messages;
+        handle.transcript.updated_at = Utc::now();
+        self.write_transcript(&handle.transcript)?;
+        self.upsert_meta(
+            &handle.transcript,
+            derive_title(&handle.transcript.messages),
+            true,
+        )?;
+        Ok(handle)
+    }
+
+    pub fn latest_id(&self) -> Result<Option<String>> {
+        Ok(self.read_index()?.latest)
+    }
+
     pub fn append_message(&self, session_id: &str, role: StoredRole, text: &str) -> Result<()> {
         if text.is_empty() {
             return Ok(());
@@ -145,7 +195,7 @@ impl SessionStore {
         let mut handle = self.load(session_id)?;
         handle.transcript.messages.push(SessionMessage {
             role,
-            text: text.to_string(),
+            text: self.redactor.redact(text).into_owned(),
             timestamp: Utc::now(),
         });
         handle.transcript.updated_at = Utc::now();
@@ -181,6 +231,47 @@ impl SessionStore {
         Ok(sessions.into_iter().take(limit).collect())
     }
 
+    /// Case-insensitive substring search over message text, newest sessions first.
+    /// Transcripts are read one at a time and only until `limit` hits are found (0 = all).
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+        let query = query.trim();
+        if query.is_empty() {
+            bail!("search query must not be empty");
+        }
+        let needle = query.to_lowercase();
+
+        let mut hits = Vec::new();
+        for meta in self.list_recent(0)? {
+            if limit > 0 && hits.len() >= limit {
+                break;
+            }
+            let transcript = self.read_transcript(&meta.id)?;
+            let hit = transcript.messages.iter().find_map(|message| {
+                match_snippet(&message.text, &needle).map(|snippet| SessionSearchHit {
+                    session_id: transcript.id.clone(),
+                    role: message.role,
+                    snippet,
+                })
+            });
+            hits.extend(hit);
+        }
+        Ok(hits)
+    }
+
+    pub fn rename(&self, session_id: &str, title: &str) -> Result<()> {
+        let title = title.trim();
+        if title.is_empty() {
+            bail!("session title must not be empty");
+        }
+        let mut index = self.read_index()?;
+        let Some(meta) = index.sessions.iter_mut().find(|v| v.id == session_id) else {
+            bail!("session not found: {}", session_id);
+        };
+        meta.title = title.to_string();
+        meta.title_locked = true;
+        self.write_index(&index)
+    }
+
     pub fn delete(&self, session_id: &str) -> Result<()> {
         let mut index = self.read_index()?;
         let before = index.sessions.len();
@@ -233,16 +324,21 @@ impl SessionStore {
         mark_latest: bool,
     ) -> Result<()> {
         let mut index = self.read_index()?;
-        let meta = SessionMeta {
+        let mut meta = SessionMeta {
             id: transcript.id.clone(),
             title,
             provider: transcript.provider.clone(),
             created_at: transcript.created_at,
             updated_at: transcript.updated_at,
             message_count: transcript.messages.len(),
+            title_locked: false,
         };
 
         if let Some(existing) = index.sessions.iter_mut().find(|v| v.id == transcript.id) {
+            if existing.title_locked {
+                meta.title = std::mem::take(&mut existing.title);
+                meta.title_locked = true;
+            }
             *existing = meta;
         } else {
             index.sessions.push(meta);
@@ -255,6 +351,14 @@ impl SessionStore {
         self.write_index(&index)
     }
 
+    fn read_transcript(&self, id: &str) -> Result<SessionTranscript> {
+        let path = self.session_file(id);
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read session {}", path.display()))?;
+        serde_json::from_str(&raw)
+            .with_context(|| format!("failed to parse session {}", path.display()))
+    }
+
     fn write_transcript(&self, transcript: &SessionTranscript) -> Result<()> {
         let path = self.session_file(&transcript.id);
         let raw = serde_json::to_string_pretty(transcript)
@@ -267,6 +371,35 @@ impl SessionStore {
     }
 }
 
+const SNIPPET_CONTEXT_CHARS: usize = 30;
+
+/// One-line excerpt around the first occurrence of the lowercase `needle` in `text`.
+fn match_snippet(text: &str, needle: &str) -> Option<String> {
+    let chars = text
+        .chars()
+        .map(|ch| if ch.is_whitespace() { ' ' } else { ch })
+        .collect::<Vec<_>>();
+    let lowered = chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect::<Vec<_>>();
+    let needle = needle.chars().collect::<Vec<_>>();
+    let start = lowered
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())?;
+
+    let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let to = (start + needle.len() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    let mut snippet = chars[from..to].iter().collect::<String>();
+    if from > 0 {
+        snippet.insert_str(0, "...");
+    }
+    if to < chars.len() {
+        snippet.push_str("...");
+    }
+    Some(snippet)
+}
+
 fn derive_title(messages: &[SessionMessage]) -> String {
     let user = messages
         .iter()
@@ -287,6 +420,7 @@ mod tests {
     use tempfile::TempDir;
 
     use super::{OpenSessionOptions, SessionStore, StoredRole};
+    use crate::plugin::prd_runner::logger::redact::{default_redact_patterns, Redactor};
 
     #[test]
     fn creates_and_continues_latest_session() {
@@ -319,6 +453,125 @@ mod tests {
         assert_eq!(resumed.transcript.messages.len(), 1);
     }
 
+    #[test]
+    fn redacts_secrets_before_persisting_messages() {
+        let tmp = TempDir::new().expect("tmp");
+        let redactor = Redactor::new(&default_redact_patterns()).expect("redactor");
+        let store = SessionStore::new(tmp.path())
+            .expect("store")
+            .with_redactor(redactor);
+        let created = store
+            .open_or_create(
+                OpenSessionOptions {
+                    continue_last: false,
+                    session_id: None,
+                },
+                "claude",
+                tmp.path(),
+            )
+            .expect("create");
+        store
+            .append_message(
+                &created.id,
+                StoredRole::User,
+                "use key sk-ABCDEFGHIJKLMNOPQRSTUV please",
+            )
+            .expect("append");
+
+        let raw = std::fs::read_to_string(
+            tmp.path()
+                .join(format!(".autocode/sessions/{}.json", created.id)),
+        )
+        .expect("transcript");
+        assert!(raw.contains("use key *** please"));
+        assert!(!raw.contains("sk-ABCDEF"));
+    }
+
+    #[test]
+    fn search_returns_only_sessions_containing_query() {
+        let tmp = TempDir::new().expect("tmp");
+        let store = SessionStore::new(tmp.path()).expect("store");
+        let a = store.create("claude", tmp.path()).expect("a");
+        let b = store.create("claude", tmp.path()).expect("b");
+        store
+            .append_message(&a.id, StoredRole::User, "how do I parse TOML?")
+            .expect("append");
+        store
+            .append_message(&b.id, StoredRole::User, "hello")
+            .expect("append");
+        store
+            .append_message(
+                &b.id,
+                StoredRole::Assistant,
+                "Use the Serde derive with toml::from_str to load Cargo.toml",
+            )
+            .expect("append");
+
+        let hits = store.search("TOML::FROM", 10).expect("search");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, b.id);
+        assert_eq!(hits[0].role, StoredRole::Assistant);
+        assert!(hits[0].snippet.contains("toml::from_str"));
+
+        assert_eq!(store.search("toml", 0).expect("search").len(), 2);
+        assert_eq!(store.search("toml", 1).expect("search").len(), 1);
+        assert!(store.search("missing", 10).expect("search").is_empty());
+        assert!(store.search("  ", 10).is_err());
+    }
+
+    #[test]
+    fn renamed_title_survives_new_messages() {
+        let tmp = TempDir::new().expect("tmp");
+        let store = SessionStore::new(tmp.path()).expect("store");
+        let session = store.create("claude", tmp.path()).expect("create");
+        store
+            .append_message(&session.id, StoredRole::User, "first prompt")
+            .expect("append");
+        assert_eq!(store.list_recent(1).expect("list")[0].title, "first prompt");
+
+        store
+            .rename(&session.id, "  auth refactor ")
+            .expect("rename");
+        store
+            .append_message(&session.id, StoredRole::Assistant, "reply")
+            .expect("append");
+        let meta = &store.list_recent(1).expect("list")[0];
+        assert_eq!(meta.title, "auth refactor");
+        assert!(meta.title_locked);
+        assert_eq!(meta.message_count, 2);
+
+        assert!(store.rename(&session.id, " ").is_err());
+        assert!(store.rename("ses_missing", "x").is_err());
+    }
+
+    #[test]
+    fn fork_copies_messages_into_new_session() {
+        let tmp = TempDir::new().expect("tmp");
+        let store = SessionStore::new(tmp.path()).expect("store");
+        let source = store.create("opencode", tmp.path()).expect("source");
+        store
+            .append_message(&source.id, StoredRole::User, "hello")
+            .expect("append");
+        store
+            .append_message(&source.id, StoredRole::Assistant, "hi")
+            .expect("append");
+
+        let forked = store.fork(&source.id).expect("fork");
+        assert_ne!(forked.id, source.id);
+        assert_eq!(forked.transcript.provider, "opencode");
+        assert_eq!(forked.transcript.messages.len(), 2);
+        assert_eq!(store.latest_id().expect("latest"), Some(forked.id.clone()));
+
+        store
+            .append_message(&forked.id, StoredRole::User, "only in fork")
+            .expect("append");
+        let original = store.load(&source.id).expect("load source");
+        let copy = store.load(&forked.id).expect("load fork");
+        assert_eq!(original.transcript.messages.len(), 2);
+        assert_eq!(copy.transcript.messages.len(), 3);
+        assert_eq!(store.list_recent(0).expect("list").len(), 2);
+    }
+
     #[test]
     fn lists_and_deletes_sessions() {
         let tmp = TempDir::new().expect("tmp");
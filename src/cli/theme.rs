@@ -0,0 +1,144 @@
+use anyhow::{anyhow, bail, Result};
+use crossterm::style::Color;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub header: Color,
+    pub status: Color,
+    pub separator: Color,
+    pub input: Color,
+    pub user: Color,
+    pub assistant: Color,
+    pub system: Color,
+    pub error: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header: Color::Cyan,
+            status: Color::DarkGrey,
+            separator: Color::DarkGrey,
+            input: Color::White,
+            user: Color::Cyan,
+            assistant: Color::Green,
+            system: Color::Blue,
+            error: Color::Red,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ThemeSection {
+    header: Option<String>,
+    status: Option<String>,
+    separator: Option<String>,
+    input: Option<String>,
+    user: Option<String>,
+    assistant: Option<String>,
+    system: Option<String>,
+    error: Option<String>,
+}
+
+impl Theme {
+    pub fn with_overrides(mut self, section: &ThemeSection) -> Result<Self> {
+        let slots = [
+            (&mut self.header, &section.header),
+            (&mut self.status, &section.status),
+            (&mut self.separator, &section.separator),
+            (&mut self.input, &section.input),
+            (&mut self.user, &section.user),
+            (&mut self.assistant, &section.assistant),
+            (&mut self.system, &section.system),
+            (&mut self.error, &section.error),
+        ];
+        for (slot, value) in slots {
+            if let Some(raw) = value {
+                *slot = parse_color(raw)?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Accepts crossterm color names (`dark_grey`, `cyan`, ...), `#rrggbb` and ANSI indexes.
+fn parse_color(raw: &str) -> Result<Color> {
+    let value = raw.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            bail!("invalid hex color '{}', expected #rrggbb", raw);
+        }
+        let channel = |range: std::ops::Range<usize>| {
+            u8::from_str_radix(&hex[range], 16).map_err(|_| anyhow!("invalid hex color '{}'", raw))
+        };
+        return Ok(Color::Rgb {
+            r: channel(0..2)?,
+            g: channel(2..4)?,
+            b: channel(4..6)?,
+        });
+    }
+
+    if let Ok(index) = value.parse::<u8>() {
+        return Ok(Color::AnsiValue(index));
+    }
+
+    let normalized = value.to_ascii_lowercase().replace(['-', ' '], "_");
+    let normalized = match normalized.as_str() {
+        "gray" => "grey".to_string(),
+        "dark_gray" | "darkgray" | "darkgrey" => "dark_grey".to_string(),
+        _ => normalized,
+    };
+    Color::try_from(normalized.as_str()).map_err(|_| anyhow!("unknown color '{}'", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+    use tempfile::TempDir;
+
+    use super::{parse_color, Theme};
+    use crate::cli::config::CliConfig;
+
+    #[test]
+    fn configured_color_overrides_default_role() {
+        let tmp = TempDir::new().expect("tmp");
+        std::fs::create_dir_all(tmp.path().join(".autocode")).expect("mkdir");
+        std::fs::write(
+            tmp.path().join(".autocode/config.toml"),
+            "[tui.theme]\nassistant = \"magenta\"\nerror = \"#ff8800\"\n",
+        )
+        .expect("write config");
+
+        let config = CliConfig::load_from_dir(tmp.path()).expect("config");
+        let theme = Theme::default()
+            .with_overrides(&config.tui.theme)
+            .expect("theme");
+        assert_eq!(theme.assistant, Color::Magenta);
+        assert_eq!(
+            theme.error,
+            Color::Rgb {
+                r: 0xff,
+                g: 0x88,
+                b: 0x00
+            }
+        );
+        assert_eq!(theme.user, Theme::default().user);
+    }
+
+    #[test]
+    fn missing_config_uses_default_palette() {
+        let tmp = TempDir::new().expect("tmp");
+        let config = CliConfig::load_from_dir(tmp.path()).expect("config");
+        let theme = Theme::default()
+            .with_overrides(&config.tui.theme)
+            .expect("theme");
+        assert_eq!(theme, Theme::default());
+    }
+
+    #[test]
+    fn rejects_unknown_color_names() {
+        assert!(parse_color("dark gray").is_ok());
+        assert!(parse_color("ultraviolet").is_err());
+    }
+}
@@ -6,24 +6,33 @@ use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use crossterm::cursor;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers,
+};
 use crossterm::execute;
 use crossterm::queue;
 use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
 use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
 
 use crate::cli::session_store::{OpenSessionOptions, SessionStore, SessionTranscript, StoredRole};
-use crate::cli::utils::split_command_tokens;
+use crate::cli::set_tracing_muted;
+use crate::cli::theme::Theme;
+use crate::cli::utils::{provider_error_message, split_command_tokens};
+use crate::plugin::prd_runner::app::{run_with_observers, PrdRunOptions};
 use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+use crate::plugin::prd_runner::logger::event::{EngineEvent, EventObservers};
 use crate::plugin::prd_runner::PluginDispatchContext;
 use crate::plugin::registry::PluginRegistry;
 use crate::provider::{resolve_provider, ProviderKind, ProviderSelection};
 use crate::runtime::signal;
 
-const CHAT_TIMEOUT: Duration = Duration::from_secs(600);
 const TICK_INTERVAL: Duration = Duration::from_millis(80);
 const HISTORY_LIMIT: usize = 12;
 const MESSAGE_LIMIT: usize = 400;
+const EVENT_TAIL_LIMIT: usize = 16;
+const INPUT_HISTORY_LIMIT: usize = 100;
+const MESSAGE_TOP_ROW: usize = 3;
 const WAIT_FRAMES: [&str; 4] = ["-", "\\", "|", "/"];
 
 #[derive(Debug, Clone, Copy)]
@@ -46,6 +55,74 @@ struct PendingResponse {
     rx: mpsc::Receiver<std::result::Result<String, String>>,
 }
 
+#[derive(Debug)]
+struct PrdRunHandle {
+    events: mpsc::Receiver<EngineEvent>,
+    done: mpsc::Receiver<std::result::Result<String, String>>,
+}
+
+/// Most recent engine events of the PRD loop running inside the TUI.
+#[derive(Debug, Default)]
+struct EventTail {
+    events: VecDeque<EngineEvent>,
+}
+
+impl EventTail {
+    fn push(&mut self, event: EngineEvent) {
+        self.events.push_back(event);
+        while self.events.len() > EVENT_TAIL_LIMIT {
+            let _ = self.events.pop_front();
+        }
+    }
+
+    fn latest(&self) -> Option<&EngineEvent> {
+        self.events.back()
+    }
+}
+
+/// Inputs submitted in this TUI session, recalled with Up/Down.
+#[derive(Debug, Default)]
+struct InputHistory {
+    entries: VecDeque<String>,
+    cursor: Option<usize>,
+}
+
+impl InputHistory {
+    fn push(&mut self, input: &str) {
+        self.cursor = None;
+        if input.is_empty() || self.entries.back().is_some_and(|last| last == input) {
+            return;
+        }
+        self.entries.push_back(input.to_string());
+        while self.entries.len() > INPUT_HISTORY_LIMIT {
+            let _ = self.entries.pop_front();
+        }
+    }
+
+    /// Steps to an older entry, staying on the oldest once reached.
+    fn previous(&mut self) -> Option<&str> {
+        let last = self.entries.len().checked_sub(1)?;
+        let index = match self.cursor {
+            None => last,
+            Some(index) => index.saturating_sub(1),
+        };
+        self.cursor = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Steps to a newer entry; past the newest it yields a blank line and stops browsing.
+    fn next(&mut self) -> Option<&str> {
+        let index = self.cursor? + 1;
+        if index < self.entries.len() {
+            self.cursor = Some(index);
+            self.entries.get(index).map(String::as_str)
+        } else {
+            self.cursor = None;
+            Some("")
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct RenderLine {
     color: Color,
@@ -61,18 +138,33 @@ struct App<'a> {
     session_store: &'a SessionStore,
     session_id: String,
     messages: Vec<Message>,
+    /// Wrapped transcript lines hidden below the viewport; 0 follows the newest output.
+    scroll_offset: usize,
     input: String,
+    input_history: InputHistory,
     pending: Option<PendingResponse>,
+    prd_run: Option<PrdRunHandle>,
+    events: EventTail,
     status: String,
+    theme: Theme,
+    chat_timeout: Duration,
+    config_path: Option<&'a Path>,
     quitting: bool,
 }
 
 impl<'a> App<'a> {
     fn push_message(&mut self, role: MessageRole, text: impl Into<String>) {
-        self.messages.push(Message {
+        let message = Message {
             role,
             text: text.into(),
-        });
+        };
+        if self.scroll_offset > 0 {
+            // Keep the scrolled-up view in place while new output arrives below it.
+            if let Ok((width, _)) = terminal::size() {
+                self.scroll_offset += count_render_lines(std::slice::from_ref(&message), width);
+            }
+        }
+        self.messages.push(message);
         if self.messages.len() > MESSAGE_LIMIT {
             let drop_count = self.messages.len().saturating_sub(MESSAGE_LIMIT);
             self.messages.drain(0..drop_count);
@@ -103,7 +195,9 @@ struct TerminalGuard {
 impl TerminalGuard {
     fn enter() -> Result<Self> {
         terminal::enable_raw_mode().context("failed to enable raw mode")?;
-        execute!(io::stdout(), EnterAlternateScreen).context("failed to enter alternate screen")?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)
+            .context("failed to enter alternate screen")?;
+        set_tracing_muted(true);
         Ok(Self { active: true })
     }
 
@@ -112,7 +206,9 @@ impl TerminalGuard {
             return Ok(());
         }
         terminal::disable_raw_mode().context("failed to disable raw mode")?;
-        execute!(io::stdout(), LeaveAlternateScreen).context("failed to leave alternate screen")?;
+        execute!(io::stdout(), DisableBracketedPaste, LeaveAlternateScreen)
+            .context("failed to leave alternate screen")?;
+        set_tracing_muted(false);
         self.active = false;
         Ok(())
     }
@@ -121,8 +217,10 @@ impl TerminalGuard {
         if self.active {
             return Ok(());
         }
-        execute!(io::stdout(), EnterAlternateScreen).context("failed to enter alternate screen")?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)
+            .context("failed to enter alternate screen")?;
         terminal::enable_raw_mode().context("failed to enable raw mode")?;
+        set_tracing_muted(true);
         self.active = true;
         Ok(())
     }
@@ -131,8 +229,9 @@ impl TerminalGuard {
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
         if self.active {
-            let _ = execute!(io::stdout(), LeaveAlternateScreen);
+            let _ = execute!(io::stdout(), DisableBracketedPaste, LeaveAlternateScreen);
             let _ = terminal::disable_raw_mode();
+            set_tracing_muted(false);
         }
     }
 }
@@ -144,6 +243,9 @@ pub struct TuiSession<'a> {
     pub session_store: &'a SessionStore,
     pub session_options: OpenSessionOptions<'a>,
     pub initial_prompt: Option<&'a str>,
+    pub chat_timeout: Duration,
+    pub config_path: Option<&'a Path>,
+    pub theme: Theme,
 }
 
 pub fn run(session: TuiSession<'_>) -> Result<()> {
@@ -169,9 +271,16 @@ pub fn run(session: TuiSession<'_>) -> Result<()> {
         session_store: session.session_store,
         session_id: opened.id.clone(),
         messages: transcript_to_messages(&opened.transcript),
+        scroll_offset: 0,
         input: String::new(),
+        input_history: InputHistory::default(),
         pending: None,
+        prd_run: None,
+        events: EventTail::default(),
         status: "ready".to_string(),
+        theme: session.theme,
+        chat_timeout: session.chat_timeout,
+        config_path: session.config_path,
         quitting: false,
     };
     app.push_system("Welcome to AUTO-CODE TUI. /help for commands.");
@@ -188,22 +297,30 @@ pub fn run(session: TuiSession<'_>) -> Result<()> {
     }
     loop {
         poll_pending_response(&mut app);
+        poll_prd_run(&mut app);
         render(&app)?;
 
-        if app.quitting || signal::interrupted() {
+        if app.quitting || (signal::interrupted() && app.prd_run.is_none()) {
             break;
         }
 
         if event::poll(TICK_INTERVAL).context("failed to poll terminal event")? {
             let event = event::read().context("failed to read terminal event")?;
-            if let Event::Key(key) = event {
-                if key.kind == KeyEventKind::Press {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
                     handle_key(key, &mut app, &mut guard)?;
                 }
+                Event::Paste(text) => app.input.push_str(&text.replace("\r\n", "\n")),
+                _ => {}
             }
         }
     }
 
+    if let Some(run) = app.prd_run.take() {
+        // Let the loop stop at its next interrupt check so it releases the run lock.
+        signal::request_interrupt();
+        let _ = run.done.recv();
+    }
     guard.suspend()?;
     println!("AUTO-CODE exited.");
     Ok(())
@@ -217,32 +334,97 @@ fn start_provider(kind: ProviderKind, workdir: &Path) -> Result<CliPrintProvider
 
 fn handle_key(key: KeyEvent, app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
     if key.modifiers.contains(KeyModifiers::CONTROL) && matches!(key.code, KeyCode::Char('c')) {
+        if app.prd_run.is_some() {
+            signal::request_interrupt();
+            app.status = "stopping prd loop...".to_string();
+            return Ok(());
+        }
         app.quitting = true;
         return Ok(());
     }
 
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
     match key.code {
-        KeyCode::Enter => submit_input(app, guard)?,
-        KeyCode::Backspace => {
-            app.input.pop();
+        KeyCode::PageUp => scroll_transcript(app, ScrollStep::PageUp)?,
+        KeyCode::PageDown => scroll_transcript(app, ScrollStep::PageDown)?,
+        KeyCode::Up if shift => scroll_transcript(app, ScrollStep::LineUp)?,
+        KeyCode::Down if shift => scroll_transcript(app, ScrollStep::LineDown)?,
+        KeyCode::Up if !shift => {
+            if let Some(entry) = app.input_history.previous() {
+                app.input = entry.to_string();
+            }
         }
-        KeyCode::Esc => {
-            app.input.clear();
+        KeyCode::Down if !shift => {
+            if let Some(entry) = app.input_history.next() {
+                app.input = entry.to_string();
+            }
         }
-        KeyCode::Char(ch) => {
-            app.input.push(ch);
+        _ => {
+            if edit_input(&mut app.input, key) {
+                submit_input(app, guard)?;
+            }
         }
+    }
+    Ok(())
+}
+
+/// Applies an editing key to the input buffer and returns whether it should be submitted.
+/// Shift+Enter (or Alt+Enter on terminals that do not report Shift) inserts a newline.
+fn edit_input(input: &mut String, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Enter
+            if key
+                .modifiers
+                .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+        {
+            input.push('\n');
+        }
+        KeyCode::Enter => return true,
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Esc => input.clear(),
+        KeyCode::Char(ch) => input.push(ch),
         _ => {}
     }
+    false
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ScrollStep {
+    PageUp,
+    PageDown,
+    LineUp,
+    LineDown,
+}
+
+fn scroll_transcript(app: &mut App<'_>, step: ScrollStep) -> Result<()> {
+    let (width, height) = terminal::size().context("failed to read terminal size")?;
+    let viewport = message_viewport_height(height);
+    let offset = match step {
+        ScrollStep::PageUp => app.scroll_offset.saturating_add(viewport),
+        ScrollStep::PageDown => app.scroll_offset.saturating_sub(viewport),
+        ScrollStep::LineUp => app.scroll_offset.saturating_add(1),
+        ScrollStep::LineDown => app.scroll_offset.saturating_sub(1),
+    };
+    let total_lines = count_render_lines(&app.messages, width);
+    app.scroll_offset = clamp_scroll_offset(offset, total_lines, viewport);
     Ok(())
 }
 
+/// Largest offset that still fills the viewport, so scrolling stops at the first line.
+fn clamp_scroll_offset(offset: usize, total_lines: usize, viewport: usize) -> usize {
+    offset.min(total_lines.saturating_sub(viewport))
+}
+
 fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
+    app.scroll_offset = 0;
     let raw_input = std::mem::take(&mut app.input);
     let input = raw_input.trim();
     if input.is_empty() {
         return Ok(());
     }
+    app.input_history.push(input);
 
     if input == "/exit" || input == "/quit" {
         app.quitting = true;
@@ -251,7 +433,7 @@ fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
 
     if input == "/help" {
         app.push_system(
-            "/help /exit /provider auto|claude|opencode /plugin <id> <cmd> /prd <cmd> /clear /session /sessions /resume [id]",
+            "/help /exit /provider auto|claude|opencode|ollama /plugin <id> <cmd> /prd <cmd> /run /clear /session /sessions /resume [id]",
         );
         return Ok(());
     }
@@ -332,8 +514,14 @@ fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
         return Ok(());
     }
 
+    if input == "/run" {
+        start_prd_run(app);
+        return Ok(());
+    }
+
     if input == "/clear" {
         app.messages.clear();
+        app.scroll_offset = 0;
         app.push_system("history cleared");
         return Ok(());
     }
@@ -344,7 +532,7 @@ fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
             return Ok(());
         }
         let Some(selection) = ProviderSelection::parse(rest.trim()) else {
-            app.push_error("invalid provider, expected auto|claude|opencode");
+            app.push_error("invalid provider, expected auto|claude|opencode|ollama");
             return Ok(());
         };
         let kind = resolve_provider(selection)?;
@@ -414,11 +602,14 @@ fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
     }
 
     let provider = Arc::clone(&app.provider);
+    let timeout = app.chat_timeout;
     let (tx, rx) = mpsc::channel();
     std::thread::spawn(move || {
         let result = match provider.lock() {
-            Ok(mut handle) => handle.read_output(CHAT_TIMEOUT).map_err(|e| e.to_string()),
-            Err(_) => Err("provider state lock poisoned".to_string()),
+            Ok(mut handle) => handle
+                .read_output(timeout)
+                .map_err(|e| provider_error_message(&e)),
+            Err(_) => Err("provider error: provider state lock poisoned".to_string()),
         };
         let _ = tx.send(result);
     });
@@ -430,6 +621,72 @@ fn submit_input(app: &mut App<'_>, guard: &mut TerminalGuard) -> Result<()> {
     Ok(())
 }
 
+fn start_prd_run(app: &mut App<'_>) {
+    if app.pending.is_some() {
+        app.push_error("provider is busy; wait for current response");
+        return;
+    }
+    if app.prd_run.is_some() {
+        app.push_error("prd loop is already running");
+        return;
+    }
+
+    let (event_tx, events) = mpsc::channel();
+    let mut observers = EventObservers::default();
+    observers.push(Arc::new(move |event: &EngineEvent| {
+        let _ = event_tx.send(event.clone());
+    }));
+
+    let workdir = app.workdir.to_path_buf();
+    let options = PrdRunOptions {
+        provider: app.provider_selection,
+        config_path: app.config_path.map(Path::to_path_buf),
+        no_progress: true,
+        ..PrdRunOptions::default()
+    };
+    let (done_tx, done) = mpsc::channel();
+    std::thread::spawn(move || {
+        let result = run_with_observers(&workdir, options, observers)
+            .map(|output| {
+                format!(
+                    "prd loop finished: completed={} iterations={} stop_reason={}",
+                    output.summary.completed,
+                    output.summary.iterations,
+                    output.summary.stop_reason.as_deref().unwrap_or("-")
+                )
+            })
+            .map_err(|err| format!("prd error: {:#}", err));
+        let _ = done_tx.send(result);
+    });
+
+    app.events = EventTail::default();
+    app.prd_run = Some(PrdRunHandle { events, done });
+    app.status = "prd running".to_string();
+    app.push_system("prd loop started (Ctrl+C stops the loop)");
+}
+
+fn poll_prd_run(app: &mut App<'_>) {
+    let Some(run) = app.prd_run.as_ref() else {
+        return;
+    };
+    while let Ok(event) = run.events.try_recv() {
+        app.events.push(event);
+    }
+
+    let finished = match run.done.try_recv() {
+        Ok(result) => result,
+        Err(mpsc::TryRecvError::Disconnected) => Err("prd loop thread exited".to_string()),
+        Err(mpsc::TryRecvError::Empty) => return,
+    };
+    match finished {
+        Ok(line) => app.push_system(line),
+        Err(err) => app.push_error(err),
+    }
+    app.prd_run = None;
+    app.status = "ready".to_string();
+    signal::reset_interrupted();
+}
+
 fn run_plugin_command(
     app: &mut App<'_>,
     guard: &mut TerminalGuard,
@@ -440,6 +697,10 @@ fn run_plugin_command(
         app.push_error("provider is busy; wait for current response");
         return Ok(());
     }
+    if app.prd_run.is_some() {
+        app.push_error("prd loop is running; wait for it to finish or press Ctrl+C");
+        return Ok(());
+    }
 
     guard.suspend()?;
     println!(
@@ -449,6 +710,7 @@ fn run_plugin_command(
     );
     let context = PluginDispatchContext {
         default_provider: app.provider_selection,
+        config_path: app.config_path.map(Path::to_path_buf),
     };
     let result = app
         .plugin_registry
@@ -487,7 +749,7 @@ fn poll_pending_response(app: &mut App<'_>) {
             app.pending = None;
         }
         Ok(Err(err)) => {
-            app.push_error(format!("provider error: {}", err));
+            app.push_error(err);
             app.status = "error".to_string();
             app.pending = None;
         }
@@ -511,38 +773,40 @@ fn render(app: &App<'_>) -> Result<()> {
         app.provider_kind.as_str(),
         app.workdir.display()
     );
-    draw_line(&mut out, 0, width, &header, Color::Cyan)?;
+    draw_line(&mut out, 0, width, &header, app.theme.header)?;
 
-    let status_text = if let Some(pending) = app.pending.as_ref() {
+    let status = if let Some(pending) = app.pending.as_ref() {
         let elapsed = pending.started.elapsed();
         let frame = WAIT_FRAMES[(elapsed.as_millis() as usize / 200) % WAIT_FRAMES.len()];
-        format!(
-            "status=thinking {} elapsed={}s  (/help for commands)",
-            frame,
-            elapsed.as_secs()
-        )
+        format!("thinking {} elapsed={}s", frame, elapsed.as_secs())
     } else {
-        format!("status={}  (/help for commands)", app.status)
+        app.status.clone()
     };
-    draw_line(&mut out, 1, width, &status_text, Color::DarkGrey)?;
+    let status_text = status_line(&status, app.events.latest());
+    draw_line(&mut out, 1, width, &status_text, app.theme.status)?;
     draw_line(
         &mut out,
         2,
         width,
         &"-".repeat(width as usize),
-        Color::DarkGrey,
+        app.theme.separator,
     )?;
 
     let input_separator_row = height.saturating_sub(2);
     let input_row = height.saturating_sub(1);
-    let message_top = 3usize;
-    let message_height = (input_separator_row as usize).saturating_sub(message_top);
-
-    let lines = collect_recent_render_lines(&app.messages, width as usize, message_height);
+    let message_height = message_viewport_height(height);
+
+    let lines = collect_recent_render_lines(
+        &app.messages,
+        &app.theme,
+        width as usize,
+        message_height,
+        app.scroll_offset,
+    );
     for (idx, line) in lines.iter().enumerate() {
         draw_line(
             &mut out,
-            (message_top + idx) as u16,
+            (MESSAGE_TOP_ROW + idx) as u16,
             width,
             &line.text,
             line.color,
@@ -554,14 +818,15 @@ fn render(app: &App<'_>) -> Result<()> {
         input_separator_row,
         width,
         &"-".repeat(width as usize),
-        Color::DarkGrey,
+        app.theme.separator,
     )?;
 
-    let prompt = if app.pending.is_some() { "… " } else { "> " };
+    let prompt = input_prompt(app.pending.is_some(), &app.input);
+    let last_line = app.input.rsplit('\n').next().unwrap_or_default();
     let max_input_width = (width as usize).saturating_sub(prompt.chars().count());
-    let input_tail = tail_chars(&app.input, max_input_width);
+    let input_tail = tail_chars(last_line, max_input_width);
     let input_text = format!("{}{}", prompt, input_tail);
-    draw_line(&mut out, input_row, width, &input_text, Color::White)?;
+    draw_line(&mut out, input_row, width, &input_text, app.theme.input)?;
 
     let cursor_x = (prompt.chars().count() + input_tail.chars().count()) as u16;
     queue!(
@@ -573,6 +838,32 @@ fn render(app: &App<'_>) -> Result<()> {
     Ok(())
 }
 
+/// Only the last input line is drawn; earlier lines are summarized in the prompt.
+fn input_prompt(pending: bool, input: &str) -> String {
+    let prompt = if pending { "… " } else { "> " };
+    match input.matches('\n').count() {
+        0 => prompt.to_string(),
+        earlier => format!("{}[+{}] ", prompt, earlier),
+    }
+}
+
+/// Rows between the header/status/separator lines and the input separator.
+fn message_viewport_height(height: u16) -> usize {
+    (height.saturating_sub(2) as usize).saturating_sub(MESSAGE_TOP_ROW)
+}
+
+fn status_line(status: &str, latest_event: Option<&EngineEvent>) -> String {
+    match latest_event {
+        Some(event) => format!(
+            "status={}  [{}] {}  (/help for commands)",
+            status,
+            event.kind,
+            event.message.lines().next().unwrap_or_default()
+        ),
+        None => format!("status={}  (/help for commands)", status),
+    }
+}
+
 fn draw_line(out: &mut io::Stdout, row: u16, width: u16, text: &str, color: Color) -> Result<()> {
     let truncated = truncate_chars(text, width as usize);
     queue!(
@@ -585,38 +876,59 @@ fn draw_line(out: &mut io::Stdout, row: u16, width: u16, text: &str, color: Colo
     Ok(())
 }
 
+fn message_prefix(role: MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "you> ",
+        MessageRole::Assistant => "ai > ",
+        MessageRole::System => "sys> ",
+        MessageRole::Error => "err> ",
+    }
+}
+
+fn count_render_lines(messages: &[Message], width: u16) -> usize {
+    messages
+        .iter()
+        .map(|message| {
+            wrap_with_prefix(&message.text, message_prefix(message.role), width as usize).len()
+        })
+        .sum()
+}
+
+/// Lines ending `skip_newest` wrapped lines above the bottom of the transcript.
 fn collect_recent_render_lines(
     messages: &[Message],
+    theme: &Theme,
     width: usize,
     max_lines: usize,
+    skip_newest: usize,
 ) -> Vec<RenderLine> {
     if width == 0 || max_lines == 0 {
         return Vec::new();
     }
 
+    let wanted = max_lines.saturating_add(skip_newest);
     let mut lines = VecDeque::new();
     for message in messages.iter().rev() {
-        let (prefix, color) = match message.role {
-            MessageRole::User => ("you> ", Color::Cyan),
-            MessageRole::Assistant => ("ai > ", Color::Green),
-            MessageRole::System => ("sys> ", Color::Blue),
-            MessageRole::Error => ("err> ", Color::Red),
+        let color = match message.role {
+            MessageRole::User => theme.user,
+            MessageRole::Assistant => theme.assistant,
+            MessageRole::System => theme.system,
+            MessageRole::Error => theme.error,
         };
 
-        let wrapped = wrap_with_prefix(&message.text, prefix, width);
+        let wrapped = wrap_with_prefix(&message.text, message_prefix(message.role), width);
         for line in wrapped.into_iter().rev() {
             lines.push_front(RenderLine { color, text: line });
-            if lines.len() > max_lines {
-                let _ = lines.pop_back();
-            }
         }
 
-        if lines.len() >= max_lines {
+        if lines.len() >= wanted {
             break;
         }
     }
 
-    lines.into_iter().collect::<Vec<_>>()
+    let start = lines.len().saturating_sub(wanted);
+    let end = lines.len().saturating_sub(skip_newest);
+    lines.into_iter().take(end).skip(start).collect::<Vec<_>>()
 }
 
 fn wrap_with_prefix(text: &str, prefix: &str, width: usize) -> Vec<String> {
@@ -731,3 +1043,119 @@ fn transcript_to_messages(transcript: &SessionTranscript) -> Vec<Message> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{
+        build_prompt, clamp_scroll_offset, collect_recent_render_lines, count_render_lines,
+        edit_input, input_prompt, status_line, EventTail, InputHistory, Message, MessageRole,
+        EVENT_TAIL_LIMIT, INPUT_HISTORY_LIMIT,
+    };
+    use crate::cli::theme::Theme;
+    use crate::plugin::prd_runner::logger::event::EngineEvent;
+
+    #[test]
+    fn shift_enter_inserts_newline_instead_of_submitting() {
+        let mut input = String::new();
+        for ch in "fix".chars() {
+            assert!(!edit_input(&mut input, KeyEvent::from(KeyCode::Char(ch))));
+        }
+        let shift_enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT);
+        assert!(!edit_input(&mut input, shift_enter));
+        assert!(!edit_input(&mut input, KeyEvent::from(KeyCode::Char('x'))));
+        assert_eq!(input, "fix\nx");
+        assert_eq!(input_prompt(false, &input), "> [+1] ");
+
+        assert!(edit_input(&mut input, KeyEvent::from(KeyCode::Enter)));
+        assert_eq!(input, "fix\nx");
+
+        let prompt = build_prompt(&[], &input);
+        assert!(prompt.ends_with("user: fix\nx\nassistant:"));
+    }
+
+    #[test]
+    fn scroll_offset_is_clamped_to_the_first_line() {
+        let messages = (0..10)
+            .map(|idx| Message {
+                role: MessageRole::System,
+                text: format!("line {idx}"),
+            })
+            .collect::<Vec<_>>();
+        let total = count_render_lines(&messages, 40);
+        assert_eq!(total, 10);
+
+        assert_eq!(clamp_scroll_offset(0, total, 4), 0);
+        assert_eq!(clamp_scroll_offset(3, total, 4), 3);
+        assert_eq!(clamp_scroll_offset(50, total, 4), 6);
+        assert_eq!(clamp_scroll_offset(5, 3, 4), 0);
+
+        let theme = Theme::default();
+        let texts = |skip| {
+            collect_recent_render_lines(&messages, &theme, 40, 4, skip)
+                .into_iter()
+                .map(|line| line.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts(0),
+            ["sys> line 6", "sys> line 7", "sys> line 8", "sys> line 9"]
+        );
+        assert_eq!(
+            texts(6),
+            ["sys> line 0", "sys> line 1", "sys> line 2", "sys> line 3"]
+        );
+    }
+
+    #[test]
+    fn input_history_cycles_and_returns_to_blank() {
+        let mut history = InputHistory::default();
+        assert_eq!(history.previous(), None);
+        assert_eq!(history.next(), None);
+
+        history.push("first");
+        history.push("second");
+        history.push("second");
+        history.push("third");
+
+        assert_eq!(history.previous(), Some("third"));
+        assert_eq!(history.previous(), Some("second"));
+        assert_eq!(history.previous(), Some("first"));
+        assert_eq!(history.previous(), Some("first"));
+        assert_eq!(history.next(), Some("second"));
+        assert_eq!(history.next(), Some("third"));
+        assert_eq!(history.next(), Some(""));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous(), Some("third"));
+
+        for idx in 0..INPUT_HISTORY_LIMIT + 5 {
+            history.push(&format!("cmd {idx}"));
+        }
+        assert_eq!(history.entries.len(), INPUT_HISTORY_LIMIT);
+        assert_eq!(history.entries.front().map(String::as_str), Some("cmd 5"));
+    }
+
+    #[test]
+    fn status_line_shows_latest_pushed_event() {
+        let mut tail = EventTail::default();
+        assert_eq!(
+            status_line("ready", tail.latest()),
+            "status=ready  (/help for commands)"
+        );
+
+        for idx in 0..EVENT_TAIL_LIMIT + 4 {
+            tail.push(EngineEvent::new(
+                "ITERATION_START",
+                &format!("iteration={idx}"),
+            ));
+        }
+        tail.push(EngineEvent::new("REQ_DONE", "REQ-003 done\nextra detail"));
+
+        assert_eq!(tail.events.len(), EVENT_TAIL_LIMIT);
+        assert_eq!(
+            status_line("prd running", tail.latest()),
+            "status=prd running  [REQ_DONE] REQ-003 done  (/help for commands)"
+        );
+    }
+}
@@ -64,9 +64,10 @@ pub fn print_help() {
     println!("Commands");
     println!("  /help");
     println!("  /exit | /quit");
-    println!("  /provider auto|claude|opencode");
+    println!("  /provider auto|claude|opencode|ollama");
     println!("  /plugin <id> <cmd> [args...]");
     println!("  /prd <cmd> [args...]");
+    println!("  /run  run PRD loop with progress (Ctrl+C stops the loop)");
     println!("  /session");
     println!("  /sessions");
     println!("  /resume [id]");
@@ -110,17 +111,29 @@ pub fn clear_wait() {
     let _ = io::stdout().flush();
 }
 
-pub fn print_assistant(provider: &str, output: &str, elapsed: Duration) {
+pub fn print_assistant_start(provider: &str) {
     println!(
-        "{}[assistant:{}]{} elapsed={}s",
+        "{}[assistant:{}]{}",
         style(FG_GREEN_BOLD),
         provider,
-        style(RESET),
-        elapsed.as_secs()
+        style(RESET)
     );
     println!("{}", line(96));
-    println!("{}", output.trim());
+}
+
+pub fn print_assistant_chunk(chunk: &str) {
+    print!("{}", chunk);
+    let _ = io::stdout().flush();
+}
+
+pub fn print_assistant_end(elapsed: Duration) {
     println!("{}", line(96));
+    println!(
+        "{}elapsed={}s{}",
+        style(FG_DIM),
+        elapsed.as_secs(),
+        style(RESET)
+    );
 }
 
 pub fn print_exit() {
@@ -1,3 +1,41 @@
 pub fn split_command_tokens(input: &str) -> Result<Vec<String>, String> {
     shell_words::split(input).map_err(|err| format!("failed to parse command arguments: {}", err))
 }
+
+/// Renders the full error chain so provider stderr embedded in inner causes stays visible.
+pub fn provider_error_message(err: &anyhow::Error) -> String {
+    format!("provider error: {:#}", err)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::provider_error_message;
+    use crate::plugin::prd_runner::core::provider::{CliPrintProvider, Provider};
+
+    #[test]
+    fn provider_error_message_includes_stderr() {
+        let tmp = TempDir::new().expect("tmp");
+        let script = tmp.path().join("failing-provider.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\necho 'Not logged in · Please run /login' >&2\nexit 1\n",
+        )
+        .expect("script");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+        let mut provider = CliPrintProvider::new(script.display().to_string(), tmp.path());
+        provider.send("hello").expect("send");
+        let err = provider
+            .read_output(Duration::from_secs(5))
+            .expect_err("provider should fail");
+
+        let message = provider_error_message(&err);
+        assert!(message.starts_with("provider error:"));
+        assert!(message.contains("Please run /login"));
+    }
+}
@@ -1,5 +1,7 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     auto_code::cli::run()
 }
@@ -1,88 +1,250 @@
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, Context, Result};
 use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
 
-use crate::plugin::prd_runner::config::parser::parse_prd_file;
+use crate::plugin::prd_runner::checkpoint::diff::{compare, unified_diff};
+use crate::plugin::prd_runner::checkpoint::saver::CheckpointManager;
+use crate::plugin::prd_runner::config::diff::diff_prd;
+use crate::plugin::prd_runner::config::parser::{
+    parse_prd_file, parse_prd_file_with_warnings, parse_prd_str, STDIN_PATH,
+};
+use crate::plugin::prd_runner::config::prd::PrdDocument;
 use crate::plugin::prd_runner::config::AppConfig;
+use crate::plugin::prd_runner::core::lock::{RunLock, RUN_LOCK_FILE};
+use crate::plugin::prd_runner::core::provider::{Provider, MODEL_ENV};
+use crate::plugin::prd_runner::logger::event::{
+    json_lines_observer, open_json_log_sink, EventObservers,
+};
 use crate::plugin::prd_runner::loop_engine::driver::{EngineRuntime, RunSummary};
 use crate::plugin::prd_runner::loop_engine::state::EngineState;
-use crate::provider::{resolve_provider, ProviderSelection};
+use crate::plugin::prd_runner::OutputFormat;
+use crate::provider::{
+    resolve_provider, resolve_provider_command, ProviderKind, ProviderSelection,
+};
 
 #[derive(Debug, Clone)]
 pub struct PrdRunOptions {
     pub provider: ProviderSelection,
-    pub max_runtime: String,
+    /// Arbitrary provider CLI invoked as `<cmd> <prompt>`; wins over `provider`.
+    pub provider_command: Option<String>,
+    /// Overrides `convergence.max_runtime` from `.autocode/config.toml` when set.
+    pub max_runtime: Option<String>,
     pub provider_timeout: Option<String>,
+    /// Overrides `provider.model` from `.autocode/config.toml` when set.
+    pub model: Option<String>,
     pub dry_run: bool,
+    /// Explicit config file replacing `.autocode/config.toml`.
+    pub config_path: Option<PathBuf>,
+    pub prd: Option<PathBuf>,
+    pub run_id: Option<String>,
+    pub preview_guards: bool,
+    /// One provider pass that only logs the planned commands: nothing runs, no checkpoints.
+    pub plan_only: bool,
+    /// Stream every engine event as a JSON line to this fd or path.
+    pub json_logs: Option<String>,
+    /// One-line progress cadence, applied only when stdout is not a terminal.
+    pub progress_interval: Option<String>,
+    /// Never print progress lines, e.g. while the TUI owns the terminal.
+    pub no_progress: bool,
+    /// Summary format; `Json` keeps stdout free of anything but the summary.
+    pub output: OutputFormat,
 }
 
 impl Default for PrdRunOptions {
     fn default() -> Self {
         Self {
             provider: ProviderSelection::Auto,
-            max_runtime: "10m".to_string(),
+            provider_command: None,
+            max_runtime: None,
             provider_timeout: None,
+            model: None,
             dry_run: false,
+            config_path: None,
+            prd: None,
+            run_id: None,
+            preview_guards: false,
+            plan_only: false,
+            json_logs: None,
+            progress_interval: None,
+            no_progress: false,
+            output: OutputFormat::Text,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct PrdResumeOptions {
-    pub provider: ProviderSelection,
-    pub max_runtime: String,
+    /// Explicit `--provider`; only this replaces the provider recorded for the run.
+    pub provider: Option<ProviderSelection>,
+    /// `AUTOCODE_PROVIDER` or auto, used when the run recorded no provider.
+    pub default_provider: ProviderSelection,
+    pub provider_command: Option<String>,
+    pub max_runtime: Option<String>,
     pub provider_timeout: Option<String>,
+    /// Overrides `provider.model` from `.autocode/config.toml` when set.
+    pub model: Option<String>,
     pub dry_run: bool,
+    pub config_path: Option<PathBuf>,
     pub run_id: Option<String>,
     pub checkpoint_id: Option<String>,
+    pub checkpoint_index: Option<i64>,
+    pub json_logs: Option<String>,
+    pub progress_interval: Option<String>,
+    /// Re-run requirements the checkpoint already marks `Done`.
+    pub reverify: bool,
+    /// Start `max_runtime` from zero instead of subtracting the runtime earlier sessions used.
+    pub reset_runtime: bool,
+    pub output: OutputFormat,
 }
 
 impl Default for PrdResumeOptions {
     fn default() -> Self {
         Self {
-            provider: ProviderSelection::Auto,
-            max_runtime: "10m".to_string(),
+            provider: None,
+            default_provider: ProviderSelection::Auto,
+            provider_command: None,
+            max_runtime: None,
             provider_timeout: None,
+            model: None,
             dry_run: false,
+            config_path: None,
             run_id: None,
             checkpoint_id: None,
+            checkpoint_index: None,
+            json_logs: None,
+            progress_interval: None,
+            reverify: false,
+            reset_runtime: false,
+            output: OutputFormat::Text,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunMeta {
+    run_id: String,
+    provider: String,
+    /// Set for `--provider-command` runs, where `provider` is `custom`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provider_command: Option<String>,
+    /// PRD the run was started from, relative to the workdir when inside it; resume reads
+    /// it instead of `PRD.md`. Stdin runs point at a copy inside the run dir.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prd_path: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct PrdRunOutput {
+    #[serde(flatten)]
     pub summary: RunSummary,
     pub log_root: PathBuf,
 }
 
-pub fn run(workdir: &Path, options: PrdRunOptions) -> Result<PrdRunOutput> {
-    let prd_path = workdir.join("PRD.md");
-    if !prd_path.exists() {
-        bail!(
-            "missing PRD.md in {}. put your PRD at project root and rerun",
-            workdir.display()
-        );
+impl PrdRunOutput {
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize run summary")
     }
+}
 
-    let prd = parse_prd_file(&prd_path)?;
-    let mut config = AppConfig::default();
-    config.convergence.max_runtime = options.max_runtime;
+pub fn run(workdir: &Path, options: PrdRunOptions) -> Result<PrdRunOutput> {
+    run_with_observers(workdir, options, EventObservers::default())
+}
+
+pub fn run_with_observers(
+    workdir: &Path,
+    options: PrdRunOptions,
+    mut observers: EventObservers,
+) -> Result<PrdRunOutput> {
+    check_json_output(options.output, options.json_logs.as_deref())?;
+    let (mut prd_path, prd) = load_prd(workdir, options.prd.as_deref())?;
+    add_json_log_observer(&mut observers, options.json_logs.as_deref())?;
+    let mut config = AppConfig::load(workdir, options.config_path.as_deref())?;
+    config.execution.dry_run_query_provider |= options.preview_guards || options.plan_only;
+    if options.plan_only {
+        config.convergence.max_iterations = Some(1);
+        config.checkpoint.enabled = false;
+    }
+    if let Some(max_runtime) = options.max_runtime {
+        config.convergence.max_runtime = max_runtime;
+    }
+    apply_progress_interval(
+        &mut config,
+        options.progress_interval,
+        options.no_progress || std::io::stdout().is_terminal(),
+    );
+    config.logging.progress_to_stderr |= options.output == OutputFormat::Json;
     if let Some(timeout) = options.provider_timeout {
         config.provider.response_timeout = timeout;
     }
+    apply_model(&mut config, options.model, std::env::var(MODEL_ENV).ok());
 
     let provider_timeout = config
         .provider
         .response_timeout_duration()
         .context("failed to parse provider response timeout")?;
-    let provider_kind = resolve_provider(options.provider)?;
-    let provider_command = provider_kind.command().to_string();
+    let (provider_name, custom_command) = match options.provider_command.as_deref() {
+        Some(command) => ("custom", Some(resolve_provider_command(command)?)),
+        None => (resolve_provider(options.provider)?.as_str(), None),
+    };
+    let provider_command = custom_command
+        .clone()
+        .unwrap_or_else(|| provider_name.to_string());
     let log_root = workdir.join(".autocode");
-    let log_dir = log_root.join("logs");
-    let run_id = Utc::now().format("run_%Y%m%d_%H%M%S").to_string();
-    let checkpoint_root = log_root.join("checkpoints").join(run_id);
+    let checkpoints_dir = log_root.join("checkpoints");
+    let (run_id, log_dir) = match options.run_id {
+        Some(run_id) => {
+            validate_run_id(&run_id)?;
+            let existing = checkpoints_dir.join(&run_id);
+            if existing.exists() {
+                bail!(
+                    "run id '{}' already exists at {}. use `prd resume --run-id {}` to continue it",
+                    run_id,
+                    existing.display(),
+                    run_id
+                );
+            }
+            let log_dir = log_root.join("logs").join(&run_id);
+            (run_id, log_dir)
+        }
+        None => {
+            let base = Utc::now().format("run_%Y%m%d_%H%M%S").to_string();
+            (
+                claim_auto_run_id(&checkpoints_dir, &base)?,
+                log_root.join("logs"),
+            )
+        }
+    };
+    let checkpoint_root = checkpoints_dir.join(&run_id);
+    if options
+        .prd
+        .as_deref()
+        .is_some_and(|path| path.as_os_str() == STDIN_PATH)
+    {
+        // The shared snapshot is overwritten by the next stdin run; keep one per run.
+        prd_path = write_prd_snapshot(&checkpoint_root, &prd.raw_markdown)?;
+    }
+    write_run_meta(
+        &checkpoint_root,
+        &RunMeta {
+            run_id,
+            provider: provider_name.to_string(),
+            provider_command: custom_command,
+            prd_path: Some(
+                prd_path
+                    .strip_prefix(workdir)
+                    .unwrap_or(&prd_path)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            created_at: Utc::now().to_rfc3339(),
+        },
+    )?;
 
     let runtime = EngineRuntime {
         prd,
@@ -93,46 +255,110 @@ pub fn run(workdir: &Path, options: PrdRunOptions) -> Result<PrdRunOutput> {
         checkpoint_root,
         provider_command,
         provider_timeout,
-        dry_run: options.dry_run,
+        dry_run: options.dry_run || options.preview_guards || options.plan_only,
+        reverify_done: false,
+        observers,
     };
 
     let summary = runtime.run(None)?;
     Ok(PrdRunOutput { summary, log_root })
 }
 
+/// Runs the loop from an in-memory PRD with a caller-supplied config and provider.
+pub fn run_prd_str(
+    workdir: &Path,
+    markdown: &str,
+    config: AppConfig,
+    provider: Box<dyn Provider>,
+) -> Result<PrdRunOutput> {
+    let prd = parse_prd_str(markdown)?;
+    let prd_path = write_prd_snapshot(&workdir.join(".autocode"), &prd.raw_markdown)?;
+    let provider_timeout = config
+        .provider
+        .response_timeout_duration()
+        .context("failed to parse provider response timeout")?;
+    let log_root = workdir.join(".autocode");
+    let run_id = Utc::now().format("run_%Y%m%d_%H%M%S").to_string();
+
+    let runtime = EngineRuntime {
+        prd,
+        prd_path,
+        config,
+        workdir: workdir.to_path_buf(),
+        log_dir: log_root.join("logs"),
+        checkpoint_root: log_root.join("checkpoints").join(run_id),
+        provider_command: provider.name().to_string(),
+        provider_timeout,
+        dry_run: false,
+        reverify_done: false,
+        observers: EventObservers::default(),
+    };
+
+    let summary = runtime.run_with_provider(provider, None)?;
+    Ok(PrdRunOutput { summary, log_root })
+}
+
 pub fn resume(workdir: &Path, options: PrdResumeOptions) -> Result<PrdRunOutput> {
-    let prd_path = workdir.join("PRD.md");
+    let checkpoint_root_all = workdir.join(".autocode").join("checkpoints");
+    let run_dir = resolve_run_dir(&checkpoint_root_all, options.run_id.as_deref())?;
+    let prd_path = recorded_prd_path(workdir, Some(&run_dir))?;
     if !prd_path.exists() {
         bail!(
-            "missing PRD.md in {}. put your PRD at project root and rerun",
-            workdir.display()
+            "missing PRD at {}. put the run's PRD back and rerun",
+            prd_path.display()
         );
     }
 
+    check_json_output(options.output, options.json_logs.as_deref())?;
     let prd = parse_prd_file(&prd_path)?;
-    let mut config = AppConfig::default();
-    config.convergence.max_runtime = options.max_runtime;
+    let mut observers = EventObservers::default();
+    add_json_log_observer(&mut observers, options.json_logs.as_deref())?;
+    let mut config = AppConfig::load(workdir, options.config_path.as_deref())?;
+    if let Some(max_runtime) = options.max_runtime {
+        config.convergence.max_runtime = max_runtime;
+    }
+    apply_progress_interval(
+        &mut config,
+        options.progress_interval,
+        std::io::stdout().is_terminal(),
+    );
+    config.logging.progress_to_stderr |= options.output == OutputFormat::Json;
     if let Some(timeout) = options.provider_timeout {
         config.provider.response_timeout = timeout;
     }
+    apply_model(&mut config, options.model, std::env::var(MODEL_ENV).ok());
 
     let provider_timeout = config
         .provider
         .response_timeout_duration()
         .context("failed to parse provider response timeout")?;
-    let provider_kind = resolve_provider(options.provider)?;
-    let provider_command = provider_kind.command().to_string();
-
-    let checkpoint_root_all = workdir.join(".autocode").join("checkpoints");
-    let run_dir = resolve_run_dir(&checkpoint_root_all, options.run_id.as_deref())?;
-    let state_path = resolve_checkpoint_state_path(&run_dir, options.checkpoint_id.as_deref())?;
+    let provider_command = match (options.provider_command.as_deref(), options.provider) {
+        (Some(command), _) => resolve_provider_command(command)?,
+        (None, Some(selection)) if selection != ProviderSelection::Auto => {
+            resolve_provider(selection)?.command().to_string()
+        }
+        (None, explicit) => match recorded_provider_command(&run_dir)? {
+            Some(recorded) => recorded,
+            None => resolve_provider(explicit.unwrap_or(options.default_provider))?
+                .command()
+                .to_string(),
+        },
+    };
+    let state_path = resolve_checkpoint_state_path(
+        &run_dir,
+        options.checkpoint_id.as_deref(),
+        options.checkpoint_index,
+    )?;
     let state_content = std::fs::read_to_string(&state_path)
         .with_context(|| format!("failed to read {}", state_path.display()))?;
-    let resume_state: EngineState = serde_json::from_str(&state_content)
+    let mut resume_state: EngineState = serde_json::from_str(&state_content)
         .with_context(|| format!("failed to parse checkpoint state {}", state_path.display()))?;
+    if options.reset_runtime {
+        resume_state.accumulated_runtime_ms = 0;
+    }
 
     let log_root = workdir.join(".autocode");
-    let log_dir = log_root.join("logs");
+    let log_dir = run_log_dir(&log_root, &run_dir);
     let runtime = EngineRuntime {
         prd,
         prd_path,
@@ -143,15 +369,57 @@ pub fn resume(workdir: &Path, options: PrdResumeOptions) -> Result<PrdRunOutput>
         provider_command,
         provider_timeout,
         dry_run: options.dry_run,
+        reverify_done: options.reverify,
+        observers,
     };
 
     let summary = runtime.run(Some(resume_state))?;
     Ok(PrdRunOutput { summary, log_root })
 }
 
-pub fn validate_prd(workdir: &Path) -> Result<()> {
-    let prd_path = workdir.join("PRD.md");
-    let prd = parse_prd_file(&prd_path)?;
+/// Progress lines are meant for CI logs; an interactive terminal keeps its usual output,
+/// whether the interval came from `--progress-interval` or `logging.progress_interval`.
+fn apply_progress_interval(
+    config: &mut AppConfig,
+    interval: Option<String>,
+    stdout_is_terminal: bool,
+) {
+    if interval.is_some() {
+        config.logging.progress_interval = interval;
+    }
+    if stdout_is_terminal {
+        config.logging.progress_interval = None;
+    }
+}
+
+/// Precedence: `--model` > `AUTOCODE_MODEL` > `provider.model` from config.
+fn apply_model(config: &mut AppConfig, flag: Option<String>, env: Option<String>) {
+    let env = env
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(model) = flag.or(env) {
+        config.provider.model = Some(model);
+    }
+}
+
+/// `--output json` owns stdout, so nothing else may be streamed there.
+fn check_json_output(output: OutputFormat, json_logs: Option<&str>) -> Result<()> {
+    if output == OutputFormat::Json && matches!(json_logs, Some("-" | "1")) {
+        bail!("--json-logs cannot write to stdout together with --output json; use 2 or a file");
+    }
+    Ok(())
+}
+
+fn add_json_log_observer(observers: &mut EventObservers, target: Option<&str>) -> Result<()> {
+    if let Some(target) = target {
+        observers.push(json_lines_observer(open_json_log_sink(target)?));
+    }
+    Ok(())
+}
+
+pub fn validate_prd(workdir: &Path, path: Option<&Path>) -> Result<()> {
+    let prd_path = resolve_prd_path(workdir, path);
+    let (prd, warnings) = parse_prd_file_with_warnings(&prd_path)?;
     println!("PRD is valid.");
     println!(
         "- project: {}",
@@ -159,6 +427,9 @@ pub fn validate_prd(workdir: &Path) -> Result<()> {
     );
     println!("- requirements: {}", prd.requirements.len());
     println!("- acceptance criteria: {}", prd.acceptance_criteria.len());
+    for warning in &warnings {
+        println!("- warning: {}", warning);
+    }
     Ok(())
 }
 
@@ -188,6 +459,467 @@ pub fn print_status(workdir: &Path, run_id: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+fn write_prd_snapshot(snapshot_dir: &Path, markdown: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(snapshot_dir)
+        .with_context(|| format!("failed to create {}", snapshot_dir.display()))?;
+    let snapshot = snapshot_dir.join("PRD.snapshot.md");
+    std::fs::write(&snapshot, markdown)
+        .with_context(|| format!("failed to write {}", snapshot.display()))?;
+    Ok(snapshot)
+}
+
+fn load_prd(workdir: &Path, path: Option<&Path>) -> Result<(PathBuf, PrdDocument)> {
+    if path.is_some_and(|path| path.as_os_str() == STDIN_PATH) {
+        let prd = parse_prd_file(Path::new(STDIN_PATH))?;
+        let snapshot = write_prd_snapshot(&workdir.join(".autocode"), &prd.raw_markdown)?;
+        return Ok((snapshot, prd));
+    }
+
+    let prd_path = resolve_prd_path(workdir, path);
+    if !prd_path.exists() {
+        bail!(
+            "missing PRD at {}. put your PRD at project root and rerun",
+            prd_path.display()
+        );
+    }
+
+    let prd = parse_prd_file(&prd_path)?;
+    Ok((prd_path, prd))
+}
+
+fn resolve_prd_path(workdir: &Path, path: Option<&Path>) -> PathBuf {
+    match path {
+        Some(path) if path.as_os_str() == STDIN_PATH => path.to_path_buf(),
+        Some(path) => workdir.join(path),
+        None => workdir.join("PRD.md"),
+    }
+}
+
+/// Auto ids only have second resolution, so runs started in the same second get `_2`, `_3`, …
+/// Creating the run dir here means two processes cannot claim the same id.
+fn claim_auto_run_id(checkpoints_dir: &Path, base: &str) -> Result<String> {
+    std::fs::create_dir_all(checkpoints_dir)
+        .with_context(|| format!("failed to create {}", checkpoints_dir.display()))?;
+    let mut suffix = 1u32;
+    loop {
+        let run_id = match suffix {
+            1 => base.to_string(),
+            n => format!("{}_{}", base, n),
+        };
+        let run_dir = checkpoints_dir.join(&run_id);
+        match std::fs::create_dir(&run_dir) {
+            Ok(()) => return Ok(run_id),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to create run dir {}", run_dir.display()))
+            }
+        }
+    }
+}
+
+fn write_run_meta(run_dir: &Path, meta: &RunMeta) -> Result<()> {
+    std::fs::create_dir_all(run_dir)
+        .with_context(|| format!("failed to create run dir {}", run_dir.display()))?;
+    let path = run_dir.join("meta.json");
+    let content = serde_json::to_string_pretty(meta).context("failed to serialize run meta")?;
+    std::fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// The PRD a run started from, per its meta; `PRD.md` for older runs or when there is no run.
+fn recorded_prd_path(workdir: &Path, run_dir: Option<&Path>) -> Result<PathBuf> {
+    let recorded = match run_dir {
+        Some(run_dir) => read_run_meta(run_dir)?.and_then(|meta| meta.prd_path),
+        None => None,
+    };
+    Ok(workdir.join(recorded.as_deref().unwrap_or("PRD.md")))
+}
+
+fn read_run_meta(run_dir: &Path) -> Result<Option<RunMeta>> {
+    let path = run_dir.join("meta.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("failed to parse run meta {}", path.display()))
+}
+
+fn recorded_provider_command(run_dir: &Path) -> Result<Option<String>> {
+    let Some(meta) = read_run_meta(run_dir)? else {
+        return Ok(None);
+    };
+    if let Some(command) = meta.provider_command {
+        return Ok(Some(command));
+    }
+    let provider = match ProviderSelection::parse(&meta.provider) {
+        Some(ProviderSelection::Claude) => ProviderKind::Claude,
+        Some(ProviderSelection::Opencode) => ProviderKind::Opencode,
+        Some(ProviderSelection::Ollama) => ProviderKind::Ollama,
+        _ => bail!(
+            "unknown provider '{}' in {}",
+            meta.provider,
+            run_dir.join("meta.json").display()
+        ),
+    };
+    Ok(Some(provider.command().to_string()))
+}
+
+pub fn print_prd_diff(
+    workdir: &Path,
+    run_id: Option<&str>,
+    checkpoint_index: Option<i64>,
+) -> Result<()> {
+    let checkpoint_root_all = workdir.join(".autocode").join("checkpoints");
+    let run_dir = resolve_run_dir(&checkpoint_root_all, run_id)?;
+    let state_path = resolve_checkpoint_state_path(&run_dir, None, checkpoint_index)?;
+    let checkpoint_dir = state_path
+        .parent()
+        .ok_or_else(|| anyhow!("invalid checkpoint path {}", state_path.display()))?;
+
+    let base = parse_prd_file(&checkpoint_dir.join("prd.md"))
+        .with_context(|| format!("failed to parse PRD in {}", checkpoint_dir.display()))?;
+    let current_path = recorded_prd_path(workdir, Some(&run_dir))?;
+    let current = parse_prd_file(&current_path)?;
+    let entries = diff_prd(&base, &current);
+
+    println!(
+        "PRD diff: {} -> {}",
+        checkpoint_dir.display(),
+        current_path
+            .strip_prefix(workdir)
+            .unwrap_or(&current_path)
+            .display()
+    );
+    if entries.is_empty() {
+        println!("- no structural changes");
+    }
+    for entry in entries {
+        println!("{}", entry);
+    }
+    Ok(())
+}
+
+/// Lists files added, removed or modified between two checkpoints' code snapshots.
+pub fn print_snapshot_diff(
+    workdir: &Path,
+    run_id: Option<&str>,
+    from: &str,
+    to: &str,
+    patch: bool,
+) -> Result<()> {
+    let run_dir = resolve_run_dir(&workdir.join(".autocode").join("checkpoints"), run_id)?;
+    let from_dir = resolve_checkpoint_dir(&run_dir, from)?;
+    let to_dir = resolve_checkpoint_dir(&run_dir, to)?;
+    let from_snapshot = from_dir.join("code_snapshot");
+    let to_snapshot = to_dir.join("code_snapshot");
+    for snapshot in [&from_snapshot, &to_snapshot] {
+        if !snapshot.is_dir() {
+            bail!("checkpoint has no code snapshot: {}", snapshot.display());
+        }
+    }
+
+    let diff = compare(&from_snapshot, &to_snapshot)?;
+    println!(
+        "Snapshot diff: {} -> {}",
+        from_dir.display(),
+        to_dir.display()
+    );
+    if diff.is_empty() {
+        println!("- no file changes");
+        return Ok(());
+    }
+    let changes = [
+        ('A', &diff.added),
+        ('D', &diff.removed),
+        ('M', &diff.modified),
+    ];
+    for (marker, paths) in changes {
+        for path in paths {
+            println!("{} {}", marker, path);
+        }
+    }
+    if patch {
+        for (_, paths) in changes {
+            for path in paths {
+                match unified_diff(&from_snapshot, &to_snapshot, path)? {
+                    Some(text) => print!("{}", text),
+                    None => println!("Binary or oversized file {} differs", path),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Accepts either a checkpoint directory name or a checkpoint number (negative counts back).
+fn resolve_checkpoint_dir(run_dir: &Path, spec: &str) -> Result<PathBuf> {
+    let state_path = match spec.parse::<i64>() {
+        Ok(index) => resolve_checkpoint_state_path(run_dir, None, Some(index))?,
+        Err(_) => resolve_checkpoint_state_path(run_dir, Some(spec), None)?,
+    };
+    state_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow!("invalid checkpoint path {}", state_path.display()))
+}
+
+/// Rolls the workspace back to the code snapshot of a checkpoint.
+pub fn restore_checkpoint(
+    workdir: &Path,
+    run_id: Option<&str>,
+    checkpoint_id: &str,
+) -> Result<PathBuf> {
+    let log_root = workdir.join(".autocode");
+    let run_dir = resolve_run_dir(&log_root.join("checkpoints"), run_id)?;
+    let state_path = resolve_checkpoint_state_path(&run_dir, Some(checkpoint_id), None)?;
+    let checkpoint_dir = state_path
+        .parent()
+        .ok_or_else(|| anyhow!("invalid checkpoint path {}", state_path.display()))?
+        .to_path_buf();
+
+    let _lock = RunLock::acquire(&log_root.join(RUN_LOCK_FILE))?;
+    CheckpointManager::restore(&checkpoint_dir, workdir)?;
+    Ok(checkpoint_dir)
+}
+
+/// Packages a run's logs, summary, latest checkpoint and effective config into a `.tar.gz`.
+pub fn export_bundle(
+    workdir: &Path,
+    run_id: Option<&str>,
+    out: &Path,
+    include_snapshot: bool,
+    config_path: Option<&Path>,
+) -> Result<PathBuf> {
+    let log_root = workdir.join(".autocode");
+    let run_dir = resolve_run_dir(&log_root.join("checkpoints"), run_id)?;
+    let run_name = run_dir
+        .file_name()
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_else(|| "run".to_string());
+
+    let staging = std::env::temp_dir().join(format!(
+        "autocode-bundle-{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_millis()
+    ));
+    let bundle_root = staging.join(&run_name);
+    let config = AppConfig::load(workdir, config_path)?;
+    let result = stage_bundle(workdir, &run_dir, &bundle_root, include_snapshot, &config)
+        .and_then(|()| write_tar_gz(&staging, &run_name, out));
+    let _ = std::fs::remove_dir_all(&staging);
+    result?;
+    Ok(out.to_path_buf())
+}
+
+fn stage_bundle(
+    workdir: &Path,
+    run_dir: &Path,
+    bundle_root: &Path,
+    include_snapshot: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let log_root = workdir.join(".autocode");
+    std::fs::create_dir_all(bundle_root)
+        .with_context(|| format!("failed to create {}", bundle_root.display()))?;
+    for name in ["meta.json", "run_summary.json"] {
+        let src = run_dir.join(name);
+        if src.is_file() {
+            copy_into(&src, &bundle_root.join(name))?;
+        }
+    }
+
+    let log_dir = run_log_dir(&log_root, run_dir);
+    if log_dir.is_dir() {
+        // The shared logs dir also holds other runs' subdirectories; only take its files.
+        let shared = log_dir == log_root.join("logs");
+        copy_tree(&log_dir, &bundle_root.join("logs"), !shared)?;
+    }
+
+    if let Ok(state_path) = resolve_checkpoint_state_path(run_dir, None, None) {
+        let checkpoint_dir = state_path
+            .parent()
+            .ok_or_else(|| anyhow!("invalid checkpoint path {}", state_path.display()))?;
+        let out = bundle_root.join("checkpoint");
+        for name in ["state.json", "iteration_report.json", "prd.md"] {
+            let src = checkpoint_dir.join(name);
+            if src.is_file() {
+                copy_into(&src, &out.join(name))?;
+            }
+        }
+        for name in ["artifacts", "code_snapshot"] {
+            let src = checkpoint_dir.join(name);
+            if src.is_dir() && (include_snapshot || name != "code_snapshot") {
+                copy_tree(&src, &out.join(name), true)?;
+            }
+        }
+    }
+
+    let config = toml::to_string_pretty(config).context("failed to serialize effective config")?;
+    std::fs::write(bundle_root.join("config.toml"), config)
+        .with_context(|| format!("failed to write config into {}", bundle_root.display()))
+}
+
+fn copy_into(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::copy(src, dst)
+        .with_context(|| format!("failed to copy {} -> {}", src.display(), dst.display()))?;
+    Ok(())
+}
+
+fn copy_tree(src: &Path, dst: &Path, recursive: bool) -> Result<()> {
+    let walker = walkdir::WalkDir::new(src).max_depth(if recursive { usize::MAX } else { 1 });
+    for entry in walker {
+        let entry = entry.with_context(|| format!("failed to walk {}", src.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry
+            .path()
+            .strip_prefix(src)
+            .with_context(|| format!("failed to strip prefix for {}", entry.path().display()))?;
+        copy_into(entry.path(), &dst.join(rel))?;
+    }
+    Ok(())
+}
+
+fn write_tar_gz(staging: &Path, entry: &str, out: &Path) -> Result<()> {
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let file = std::fs::File::create(out)
+        .with_context(|| format!("failed to create {}", out.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    archive
+        .append_dir_all(entry, staging.join(entry))
+        .with_context(|| format!("failed to archive {}", staging.join(entry).display()))?;
+    archive
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .with_context(|| format!("failed to write {}", out.display()))?;
+    Ok(())
+}
+
+/// Named runs log into `logs/<run_id>`; timestamped runs share `logs/`.
+fn run_log_dir(log_root: &Path, run_dir: &Path) -> PathBuf {
+    run_dir
+        .file_name()
+        .map(|name| log_root.join("logs").join(name))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| log_root.join("logs"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequirementRow {
+    pub id: String,
+    pub title: String,
+    pub priority: Option<String>,
+    pub status: Option<String>,
+    pub attempts: Option<u32>,
+}
+
+pub fn requirement_rows(workdir: &Path, run_id: Option<&str>) -> Result<Vec<RequirementRow>> {
+    let run_dir = existing_run_dir(workdir, run_id)?;
+    let prd = parse_prd_file(&recorded_prd_path(workdir, run_dir.as_deref())?)?;
+    let state = match &run_dir {
+        Some(run_dir) => latest_checkpoint_state(run_dir)?,
+        None => None,
+    };
+    let rows = prd
+        .requirements
+        .into_iter()
+        .map(|req| {
+            let record = state
+                .as_ref()
+                .and_then(|state| state.req_status.get(&req.id));
+            RequirementRow {
+                status: record.map(|record| record.status.as_str().to_string()),
+                attempts: record.map(|record| record.attempts),
+                id: req.id,
+                title: req.title,
+                priority: req.priority,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+pub fn print_requirements(workdir: &Path, run_id: Option<&str>, json: bool) -> Result<()> {
+    let rows = requirement_rows(workdir, run_id)?;
+    if json {
+        let content =
+            serde_json::to_string_pretty(&rows).context("failed to serialize requirements")?;
+        println!("{}", content);
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:<8} {:<12} {:<8} TITLE",
+        "ID", "PRIORITY", "STATUS", "ATTEMPTS"
+    );
+    for row in rows {
+        println!(
+            "{:<12} {:<8} {:<12} {:<8} {}",
+            row.id,
+            row.priority.as_deref().unwrap_or("-"),
+            row.status.as_deref().unwrap_or("-"),
+            row.attempts
+                .map(|attempts| attempts.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            row.title
+        );
+    }
+    Ok(())
+}
+
+/// The requested run, or the latest one; `None` (not an error) when no run exists yet.
+fn existing_run_dir(workdir: &Path, run_id: Option<&str>) -> Result<Option<PathBuf>> {
+    let checkpoint_root_all = workdir.join(".autocode").join("checkpoints");
+    if run_id.is_none() && !checkpoint_root_all.is_dir() {
+        return Ok(None);
+    }
+    match resolve_run_dir(&checkpoint_root_all, run_id) {
+        Ok(run_dir) => Ok(Some(run_dir)),
+        Err(_) if run_id.is_none() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn latest_checkpoint_state(run_dir: &Path) -> Result<Option<EngineState>> {
+    let Some(checkpoint_dir) = list_checkpoint_dirs(run_dir)?.pop() else {
+        return Ok(None);
+    };
+
+    let state_path = checkpoint_dir.join("state.json");
+    let content = std::fs::read_to_string(&state_path)
+        .with_context(|| format!("failed to read {}", state_path.display()))?;
+    let state = serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse checkpoint state {}", state_path.display()))?;
+    Ok(Some(state))
+}
+
+fn validate_run_id(run_id: &str) -> Result<()> {
+    let safe = !run_id.is_empty()
+        && run_id.len() <= 64
+        && !run_id.starts_with('.')
+        && run_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    if !safe {
+        bail!(
+            "invalid run id '{}': use 1-64 ASCII letters, digits, '-', '_' or '.', not starting with '.'",
+            run_id
+        );
+    }
+    Ok(())
+}
+
 fn resolve_run_dir(root: &Path, run_id: Option<&str>) -> Result<PathBuf> {
     if let Some(run_id) = run_id {
         let path = root.join(run_id);
@@ -214,7 +946,11 @@ fn resolve_run_dir(root: &Path, run_id: Option<&str>) -> Result<PathBuf> {
         .ok_or_else(|| anyhow!("no run directories found in {}", root.display()))
 }
 
-fn resolve_checkpoint_state_path(run_dir: &Path, checkpoint_id: Option<&str>) -> Result<PathBuf> {
+fn resolve_checkpoint_state_path(
+    run_dir: &Path,
+    checkpoint_id: Option<&str>,
+    checkpoint_index: Option<i64>,
+) -> Result<PathBuf> {
     if let Some(checkpoint_id) = checkpoint_id {
         let state = run_dir.join(checkpoint_id).join("state.json");
         if state.exists() {
@@ -228,11 +964,32 @@ fn resolve_checkpoint_state_path(run_dir: &Path, checkpoint_id: Option<&str>) ->
     }
 
     let checkpoints = list_checkpoint_dirs(run_dir)?;
-    let last = checkpoints
-        .last()
-        .ok_or_else(|| anyhow!("no checkpoints found in {}", run_dir.display()))?;
+    if checkpoints.is_empty() {
+        bail!("no checkpoints found in {}", run_dir.display());
+    }
+
+    let selected = match checkpoint_index {
+        None => checkpoints.last(),
+        Some(index) if index < 0 => checkpoints
+            .len()
+            .checked_sub(index.unsigned_abs() as usize)
+            .and_then(|pos| checkpoints.get(pos)),
+        Some(index) => checkpoints.iter().find(|path| {
+            path.file_name()
+                .and_then(|name| parse_checkpoint_index(&name.to_string_lossy()))
+                .is_some_and(|num| i64::from(num) == index)
+        }),
+    };
+    let selected = selected.ok_or_else(|| {
+        anyhow!(
+            "checkpoint index {} not found in {} ({} checkpoints)",
+            checkpoint_index.unwrap_or_default(),
+            run_dir.display(),
+            checkpoints.len()
+        )
+    })?;
 
-    Ok(last.join("state.json"))
+    Ok(selected.join("state.json"))
 }
 
 fn list_checkpoint_dirs(run_dir: &Path) -> Result<Vec<PathBuf>> {
@@ -273,3 +1030,524 @@ fn parse_checkpoint_index(name: &str) -> Option<u32> {
     }
     digits.parse::<u32>().ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use flate2::read::GzDecoder;
+    use tempfile::TempDir;
+
+    use super::{
+        apply_model, apply_progress_interval, claim_auto_run_id, resume, run, run_prd_str,
+    };
+    use super::{export_bundle, print_prd_diff, requirement_rows};
+    use super::{resolve_checkpoint_state_path, validate_run_id};
+    use super::{PrdResumeOptions, PrdRunOptions};
+    use crate::plugin::prd_runner::config::AppConfig;
+    use crate::plugin::prd_runner::core::scripted::ScriptedProvider;
+    use crate::plugin::prd_runner::init_prd_template;
+    use crate::plugin::prd_runner::logger::event::EngineEvent;
+    use crate::plugin::prd_runner::loop_engine::state::EngineState;
+    use crate::provider::ProviderSelection;
+
+    #[test]
+    fn selects_checkpoint_by_index() -> Result<()> {
+        let tmp = TempDir::new()?;
+        for name in ["checkpoint_002", "checkpoint_010", "checkpoint_009"] {
+            let dir = tmp.path().join(name);
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(dir.join("state.json"), "{}")?;
+        }
+
+        let pick = |index| resolve_checkpoint_state_path(tmp.path(), None, index);
+        assert!(pick(Some(9))?.ends_with("checkpoint_009/state.json"));
+        assert!(pick(Some(-1))?.ends_with("checkpoint_010/state.json"));
+        assert!(pick(Some(-3))?.ends_with("checkpoint_002/state.json"));
+        assert!(pick(None)?.ends_with("checkpoint_010/state.json"));
+        assert!(pick(Some(5)).is_err());
+        assert!(pick(Some(-4)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resume_reuses_recorded_provider() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+
+        let output = run(
+            tmp.path(),
+            PrdRunOptions {
+                provider: ProviderSelection::Opencode,
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                ..PrdRunOptions::default()
+            },
+        )?;
+        assert_eq!(output.summary.provider, "opencode");
+
+        let checkpoints = tmp.path().join(".autocode/checkpoints");
+        let run_dir = std::fs::read_dir(&checkpoints)?
+            .flatten()
+            .next()
+            .expect("run dir")
+            .path();
+        let meta = std::fs::read_to_string(run_dir.join("meta.json"))?;
+        assert!(meta.contains("\"provider\": \"opencode\""));
+
+        let resumed = resume(
+            tmp.path(),
+            PrdResumeOptions {
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                ..PrdResumeOptions::default()
+            },
+        )?;
+        assert_eq!(resumed.summary.provider, "opencode");
+        Ok(())
+    }
+
+    #[test]
+    fn provider_command_overrides_provider_and_is_reused_on_resume() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+
+        let output = run(
+            tmp.path(),
+            PrdRunOptions {
+                provider: ProviderSelection::Claude,
+                provider_command: Some("cat".to_string()),
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                ..PrdRunOptions::default()
+            },
+        )?;
+        assert_eq!(output.summary.provider, "cat");
+
+        let checkpoints = tmp.path().join(".autocode/checkpoints");
+        let run_dir = std::fs::read_dir(&checkpoints)?
+            .flatten()
+            .next()
+            .expect("run dir")
+            .path();
+        let meta = std::fs::read_to_string(run_dir.join("meta.json"))?;
+        assert!(meta.contains("\"provider\": \"custom\""));
+        assert!(meta.contains("\"provider_command\": \"cat\""));
+
+        let resumed = resume(
+            tmp.path(),
+            PrdResumeOptions {
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                ..PrdResumeOptions::default()
+            },
+        )?;
+        assert_eq!(resumed.summary.provider, "cat");
+        Ok(())
+    }
+
+    #[test]
+    fn resume_reads_the_prd_the_run_started_from() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        std::fs::rename(tmp.path().join("PRD.md"), tmp.path().join("other.md"))?;
+
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider_command: Some("cat".to_string()),
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                prd: Some("other.md".into()),
+                run_id: Some("custom-prd".to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+        let meta = std::fs::read_to_string(
+            tmp.path()
+                .join(".autocode/checkpoints/custom-prd/meta.json"),
+        )?;
+        assert!(meta.contains("\"prd_path\": \"other.md\""));
+
+        let resumed = resume(
+            tmp.path(),
+            PrdResumeOptions {
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                run_id: Some("custom-prd".to_string()),
+                ..PrdResumeOptions::default()
+            },
+        )?;
+        assert_eq!(resumed.summary.provider, "cat");
+        assert!(!tmp.path().join("PRD.md").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn resume_keeps_recorded_provider_over_env_default() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider_command: Some("cat".to_string()),
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                run_id: Some("recorded".to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        // AUTOCODE_PROVIDER=opencode arrives as the default, not as an explicit --provider.
+        let resumed = resume(
+            tmp.path(),
+            PrdResumeOptions {
+                default_provider: ProviderSelection::Opencode,
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                run_id: Some("recorded".to_string()),
+                ..PrdResumeOptions::default()
+            },
+        )?;
+        assert_eq!(resumed.summary.provider, "cat");
+        Ok(())
+    }
+
+    #[test]
+    fn resume_counts_earlier_runtime_unless_reset() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider_command: Some("cat".to_string()),
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                run_id: Some("budget".to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+        let run_dir = tmp.path().join(".autocode/checkpoints/budget");
+        let state_path = resolve_checkpoint_state_path(&run_dir, None, None)?;
+        let mut state: EngineState = serde_json::from_str(&std::fs::read_to_string(&state_path)?)?;
+        state.accumulated_runtime_ms = 60 * 60 * 1000;
+        std::fs::write(&state_path, serde_json::to_string(&state)?)?;
+        let iterations = state.iteration;
+
+        let resume_options = PrdResumeOptions {
+            max_runtime: Some("30m".to_string()),
+            dry_run: true,
+            run_id: Some("budget".to_string()),
+            ..PrdResumeOptions::default()
+        };
+        let exhausted = resume(tmp.path(), resume_options.clone())?;
+        assert_eq!(exhausted.summary.iterations, iterations);
+        assert_eq!(
+            exhausted.summary.stop_reason.as_deref(),
+            Some("reached max_runtime (30m)")
+        );
+
+        let reset = resume(
+            tmp.path(),
+            PrdResumeOptions {
+                reset_runtime: true,
+                ..resume_options
+            },
+        )?;
+        assert!(reset.summary.iterations > iterations);
+        Ok(())
+    }
+
+    #[test]
+    fn configured_progress_interval_is_dropped_on_a_terminal() -> Result<()> {
+        let tmp = TempDir::new()?;
+        std::fs::create_dir_all(tmp.path().join(".autocode"))?;
+        std::fs::write(
+            tmp.path().join(".autocode/config.toml"),
+            "[logging]\nprogress_interval = \"30s\"\n",
+        )?;
+
+        let mut piped = AppConfig::load(tmp.path(), None)?;
+        apply_progress_interval(&mut piped, None, false);
+        assert_eq!(piped.logging.progress_interval.as_deref(), Some("30s"));
+
+        let mut terminal = AppConfig::load(tmp.path(), None)?;
+        apply_progress_interval(&mut terminal, None, true);
+        assert_eq!(terminal.logging.progress_interval, None);
+        apply_progress_interval(&mut terminal, Some("1s".to_string()), true);
+        assert_eq!(terminal.logging.progress_interval, None);
+        Ok(())
+    }
+
+    #[test]
+    fn model_env_applies_below_flag_and_above_config() {
+        let mut config = AppConfig::default();
+        config.provider.model = Some("from-config".to_string());
+        apply_model(&mut config, None, Some("  ".to_string()));
+        assert_eq!(config.provider.model.as_deref(), Some("from-config"));
+
+        apply_model(&mut config, None, Some("from-env".to_string()));
+        assert_eq!(config.provider.model.as_deref(), Some("from-env"));
+
+        apply_model(
+            &mut config,
+            Some("from-flag".to_string()),
+            Some("from-env".to_string()),
+        );
+        assert_eq!(config.provider.model.as_deref(), Some("from-flag"));
+    }
+
+    #[test]
+    fn json_logs_stream_one_event_per_line() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        let sink = tmp.path().join("events.jsonl");
+
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider: ProviderSelection::Claude,
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                json_logs: Some(sink.display().to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        let content = std::fs::read_to_string(&sink)?;
+        let events = content
+            .lines()
+            .map(serde_json::from_str::<EngineEvent>)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(events.first().map(|e| e.kind.as_str()), Some("START"));
+        assert!(events.iter().any(|e| e.kind == "STOP"));
+        assert!(events.iter().all(|e| !e.ts.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn export_bundle_packages_run_files() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider: ProviderSelection::Claude,
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                run_id: Some("bundle-me".to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        let list = |archive: &std::path::Path| -> Result<Vec<String>> {
+            let mut archive = tar::Archive::new(GzDecoder::new(std::fs::File::open(archive)?));
+            let mut entries = Vec::new();
+            for entry in archive.entries()? {
+                entries.push(entry?.path()?.to_string_lossy().replace('\\', "/"));
+            }
+            Ok(entries)
+        };
+
+        let full = tmp.path().join("out/full.tar.gz");
+        export_bundle(tmp.path(), Some("bundle-me"), &full, true, None)?;
+        let entries = list(&full)?;
+        for expected in [
+            "bundle-me/meta.json",
+            "bundle-me/run_summary.json",
+            "bundle-me/config.toml",
+            "bundle-me/logs/events.log",
+            "bundle-me/checkpoint/state.json",
+            "bundle-me/checkpoint/iteration_report.json",
+            "bundle-me/checkpoint/prd.md",
+        ] {
+            assert!(
+                entries.iter().any(|entry| entry == expected),
+                "missing {expected} in {entries:?}"
+            );
+        }
+        assert!(entries
+            .iter()
+            .any(|entry| entry.starts_with("bundle-me/checkpoint/code_snapshot/")));
+
+        let small = tmp.path().join("small.tar.gz");
+        export_bundle(tmp.path(), Some("bundle-me"), &small, false, None)?;
+        assert!(!list(&small)?
+            .iter()
+            .any(|entry| entry.contains("code_snapshot")));
+        Ok(())
+    }
+
+    #[test]
+    fn named_run_uses_pinned_directory() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+
+        let options = PrdRunOptions {
+            provider: ProviderSelection::Claude,
+            max_runtime: Some("1s".to_string()),
+            dry_run: true,
+            run_id: Some("feature-auth".to_string()),
+            ..PrdRunOptions::default()
+        };
+        run(tmp.path(), options.clone())?;
+
+        assert!(tmp
+            .path()
+            .join(".autocode/checkpoints/feature-auth/meta.json")
+            .exists());
+        assert!(tmp
+            .path()
+            .join(".autocode/logs/feature-auth/events.log")
+            .exists());
+        assert!(run(tmp.path(), options).is_err());
+
+        assert!(validate_run_id("release_1.2").is_ok());
+        assert!(validate_run_id("../escape").is_err());
+        assert!(validate_run_id("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn auto_run_ids_started_in_the_same_second_stay_distinct() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let checkpoints = tmp.path().join("checkpoints");
+        let first = claim_auto_run_id(&checkpoints, "run_20260101_000000")?;
+        let second = claim_auto_run_id(&checkpoints, "run_20260101_000000")?;
+        let third = claim_auto_run_id(&checkpoints, "run_20260101_000000")?;
+
+        assert_eq!(first, "run_20260101_000000");
+        assert_eq!(second, "run_20260101_000000_2");
+        assert_eq!(third, "run_20260101_000000_3");
+        assert!(checkpoints.join(&third).is_dir());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plan_only_queries_provider_once_without_checkpoints() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        let script = tmp.path().join("planner.sh");
+        std::fs::write(&script, "#!/bin/sh\necho 'CMD: touch planned'\n")?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        let output = run(
+            tmp.path(),
+            PrdRunOptions {
+                provider_command: Some(script.display().to_string()),
+                max_runtime: Some("5s".to_string()),
+                run_id: Some("plan".to_string()),
+                plan_only: true,
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        assert_eq!(output.summary.iterations, 1);
+        assert_eq!(output.summary.last_checkpoint, None);
+        let events = std::fs::read_to_string(tmp.path().join(".autocode/logs/plan/events.log"))?;
+        assert!(events.contains("[AI_CMD_DRY_RUN] iteration=1 req=REQ-001 command='touch planned'"));
+        assert!(!events.contains("AI_CMD_EXEC"));
+        assert!(!tmp.path().join("planned").exists());
+        assert!(!events.contains("ACCEPTANCE_PASS"));
+        Ok(())
+    }
+
+    #[test]
+    fn runs_from_in_memory_prd() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let markdown = "# PRD: mem\n> 项目名称：mem\n\n## 1. 项目上下文\n- type: shell\n\n## 2. 需求列表\n### REQ-001: marker\n| 字段 | 值 |\n|------|-----|\n| **描述** | create marker |\n| **验证命令** | `test -f marker` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| marker | `test -f marker` | 退出码 = 0 |\n";
+
+        let mut config = AppConfig::default();
+        config.convergence.max_runtime = "1s".to_string();
+        config.execution.max_retry = 0;
+        let provider = ScriptedProvider::new(&["```bash\ntouch marker\n```"]);
+        let output = run_prd_str(tmp.path(), markdown, config, Box::new(provider))?;
+
+        assert!(output.summary.completed);
+        assert_eq!(output.summary.outcome.exit_code(), 0);
+        assert_eq!(output.summary.provider, "scripted");
+        assert!(!tmp.path().join("PRD.md").exists());
+        let snapshot = std::fs::read_to_string(tmp.path().join(".autocode/PRD.snapshot.md"))?;
+        assert_eq!(snapshot, markdown);
+        let checkpoint = output.summary.last_checkpoint.expect("checkpoint saved");
+        assert!(checkpoint.join("prd.md").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn run_output_serializes_summary_fields() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        let output = run(
+            tmp.path(),
+            PrdRunOptions {
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        let value: serde_json::Value = serde_json::from_str(&output.to_json()?)?;
+        assert_eq!(value["completed"], output.summary.completed);
+        assert_eq!(value["iterations"], output.summary.iterations);
+        assert_eq!(
+            value["stop_reason"].as_str(),
+            output.summary.stop_reason.as_deref()
+        );
+        assert!(value["last_checkpoint"].is_string());
+        assert_eq!(value["log_root"].as_str(), output.log_root.to_str());
+        Ok(())
+    }
+
+    #[test]
+    fn requirement_rows_reflect_latest_checkpoint() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+
+        let before = requirement_rows(tmp.path(), None)?;
+        assert_eq!(before[0].id, "REQ-001");
+        assert_eq!(before[0].status, None);
+
+        let checkpoint = tmp
+            .path()
+            .join(".autocode/checkpoints/run_a/checkpoint_002");
+        std::fs::create_dir_all(&checkpoint)?;
+        std::fs::write(
+            checkpoint.join("state.json"),
+            r#"{"iteration":2,"req_status":{"REQ-001":{"status":"failed","attempts":3}}}"#,
+        )?;
+
+        let rows = requirement_rows(tmp.path(), Some("run_a"))?;
+        assert_eq!(rows[0].priority.as_deref(), Some("high"));
+        assert_eq!(rows[0].status.as_deref(), Some("failed"));
+        assert_eq!(rows[0].attempts, Some(3));
+        assert!(requirement_rows(tmp.path(), Some("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn requirement_rows_and_prd_diff_use_the_runs_prd() -> Result<()> {
+        let tmp = TempDir::new()?;
+        init_prd_template(tmp.path())?;
+        std::fs::rename(tmp.path().join("PRD.md"), tmp.path().join("other.md"))?;
+        run(
+            tmp.path(),
+            PrdRunOptions {
+                provider_command: Some("cat".to_string()),
+                max_runtime: Some("1s".to_string()),
+                dry_run: true,
+                prd: Some("other.md".into()),
+                run_id: Some("other".to_string()),
+                ..PrdRunOptions::default()
+            },
+        )?;
+
+        let rows = requirement_rows(tmp.path(), Some("other"))?;
+        assert_eq!(rows[0].id, "REQ-001");
+        assert!(rows[0].status.is_some());
+        print_prd_diff(tmp.path(), Some("other"), None)?;
+        assert!(!tmp.path().join("PRD.md").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use walkdir::WalkDir;
+
+const CONTEXT_LINES: usize = 3;
+/// Upper bound on `old_lines * new_lines` for the LCS table behind text diffs.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// File-level changes between two `code_snapshot/` trees, as `/`-separated relative paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+pub fn compare(from: &Path, to: &Path) -> Result<SnapshotDiff> {
+    let before = list_files(from)?;
+    let after = list_files(to)?;
+
+    let mut diff = SnapshotDiff::default();
+    for rel in before.union(&after) {
+        match (before.contains(rel), after.contains(rel)) {
+            (true, false) => diff.removed.push(rel.clone()),
+            (false, true) => diff.added.push(rel.clone()),
+            _ => {
+                if !same_content(&from.join(rel), &to.join(rel))? {
+                    diff.modified.push(rel.clone());
+                }
+            }
+        }
+    }
+    Ok(diff)
+}
+
+/// Unified diff of `rel` between the two trees; a missing side counts as empty.
+/// Returns `None` for binary files or when the file is too large to diff.
+pub fn unified_diff(from: &Path, to: &Path, rel: &str) -> Result<Option<String>> {
+    let Some(old) = read_text(&from.join(rel))? else {
+        return Ok(None);
+    };
+    let Some(new) = read_text(&to.join(rel))? else {
+        return Ok(None);
+    };
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let Some(ops) = diff_lines(&old_lines, &new_lines) else {
+        return Ok(None);
+    };
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", rel, rel);
+    let mut index = 0;
+    while index < ops.len() {
+        if ops[index].0 == ' ' {
+            index += 1;
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let mut last_change = index;
+        let mut cursor = index;
+        while cursor < ops.len() {
+            if ops[cursor].0 != ' ' {
+                last_change = cursor;
+            } else if cursor - last_change > 2 * CONTEXT_LINES {
+                break;
+            }
+            cursor += 1;
+        }
+        let stop = (last_change + CONTEXT_LINES + 1).min(ops.len());
+
+        let old_before = ops[..start].iter().filter(|op| op.0 != '+').count();
+        let new_before = ops[..start].iter().filter(|op| op.0 != '-').count();
+        let hunk = &ops[start..stop];
+        let old_len = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_len = hunk.iter().filter(|op| op.0 != '-').count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_before, old_len),
+            hunk_range(new_before, new_len)
+        ));
+        for (marker, line) in hunk {
+            out.push(*marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+        index = stop;
+    }
+    Ok(Some(out))
+}
+
+fn hunk_range(before: usize, len: usize) -> String {
+    let start = if len == 0 { before } else { before + 1 };
+    format!("{},{}", start, len)
+}
+
+/// Line edit script from an LCS table: `' '` keep, `'-'` remove, `'+'` add.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<(char, &'a str)>> {
+    let (rows, cols) = (old.len(), new.len());
+    if rows.saturating_mul(cols) > MAX_DIFF_CELLS {
+        return None;
+    }
+    let width = cols + 1;
+    let mut table = vec![0u32; (rows + 1) * width];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(rows + cols);
+    let (mut i, mut j) = (0, 0);
+    while i < rows && j < cols {
+        if old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(('-', old[i]));
+            i += 1;
+        } else {
+            ops.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|line| ('-', *line)));
+    ops.extend(new[j..].iter().map(|line| ('+', *line)));
+    Some(ops)
+}
+
+fn list_files(root: &Path) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry.with_context(|| format!("failed to walk snapshot {}", root.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Ok(rel) = entry.path().strip_prefix(root) {
+            files.insert(rel.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(files)
+}
+
+fn same_content(left: &Path, right: &Path) -> Result<bool> {
+    let left_meta =
+        std::fs::metadata(left).with_context(|| format!("failed to stat {}", left.display()))?;
+    let right_meta =
+        std::fs::metadata(right).with_context(|| format!("failed to stat {}", right.display()))?;
+    if left_meta.len() != right_meta.len() {
+        return Ok(false);
+    }
+    let left = std::fs::read(left).with_context(|| format!("failed to read {}", left.display()))?;
+    let right =
+        std::fs::read(right).with_context(|| format!("failed to read {}", right.display()))?;
+    Ok(left == right)
+}
+
+fn read_text(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(Some(String::new()));
+    }
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(bytes).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::{compare, unified_diff};
+
+    #[test]
+    fn compares_hand_built_snapshots() -> Result<()> {
+        let from = TempDir::new()?;
+        let to = TempDir::new()?;
+        std::fs::create_dir_all(from.path().join("src"))?;
+        std::fs::create_dir_all(to.path().join("src"))?;
+        std::fs::write(from.path().join("src/lib.rs"), "one\ntwo\nthree\n")?;
+        std::fs::write(to.path().join("src/lib.rs"), "one\n2\nthree\n")?;
+        std::fs::write(from.path().join("same.txt"), "same")?;
+        std::fs::write(to.path().join("same.txt"), "same")?;
+        std::fs::write(from.path().join("old.txt"), "gone")?;
+        std::fs::write(to.path().join("new.txt"), "fresh")?;
+        std::fs::write(to.path().join("blob.bin"), [0u8, 1, 2])?;
+
+        let diff = compare(from.path(), to.path())?;
+        assert_eq!(diff.added, vec!["blob.bin", "new.txt"]);
+        assert_eq!(diff.removed, vec!["old.txt"]);
+        assert_eq!(diff.modified, vec!["src/lib.rs"]);
+
+        let patch = unified_diff(from.path(), to.path(), "src/lib.rs")?.expect("text diff");
+        assert_eq!(
+            patch,
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n"
+        );
+        let added = unified_diff(from.path(), to.path(), "new.txt")?.expect("text diff");
+        assert!(added.contains("@@ -0,0 +1,1 @@\n+fresh\n"));
+        assert!(unified_diff(from.path(), to.path(), "blob.bin")?.is_none());
+        Ok(())
+    }
+}
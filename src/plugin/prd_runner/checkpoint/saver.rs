@@ -1,8 +1,15 @@
-use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 use walkdir::WalkDir;
 
+use crate::plugin::prd_runner::config::prd::Requirement;
+use crate::plugin::prd_runner::config::SnapshotSource;
+
 use crate::plugin::prd_runner::logger::report::IterationReport;
 use crate::plugin::prd_runner::loop_engine::state::EngineState;
 
@@ -10,8 +17,33 @@ use crate::plugin::prd_runner::loop_engine::state::EngineState;
 pub struct CheckpointManager {
     root: PathBuf,
     max_keep: usize,
+    snapshot_source: SnapshotSource,
+    snapshot_untracked: bool,
+    ignore: Vec<String>,
+    incremental: bool,
+    artifacts: BTreeMap<String, Vec<String>>,
+}
+
+/// Per-checkpoint record of snapshot file hashes, written when `checkpoint.incremental` is on.
+pub const SNAPSHOT_MANIFEST: &str = "snapshot_manifest.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Keyed by workspace-relative path with `/` separators.
+    pub files: BTreeMap<String, ManifestEntry>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub hash: String,
+    pub size: u64,
+    /// False when the file was unchanged and linked from the previous snapshot.
+    pub copied: bool,
+}
+
+/// Workspace file listing extra snapshot ignore patterns, one per line.
+pub const IGNORE_FILE: &str = ".autocodeignore";
+
 impl CheckpointManager {
     pub fn new(root: impl AsRef<Path>, max_keep: usize) -> Result<Self> {
         let root = root.as_ref().to_path_buf();
@@ -21,9 +53,42 @@ impl CheckpointManager {
         Ok(Self {
             root,
             max_keep: max_keep.max(1),
+            snapshot_source: SnapshotSource::Walk,
+            snapshot_untracked: false,
+            ignore: Vec::new(),
+            incremental: false,
+            artifacts: BTreeMap::new(),
         })
     }
 
+    pub fn with_snapshot_source(mut self, source: SnapshotSource, include_untracked: bool) -> Self {
+        self.snapshot_source = source;
+        self.snapshot_untracked = include_untracked;
+        self
+    }
+
+    /// Extra `checkpoint.ignore` patterns, on top of the builtin skip list and `.autocodeignore`.
+    pub fn with_ignore_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.ignore = patterns;
+        self
+    }
+
+    /// Hard-links files whose content hash matches the previous checkpoint instead of copying.
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Records the `产出物` paths declared by each requirement so `save` copies them.
+    pub fn with_artifacts(mut self, requirements: &[Requirement]) -> Self {
+        self.artifacts = requirements
+            .iter()
+            .filter(|req| !req.artifacts.is_empty())
+            .map(|req| (req.id.clone(), req.artifacts.clone()))
+            .collect();
+        self
+    }
+
     pub fn save(
         &self,
         iteration: u32,
@@ -32,6 +97,11 @@ impl CheckpointManager {
         report: &IterationReport,
         workdir: &Path,
     ) -> Result<PathBuf> {
+        let previous = if self.incremental {
+            self.latest_manifest()?
+        } else {
+            None
+        };
         let checkpoint_dir = self.next_checkpoint_dir(iteration);
 
         std::fs::create_dir_all(&checkpoint_dir).with_context(|| {
@@ -50,7 +120,13 @@ impl CheckpointManager {
         std::fs::write(&state_path, state_content)
             .with_context(|| format!("failed to write {}", state_path.display()))?;
 
-        let report_content = serde_json::to_string_pretty(report)
+        let mut report = report.clone();
+        for (req_id, copied) in self.copy_artifacts(workdir, &checkpoint_dir.join("artifacts"))? {
+            if let Some(req_report) = report.req_status.get_mut(&req_id) {
+                req_report.artifacts = copied;
+            }
+        }
+        let report_content = serde_json::to_string_pretty(&report)
             .context("failed to serialize checkpoint report")?;
         std::fs::write(&report_path, report_content)
             .with_context(|| format!("failed to write {}", report_path.display()))?;
@@ -64,13 +140,124 @@ impl CheckpointManager {
         })?;
 
         let snapshot_dir = checkpoint_dir.join("code_snapshot");
-        copy_workspace_snapshot(workdir, &snapshot_dir)?;
+        let mut ignore = self.ignore.clone();
+        ignore.extend(read_ignore_file(workdir)?);
+        if self.incremental {
+            let files = self.snapshot_file_list(workdir, &ignore)?;
+            let manifest =
+                write_incremental_snapshot(workdir, &snapshot_dir, &files, previous.as_ref())?;
+            let manifest_path = checkpoint_dir.join(SNAPSHOT_MANIFEST);
+            let content = serde_json::to_string_pretty(&manifest)
+                .context("failed to serialize snapshot manifest")?;
+            std::fs::write(&manifest_path, content)
+                .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+            self.prune_old_checkpoints()?;
+            return Ok(checkpoint_dir);
+        }
+        match self.snapshot_source {
+            SnapshotSource::Walk => copy_workspace_snapshot(workdir, &snapshot_dir, &ignore)?,
+            SnapshotSource::Git => match git_listed_files(workdir, self.snapshot_untracked) {
+                Ok(files) => copy_listed_files(workdir, &snapshot_dir, &files, &ignore)?,
+                Err(err) => {
+                    warn!(error = %format!("{:#}", err), "git snapshot unavailable, walking workspace");
+                    copy_workspace_snapshot(workdir, &snapshot_dir, &ignore)?;
+                }
+            },
+        }
 
         self.prune_old_checkpoints()?;
 
         Ok(checkpoint_dir)
     }
 
+    /// Copies a checkpoint's `code_snapshot/` back over `workdir`; skipped dirs are left untouched.
+    pub fn restore(checkpoint_dir: &Path, workdir: &Path) -> Result<()> {
+        let snapshot_dir = checkpoint_dir.join("code_snapshot");
+        if !snapshot_dir.is_dir() {
+            bail!(
+                "checkpoint {} has no code_snapshot",
+                checkpoint_dir.display()
+            );
+        }
+        copy_workspace_snapshot(&snapshot_dir, workdir, &[])
+            .with_context(|| format!("failed to restore {}", snapshot_dir.display()))
+    }
+
+    /// Copies declared artifacts that exist, returning the copied paths per requirement.
+    fn copy_artifacts(&self, workdir: &Path, dst: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+        let mut copied = BTreeMap::new();
+        for (req_id, paths) in &self.artifacts {
+            for raw in paths {
+                let Some(rel) = workspace_relative(raw) else {
+                    warn!(req = %req_id, path = %raw, "artifact path escapes the workspace, skipping");
+                    continue;
+                };
+                let src = workdir.join(&rel);
+                let out = dst.join(&rel);
+                if src.is_dir() {
+                    copy_workspace_snapshot(&src, &out, &[])?;
+                } else if src.is_file() {
+                    if let Some(parent) = out.parent() {
+                        std::fs::create_dir_all(parent).with_context(|| {
+                            format!("failed to create artifact dir {}", parent.display())
+                        })?;
+                    }
+                    std::fs::copy(&src, &out).with_context(|| {
+                        format!(
+                            "failed to copy artifact {} -> {}",
+                            src.display(),
+                            out.display()
+                        )
+                    })?;
+                } else {
+                    continue;
+                }
+                copied
+                    .entry(req_id.clone())
+                    .or_insert_with(Vec::new)
+                    .push(rel.display().to_string());
+            }
+        }
+        Ok(copied)
+    }
+
+    fn snapshot_file_list(&self, workdir: &Path, ignore: &[String]) -> Result<Vec<PathBuf>> {
+        let files = match self.snapshot_source {
+            SnapshotSource::Walk => list_workspace_files(workdir, ignore)?,
+            SnapshotSource::Git => match git_listed_files(workdir, self.snapshot_untracked) {
+                Ok(files) => files,
+                Err(err) => {
+                    warn!(error = %format!("{:#}", err), "git snapshot unavailable, walking workspace");
+                    list_workspace_files(workdir, ignore)?
+                }
+            },
+        };
+        Ok(files
+            .into_iter()
+            .filter(|rel| !is_ignored(rel, ignore) && workdir.join(rel).is_file())
+            .collect())
+    }
+
+    /// Snapshot dir and manifest of the newest checkpoint that recorded one.
+    fn latest_manifest(&self) -> Result<Option<(PathBuf, SnapshotManifest)>> {
+        let mut dirs = std::fs::read_dir(&self.root)
+            .with_context(|| format!("failed to read checkpoint root {}", self.root.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join(SNAPSHOT_MANIFEST).is_file())
+            .collect::<Vec<_>>();
+        dirs.sort_by_key(|path| checkpoint_order_key(path));
+        let Some(dir) = dirs.pop() else {
+            return Ok(None);
+        };
+        let path = dir.join(SNAPSHOT_MANIFEST);
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let manifest = serde_json::from_str(&raw)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Some((dir.join("code_snapshot"), manifest)))
+    }
+
     fn next_checkpoint_dir(&self, iteration: u32) -> PathBuf {
         let base = format!("checkpoint_{:03}", iteration);
         let primary = self.root.join(&base);
@@ -139,13 +326,13 @@ fn parse_checkpoint_index(name: &str) -> Option<u32> {
     digits.parse::<u32>().ok()
 }
 
-fn copy_workspace_snapshot(src: &Path, dst: &Path) -> Result<()> {
+fn copy_workspace_snapshot(src: &Path, dst: &Path, ignore: &[String]) -> Result<()> {
     std::fs::create_dir_all(dst)
         .with_context(|| format!("failed to create snapshot dir {}", dst.display()))?;
 
     let walker = WalkDir::new(src).into_iter().filter_entry(|entry| {
         let rel = entry.path().strip_prefix(src).unwrap_or(entry.path());
-        !should_skip(rel)
+        !is_ignored(rel, ignore)
     });
 
     for entry in walker {
@@ -186,6 +373,172 @@ fn copy_workspace_snapshot(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
+fn list_workspace_files(src: &Path, ignore: &[String]) -> Result<Vec<PathBuf>> {
+    let walker = WalkDir::new(src).into_iter().filter_entry(|entry| {
+        let rel = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        !is_ignored(rel, ignore)
+    });
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.with_context(|| format!("failed to walk workspace {}", src.display()))?;
+        if entry.file_type().is_file() {
+            if let Ok(rel) = entry.path().strip_prefix(src) {
+                files.push(rel.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Copies changed files and hard-links (or copies, where linking fails) unchanged ones from
+/// the previous snapshot, so every checkpoint still holds a complete tree.
+fn write_incremental_snapshot(
+    src: &Path,
+    dst: &Path,
+    files: &[PathBuf],
+    previous: Option<&(PathBuf, SnapshotManifest)>,
+) -> Result<SnapshotManifest> {
+    std::fs::create_dir_all(dst)
+        .with_context(|| format!("failed to create snapshot dir {}", dst.display()))?;
+
+    let mut manifest = SnapshotManifest::default();
+    for rel in files {
+        let path = src.join(rel);
+        let content =
+            std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let hash = format!("{:016x}", fnv1a_64(&content));
+        let size = content.len() as u64;
+        let key = rel.to_string_lossy().replace('\\', "/");
+
+        let out = dst.join(rel);
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create snapshot parent {}", parent.display())
+            })?;
+        }
+        let unchanged = previous
+            .filter(|(_, manifest)| {
+                manifest
+                    .files
+                    .get(&key)
+                    .is_some_and(|entry| entry.hash == hash && entry.size == size)
+            })
+            .map(|(dir, _)| dir.join(rel))
+            // The hash is only a fast filter; linking a colliding file would corrupt the snapshot.
+            .filter(|prev| std::fs::read(prev).is_ok_and(|bytes| bytes == content));
+        let copied = match unchanged {
+            Some(prev) => {
+                if std::fs::hard_link(&prev, &out).is_err() {
+                    std::fs::copy(&prev, &out).with_context(|| {
+                        format!(
+                            "failed to copy snapshot file {} -> {}",
+                            prev.display(),
+                            out.display()
+                        )
+                    })?;
+                }
+                false
+            }
+            None => {
+                std::fs::copy(&path, &out).with_context(|| {
+                    format!(
+                        "failed to copy snapshot file {} -> {}",
+                        path.display(),
+                        out.display()
+                    )
+                })?;
+                true
+            }
+        };
+        manifest
+            .files
+            .insert(key, ManifestEntry { hash, size, copied });
+    }
+    Ok(manifest)
+}
+
+/// Stable across builds, unlike `DefaultHasher`, so manifests stay comparable after upgrades.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn git_listed_files(workdir: &Path, include_untracked: bool) -> Result<Vec<PathBuf>> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(workdir)
+        .args(["ls-files", "-z", "--cached"]);
+    if include_untracked {
+        command.args(["--others", "--exclude-standard"]);
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("failed to run git ls-files")?;
+    if !output.status.success() {
+        bail!(
+            "git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output
+        .stdout
+        .split(|byte| *byte == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| PathBuf::from(String::from_utf8_lossy(entry).as_ref()))
+        .collect())
+}
+
+fn copy_listed_files(src: &Path, dst: &Path, files: &[PathBuf], ignore: &[String]) -> Result<()> {
+    std::fs::create_dir_all(dst)
+        .with_context(|| format!("failed to create snapshot dir {}", dst.display()))?;
+
+    for rel in files {
+        let path = src.join(rel);
+        if is_ignored(rel, ignore) || !path.is_file() {
+            continue;
+        }
+
+        let out = dst.join(rel);
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create snapshot parent {}", parent.display())
+            })?;
+        }
+        std::fs::copy(&path, &out).with_context(|| {
+            format!(
+                "failed to copy snapshot file {} -> {}",
+                path.display(),
+                out.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Normalizes an artifact path, rejecting absolute paths and `..` that leave the workspace.
+fn workspace_relative(raw: &str) -> Option<PathBuf> {
+    let mut rel = PathBuf::new();
+    for component in Path::new(raw).components() {
+        match component {
+            Component::Normal(part) => rel.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !rel.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!rel.as_os_str().is_empty()).then_some(rel)
+}
+
 fn should_skip(path: &Path) -> bool {
     let Some(first) = path.components().next() else {
         return false;
@@ -198,6 +551,78 @@ fn should_skip(path: &Path) -> bool {
     )
 }
 
+fn read_ignore_file(workdir: &Path) -> Result<Vec<String>> {
+    let path = workdir.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn is_ignored(path: &Path, patterns: &[String]) -> bool {
+    should_skip(path)
+        || patterns
+            .iter()
+            .any(|pattern| matches_ignore_pattern(path, pattern))
+}
+
+/// `name` or `*.log` matches that name at any depth; `dir/sub` or `/dist` is anchored at the
+/// workspace root and also covers everything below it. `*` and `?` are wildcards.
+fn matches_ignore_pattern(path: &Path, pattern: &str) -> bool {
+    let pattern = pattern
+        .trim()
+        .trim_start_matches("./")
+        .trim_end_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+    let parts = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    let anchored = pattern.trim_start_matches('/');
+    if anchored.contains('/') || anchored.len() != pattern.len() {
+        let segments = anchored.split('/').collect::<Vec<_>>();
+        return parts.len() >= segments.len()
+            && segments
+                .iter()
+                .zip(&parts)
+                .all(|(segment, part)| wildcard_match(segment, part));
+    }
+    parts.iter().any(|part| wildcard_match(pattern, part))
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -206,34 +631,37 @@ mod tests {
     use chrono::Utc;
     use tempfile::TempDir;
 
-    use crate::plugin::prd_runner::logger::report::{IterationReport, ReqReport};
+    use crate::plugin::prd_runner::logger::report::{IterationReport, IterationTiming, ReqReport};
     use crate::plugin::prd_runner::loop_engine::state::EngineState;
 
-    use super::CheckpointManager;
+    use super::{CheckpointManager, SnapshotManifest, SNAPSHOT_MANIFEST};
+    use crate::plugin::prd_runner::config::prd::Requirement;
+    use crate::plugin::prd_runner::config::SnapshotSource;
 
-    #[test]
-    fn saves_checkpoint_files() -> Result<()> {
-        let workspace = TempDir::new()?;
-        let checkpoints = TempDir::new()?;
-        let prd_path = workspace.path().join("prd.md");
-        std::fs::write(&prd_path, "# PRD")?;
-        std::fs::write(workspace.path().join("README.md"), "demo")?;
-        std::fs::create_dir_all(workspace.path().join(".autocode/checkpoints/nested"))?;
-        std::fs::write(
-            workspace
-                .path()
-                .join(".autocode/checkpoints/nested/marker.txt"),
-            "skip me",
-        )?;
-
-        let state = EngineState {
+    fn sample_state() -> EngineState {
+        EngineState {
             iteration: 1,
             req_status: BTreeMap::new(),
-        };
-        let report = IterationReport {
+            accumulated_runtime_ms: 0,
+        }
+    }
+
+    fn sample_report() -> IterationReport {
+        IterationReport {
             iteration: 1,
             timestamp: Utc::now(),
-            duration_seconds: 1,
+            duration_seconds: 0,
+            timing: IterationTiming::default(),
+            req_status: BTreeMap::new(),
+            overall_progress: 0.0,
+            has_progress: false,
+            next_actions: Vec::new(),
+        }
+    }
+
+    /// `sample_report` with REQ-001 done.
+    fn done_report() -> IterationReport {
+        IterationReport {
             req_status: BTreeMap::from([(
                 "REQ-001".to_string(),
                 ReqReport {
@@ -241,12 +669,33 @@ mod tests {
                     progress: Some(1.0),
                     evidence: None,
                     error: None,
+                    blocked_reason: None,
+                    artifacts: Vec::new(),
                 },
             )]),
             overall_progress: 1.0,
             has_progress: true,
-            next_actions: vec![],
-        };
+            ..sample_report()
+        }
+    }
+
+    #[test]
+    fn saves_checkpoint_files() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("prd.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::write(workspace.path().join("README.md"), "demo")?;
+        std::fs::create_dir_all(workspace.path().join(".autocode/checkpoints/nested"))?;
+        std::fs::write(
+            workspace
+                .path()
+                .join(".autocode/checkpoints/nested/marker.txt"),
+            "skip me",
+        )?;
+
+        let state = sample_state();
+        let report = done_report();
 
         let manager = CheckpointManager::new(checkpoints.path(), 2)?;
         let path = manager.save(1, &prd_path, &state, &report, workspace.path())?;
@@ -259,6 +708,151 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn snapshot_honors_configured_and_file_ignore_patterns() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("PRD.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        for file in [
+            "src/main.rs",
+            "node_modules/pkg/index.js",
+            "web/node_modules/pkg/index.js",
+            "dist/app.js",
+            "web/dist/keep.js",
+            "debug.log",
+        ] {
+            let path = workspace.path().join(file);
+            std::fs::create_dir_all(path.parent().expect("parent"))?;
+            std::fs::write(path, "x")?;
+        }
+        std::fs::write(
+            workspace.path().join(".autocodeignore"),
+            "# build output\n/dist\n",
+        )?;
+
+        let state = sample_state();
+        let report = sample_report();
+        let manager = CheckpointManager::new(checkpoints.path(), 2)?
+            .with_ignore_patterns(vec!["node_modules/".to_string(), "*.log".to_string()]);
+        let snapshot = manager
+            .save(1, &prd_path, &state, &report, workspace.path())?
+            .join("code_snapshot");
+
+        assert!(snapshot.join("src/main.rs").exists());
+        assert!(snapshot.join("web/dist/keep.js").exists());
+        assert!(!snapshot.join("node_modules").exists());
+        assert!(!snapshot.join("web/node_modules").exists());
+        assert!(!snapshot.join("dist").exists());
+        assert!(!snapshot.join("debug.log").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_snapshot_copies_only_changed_files() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("PRD.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::create_dir_all(workspace.path().join("src"))?;
+        std::fs::write(workspace.path().join("src/lib.rs"), "pub fn a() {}")?;
+        std::fs::write(workspace.path().join("src/main.rs"), "fn main() {}")?;
+
+        let state = sample_state();
+        let report = sample_report();
+        let manager = CheckpointManager::new(checkpoints.path(), 5)?.with_incremental(true);
+        let manifest = |dir: &std::path::Path| -> Result<SnapshotManifest> {
+            Ok(serde_json::from_str(&std::fs::read_to_string(
+                dir.join(SNAPSHOT_MANIFEST),
+            )?)?)
+        };
+
+        let first = manager.save(1, &prd_path, &state, &report, workspace.path())?;
+        assert!(manifest(&first)?.files.values().all(|entry| entry.copied));
+
+        std::fs::write(workspace.path().join("src/lib.rs"), "pub fn b() {}")?;
+        let second = manager.save(2, &prd_path, &state, &report, workspace.path())?;
+        let files = manifest(&second)?.files;
+        let copied = files
+            .iter()
+            .filter(|(_, entry)| entry.copied)
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(copied, vec!["src/lib.rs"]);
+        assert!(files.contains_key("src/main.rs"));
+        assert!(files.contains_key("PRD.md"));
+
+        let restored = TempDir::new()?;
+        CheckpointManager::restore(&second, restored.path())?;
+        assert_eq!(
+            std::fs::read_to_string(restored.path().join("src/lib.rs"))?,
+            "pub fn b() {}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(restored.path().join("src/main.rs"))?,
+            "fn main() {}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn incremental_snapshot_copies_when_previous_bytes_differ() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("PRD.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::write(workspace.path().join("main.rs"), "fn main() {}")?;
+
+        let state = sample_state();
+        let report = sample_report();
+        let manager = CheckpointManager::new(checkpoints.path(), 5)?.with_incremental(true);
+        let first = manager.save(1, &prd_path, &state, &report, workspace.path())?;
+        // Same size and recorded hash, different bytes: what a hash collision looks like.
+        std::fs::write(first.join("code_snapshot/main.rs"), "fn evil() {}")?;
+
+        let second = manager.save(2, &prd_path, &state, &report, workspace.path())?;
+        let manifest: SnapshotManifest =
+            serde_json::from_str(&std::fs::read_to_string(second.join(SNAPSHOT_MANIFEST))?)?;
+        assert!(manifest.files["main.rs"].copied);
+        assert!(!manifest.files["PRD.md"].copied);
+        assert_eq!(
+            std::fs::read_to_string(second.join("code_snapshot/main.rs"))?,
+            "fn main() {}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn restores_workspace_from_snapshot() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("PRD.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::create_dir_all(workspace.path().join("src"))?;
+        std::fs::write(workspace.path().join("src/lib.rs"), "pub fn ok() {}")?;
+
+        let state = sample_state();
+        let report = sample_report();
+        let manager = CheckpointManager::new(checkpoints.path(), 2)?;
+        let checkpoint = manager.save(1, &prd_path, &state, &report, workspace.path())?;
+
+        std::fs::write(workspace.path().join("src/lib.rs"), "broken")?;
+        std::fs::create_dir_all(workspace.path().join(".autocode"))?;
+        std::fs::write(workspace.path().join(".autocode/config.toml"), "keep")?;
+
+        CheckpointManager::restore(&checkpoint, workspace.path())?;
+
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join("src/lib.rs"))?,
+            "pub fn ok() {}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join(".autocode/config.toml"))?,
+            "keep"
+        );
+        Ok(())
+    }
+
     #[test]
     fn allocates_unique_dir_when_same_iteration_is_saved_multiple_times() -> Result<()> {
         let workspace = TempDir::new()?;
@@ -267,19 +861,8 @@ mod tests {
         std::fs::write(&prd_path, "# PRD")?;
         std::fs::write(workspace.path().join("README.md"), "demo")?;
 
-        let state = EngineState {
-            iteration: 1,
-            req_status: BTreeMap::new(),
-        };
-        let report = IterationReport {
-            iteration: 1,
-            timestamp: Utc::now(),
-            duration_seconds: 1,
-            req_status: BTreeMap::new(),
-            overall_progress: 0.0,
-            has_progress: false,
-            next_actions: vec![],
-        };
+        let state = sample_state();
+        let report = sample_report();
 
         let manager = CheckpointManager::new(checkpoints.path(), 10)?;
         let first = manager.save(1, &prd_path, &state, &report, workspace.path())?;
@@ -294,4 +877,90 @@ mod tests {
         assert!(third.join("state.json").exists());
         Ok(())
     }
+
+    #[test]
+    fn git_snapshot_copies_only_tracked_files() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(workspace.path())
+                .args(args)
+                .output()
+        };
+        if git(&["init", "-q"])
+            .map(|out| !out.status.success())
+            .unwrap_or(true)
+        {
+            return Ok(());
+        }
+
+        let prd_path = workspace.path().join("prd.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::create_dir_all(workspace.path().join("src"))?;
+        std::fs::write(workspace.path().join("src/lib.rs"), "// tracked")?;
+        std::fs::write(workspace.path().join("scratch.txt"), "untracked")?;
+        git(&["add", "src/lib.rs", "prd.md"])?;
+
+        let state = sample_state();
+        let report = sample_report();
+
+        let manager = CheckpointManager::new(checkpoints.path(), 2)?
+            .with_snapshot_source(SnapshotSource::Git, false);
+        let path = manager.save(1, &prd_path, &state, &report, workspace.path())?;
+
+        let snapshot = path.join("code_snapshot");
+        assert!(snapshot.join("src/lib.rs").exists());
+        assert!(snapshot.join("prd.md").exists());
+        assert!(!snapshot.join("scratch.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn copies_declared_artifacts_and_lists_them_in_report() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let checkpoints = TempDir::new()?;
+        let prd_path = workspace.path().join("prd.md");
+        std::fs::write(&prd_path, "# PRD")?;
+        std::fs::create_dir_all(workspace.path().join("dist/assets"))?;
+        std::fs::write(workspace.path().join("dist/assets/app.js"), "bundle")?;
+        std::fs::write(workspace.path().join("api.md"), "docs")?;
+
+        let requirement = Requirement {
+            id: "REQ-001".to_string(),
+            title: "build".to_string(),
+            priority: None,
+            description: "build".to_string(),
+            validate_command: "true".to_string(),
+            validate_commands: Vec::new(),
+            pass_condition: "退出码 = 0".to_string(),
+            tasks: Vec::new(),
+            artifacts: vec![
+                "dist".to_string(),
+                "./api.md".to_string(),
+                "missing.txt".to_string(),
+                "../outside.txt".to_string(),
+            ],
+            provider_timeout: None,
+            depends_on: Vec::new(),
+            workdir: None,
+        };
+        let state = EngineState::new(std::slice::from_ref(&requirement));
+        let report = done_report();
+
+        let manager = CheckpointManager::new(checkpoints.path(), 2)?.with_artifacts(&[requirement]);
+        let path = manager.save(1, &prd_path, &state, &report, workspace.path())?;
+
+        assert!(path.join("artifacts/dist/assets/app.js").exists());
+        assert!(path.join("artifacts/api.md").exists());
+        let saved: IterationReport = serde_json::from_str(&std::fs::read_to_string(
+            path.join("iteration_report.json"),
+        )?)?;
+        assert_eq!(
+            saved.req_status["REQ-001"].artifacts,
+            vec!["dist".to_string(), "api.md".to_string()]
+        );
+        Ok(())
+    }
 }
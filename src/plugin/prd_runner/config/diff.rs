@@ -0,0 +1,207 @@
+use std::fmt;
+
+use super::prd::{AcceptanceCriterion, PrdDocument, Requirement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl DiffKind {
+    fn marker(self) -> char {
+        match self {
+            DiffKind::Added => '+',
+            DiffKind::Removed => '-',
+            DiffKind::Changed => '~',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    pub kind: DiffKind,
+    pub section: &'static str,
+    pub key: String,
+    pub fields: Vec<&'static str>,
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.kind.marker(), self.section, self.key)?;
+        if !self.fields.is_empty() {
+            write!(f, " ({})", self.fields.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn diff_prd(base: &PrdDocument, current: &PrdDocument) -> Vec<DiffEntry> {
+    let mut entries = diff_items(
+        "requirement",
+        &base.requirements,
+        &current.requirements,
+        |req| req.id.as_str(),
+        requirement_fields,
+    );
+    entries.extend(diff_items(
+        "acceptance",
+        &base.acceptance_criteria,
+        &current.acceptance_criteria,
+        |criterion| criterion.name.as_str(),
+        criterion_fields,
+    ));
+    entries
+}
+
+fn diff_items<T: PartialEq>(
+    section: &'static str,
+    base: &[T],
+    current: &[T],
+    key: impl Fn(&T) -> &str,
+    changed_fields: impl Fn(&T, &T) -> Vec<&'static str>,
+) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+
+    for old in base {
+        match current.iter().find(|new| key(new) == key(old)) {
+            None => entries.push(DiffEntry {
+                kind: DiffKind::Removed,
+                section,
+                key: key(old).to_string(),
+                fields: Vec::new(),
+            }),
+            Some(new) if new != old => entries.push(DiffEntry {
+                kind: DiffKind::Changed,
+                section,
+                key: key(old).to_string(),
+                fields: changed_fields(old, new),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for new in current {
+        if !base.iter().any(|old| key(old) == key(new)) {
+            entries.push(DiffEntry {
+                kind: DiffKind::Added,
+                section,
+                key: key(new).to_string(),
+                fields: Vec::new(),
+            });
+        }
+    }
+
+    entries
+}
+
+fn requirement_fields(old: &Requirement, new: &Requirement) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if old.title != new.title {
+        fields.push("title");
+    }
+    if old.priority != new.priority {
+        fields.push("priority");
+    }
+    if old.description != new.description {
+        fields.push("description");
+    }
+    if old.validation_commands() != new.validation_commands() {
+        fields.push("validate_command");
+    }
+    if old.pass_condition != new.pass_condition {
+        fields.push("pass_condition");
+    }
+    if old.tasks != new.tasks {
+        fields.push("tasks");
+    }
+    if old.artifacts != new.artifacts {
+        fields.push("artifacts");
+    }
+    if old.provider_timeout != new.provider_timeout {
+        fields.push("provider_timeout");
+    }
+    if old.depends_on != new.depends_on {
+        fields.push("depends_on");
+    }
+    fields
+}
+
+fn criterion_fields(old: &AcceptanceCriterion, new: &AcceptanceCriterion) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if old.validate_command != new.validate_command {
+        fields.push("validate_command");
+    }
+    if old.pass_condition != new.pass_condition {
+        fields.push("pass_condition");
+    }
+    if old.phase != new.phase {
+        fields.push("phase");
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_prd, DiffKind};
+    use crate::plugin::prd_runner::config::parser::parse_prd_str;
+
+    const BASE: &str = r#"
+# PRD: test
+> 项目名称：demo
+
+## 1. 项目上下文
+- type: rust
+
+## 2. 需求列表
+### REQ-001: build
+| 字段 | 值 |
+|------|-----|
+| **描述** | run build |
+| **验证命令** | `cargo build` |
+| **通过条件** | 退出码 = 0 |
+
+### REQ-002: lint
+| 字段 | 值 |
+|------|-----|
+| **描述** | run lint |
+| **验证命令** | `cargo clippy` |
+| **通过条件** | 退出码 = 0 |
+
+## 3. 验收标准
+| 标准 | 验证命令 | 通过条件 |
+|------|----------|----------|
+| 构建成功 | `cargo build` | 退出码 = 0 |
+"#;
+
+    #[test]
+    fn reports_changed_added_and_removed_items() {
+        let base = parse_prd_str(BASE).expect("base prd");
+        let current = parse_prd_str(
+            &BASE
+                .replace(
+                    "`cargo build` |\n| **通过条件**",
+                    "`cargo build --release` |\n| **通过条件**",
+                )
+                .replace("### REQ-002: lint", "### REQ-003: lint")
+                .replace("| 构建成功 |", "| 发布构建 |"),
+        )
+        .expect("current prd");
+
+        let entries = diff_prd(&base, &current);
+        let rendered = entries.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            vec![
+                "~ requirement REQ-001 (validate_command)",
+                "- requirement REQ-002",
+                "+ requirement REQ-003",
+                "- acceptance 构建成功",
+                "+ acceptance 发布构建",
+            ]
+        );
+        assert_eq!(entries[0].kind, DiffKind::Changed);
+        assert!(diff_prd(&base, &base).is_empty());
+    }
+}
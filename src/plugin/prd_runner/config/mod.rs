@@ -1,9 +1,14 @@
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use humantime::parse_duration;
 use serde::{Deserialize, Serialize};
 
+use crate::plugin::prd_runner::logger::redact::default_redact_patterns;
+
+pub mod diff;
 pub mod parser;
 pub mod prd;
 
@@ -19,12 +24,63 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub provider: ProviderConfig,
+    #[serde(default)]
+    pub process: ProcessConfig,
+    #[serde(default)]
+    pub prompt: PromptConfig,
+}
+
+impl AppConfig {
+    /// Reads `<workdir>/.autocode/config.toml`, falling back to defaults when it is absent.
+    /// Tables owned by the CLI (`[tui]`, `[chat]`) are ignored here.
+    pub fn load_from_dir(workdir: &Path) -> Result<Self> {
+        let path = workdir.join(".autocode").join("config.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load_from_file(&path)
+    }
+
+    /// Reads an explicit config file; unlike [`AppConfig::load_from_dir`] a missing file is an error.
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            bail!("config file not found: {}", path.display());
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// `--config` (relative to `workdir`) when given, otherwise the workspace default.
+    pub fn load(workdir: &Path, config_path: Option<&Path>) -> Result<Self> {
+        match config_path {
+            Some(path) => Self::load_from_file(&workdir.join(path)),
+            None => Self::load_from_dir(workdir),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConvergenceConfig {
     #[serde(default = "defaults::max_runtime")]
     pub max_runtime: String,
+    /// Iterations allowed per invocation; `None` or 0 is unlimited.
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+    #[serde(default)]
+    pub acceptance_only_when_all_done: bool,
+    /// Stop after this many consecutive iterations without progress; 0 disables the guard.
+    #[serde(default = "defaults::max_stagnant_iterations")]
+    pub max_stagnant_iterations: u32,
+    /// Extra runs of a failing acceptance criterion before it counts as failed.
+    #[serde(default)]
+    pub acceptance_retries: u32,
+    /// Wait before each acceptance retry, multiplied by the attempt number.
+    #[serde(default = "defaults::acceptance_retry_backoff")]
+    pub acceptance_retry_backoff: String,
+    /// Stop once provider-reported input+output tokens reach this total (Claude only).
+    #[serde(default)]
+    pub max_total_tokens: Option<u64>,
 }
 
 impl ConvergenceConfig {
@@ -32,12 +88,27 @@ impl ConvergenceConfig {
         parse_duration(&self.max_runtime)
             .with_context(|| format!("invalid convergence.max_runtime: {}", self.max_runtime))
     }
+
+    pub fn acceptance_retry_backoff_duration(&self) -> Result<Duration> {
+        parse_duration(&self.acceptance_retry_backoff).with_context(|| {
+            format!(
+                "invalid convergence.acceptance_retry_backoff: {}",
+                self.acceptance_retry_backoff
+            )
+        })
+    }
 }
 
 impl Default for ConvergenceConfig {
     fn default() -> Self {
         Self {
             max_runtime: defaults::max_runtime(),
+            max_iterations: None,
+            acceptance_only_when_all_done: false,
+            max_stagnant_iterations: defaults::max_stagnant_iterations(),
+            acceptance_retries: 0,
+            acceptance_retry_backoff: defaults::acceptance_retry_backoff(),
+            max_total_tokens: None,
         }
     }
 }
@@ -48,6 +119,38 @@ pub struct ExecutionConfig {
     pub command_timeout: String,
     #[serde(default = "defaults::max_retry")]
     pub max_retry: u32,
+    #[serde(default)]
+    pub dry_run_query_provider: bool,
+    /// Kill a command as soon as any of these substrings shows up in its output.
+    #[serde(default)]
+    pub abort_on_output: Vec<String>,
+    /// Run-wide cap on command, provider and acceptance retries; unset means unlimited.
+    #[serde(default)]
+    pub max_total_retries: Option<u32>,
+    /// Shell program for validate commands; `auto` picks `cmd` on Windows and `bash` elsewhere.
+    #[serde(default = "defaults::shell")]
+    pub shell: String,
+    /// Arguments placed before the command; empty uses the shell's default (`bash -lc`, `cmd /C`, ...).
+    #[serde(default)]
+    pub shell_args: Vec<String>,
+    /// Extra environment variables for validate and acceptance commands.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Run acceptance criteria concurrently, at most `max_parallel_acceptance` at a time.
+    #[serde(default)]
+    pub parallel_acceptance: bool,
+    #[serde(default = "defaults::max_parallel_acceptance")]
+    pub max_parallel_acceptance: usize,
+    /// Times a requirement's provider call, commands and evaluation run per iteration
+    /// before it is marked failed; unlike `max_retry` this re-asks the provider.
+    #[serde(default = "defaults::max_requirement_attempts")]
+    pub max_requirement_attempts: u32,
+    /// Send stderr into the stdout pipe so `CommandResult::combined` keeps the real ordering.
+    #[serde(default)]
+    pub merge_output: bool,
+    /// Regexes for commands that are never run; an empty list disables the denylist.
+    #[serde(default = "defaults::denied_patterns")]
+    pub denied_patterns: Vec<String>,
 }
 
 impl ExecutionConfig {
@@ -59,6 +162,34 @@ impl ExecutionConfig {
             )
         })
     }
+
+    /// Resolves the program and leading arguments used to run a command string.
+    pub fn shell_invocation(&self) -> (String, Vec<String>) {
+        let program = match self.shell.trim() {
+            "" | "auto" if cfg!(windows) => "cmd",
+            "" | "auto" => "bash",
+            other => other,
+        };
+        if !self.shell_args.is_empty() {
+            return (program.to_string(), self.shell_args.clone());
+        }
+
+        let name = Path::new(program)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(program)
+            .to_ascii_lowercase();
+        let args: &[&str] = match name.as_str() {
+            "bash" => &["-lc"],
+            "cmd" => &["/C"],
+            "powershell" | "pwsh" => &["-NoProfile", "-Command"],
+            _ => &["-c"],
+        };
+        (
+            program.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        )
+    }
 }
 
 impl Default for ExecutionConfig {
@@ -66,6 +197,17 @@ impl Default for ExecutionConfig {
         Self {
             command_timeout: defaults::command_timeout(),
             max_retry: defaults::max_retry(),
+            dry_run_query_provider: false,
+            abort_on_output: Vec::new(),
+            max_total_retries: None,
+            shell: defaults::shell(),
+            shell_args: Vec::new(),
+            env: BTreeMap::new(),
+            parallel_acceptance: false,
+            max_parallel_acceptance: defaults::max_parallel_acceptance(),
+            max_requirement_attempts: defaults::max_requirement_attempts(),
+            merge_output: false,
+            denied_patterns: defaults::denied_patterns(),
         }
     }
 }
@@ -76,6 +218,27 @@ pub struct CheckpointConfig {
     pub enabled: bool,
     #[serde(default = "defaults::max_checkpoints")]
     pub max_checkpoints: usize,
+    #[serde(default)]
+    pub snapshot_source: SnapshotSource,
+    #[serde(default)]
+    pub snapshot_untracked: bool,
+    #[serde(default)]
+    pub only_on_progress: bool,
+    /// Extra snapshot ignore patterns (`node_modules`, `/dist`, `*.log`), merged with the
+    /// builtin skip list and the workspace `.autocodeignore`.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Hard-link files unchanged since the previous checkpoint instead of copying them again.
+    #[serde(default)]
+    pub incremental: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotSource {
+    #[default]
+    Walk,
+    Git,
 }
 
 impl Default for CheckpointConfig {
@@ -83,6 +246,11 @@ impl Default for CheckpointConfig {
         Self {
             enabled: defaults::checkpoint_enabled(),
             max_checkpoints: defaults::max_checkpoints(),
+            snapshot_source: SnapshotSource::Walk,
+            snapshot_untracked: false,
+            only_on_progress: false,
+            ignore: Vec::new(),
+            incremental: false,
         }
     }
 }
@@ -99,6 +267,36 @@ pub struct LoggingConfig {
     pub max_file_size_bytes: u64,
     #[serde(default = "defaults::log_max_rotated_files")]
     pub max_rotated_files: usize,
+    /// Print a one-line progress summary to stdout at most this often (CI mode).
+    #[serde(default)]
+    pub progress_interval: Option<String>,
+    /// Print progress lines on stderr instead; forced by `--output json`.
+    #[serde(default)]
+    pub progress_to_stderr: bool,
+    /// Gzip rotated log files (`events.log.1.gz`); the live file stays plain text.
+    #[serde(default)]
+    pub compress_rotated: bool,
+    /// Also write every event/AI/terminal record as a JSON line to `events.ndjson`.
+    #[serde(default)]
+    pub json: bool,
+    /// Event kinds kept in `events.log`/`events.ndjson`; empty keeps all. STOP and errors always pass.
+    #[serde(default)]
+    pub event_filter: Vec<String>,
+    /// Regexes masked as `***` in logs and session transcripts; defaults cover AWS, `sk-` and bearer tokens.
+    #[serde(default = "default_redact_patterns")]
+    pub redact_patterns: Vec<String>,
+}
+
+impl LoggingConfig {
+    pub fn progress_interval_duration(&self) -> Result<Option<Duration>> {
+        self.progress_interval
+            .as_deref()
+            .map(|raw| {
+                parse_duration(raw)
+                    .with_context(|| format!("invalid logging.progress_interval: {}", raw))
+            })
+            .transpose()
+    }
 }
 
 impl Default for LoggingConfig {
@@ -109,6 +307,12 @@ impl Default for LoggingConfig {
             output_summary_max_chars: defaults::output_summary_max_chars(),
             max_file_size_bytes: defaults::log_max_file_size_bytes(),
             max_rotated_files: defaults::log_max_rotated_files(),
+            progress_interval: None,
+            progress_to_stderr: false,
+            compress_rotated: false,
+            json: false,
+            event_filter: Vec::new(),
+            redact_patterns: default_redact_patterns(),
         }
     }
 }
@@ -117,6 +321,23 @@ impl Default for LoggingConfig {
 pub struct ProviderConfig {
     #[serde(default = "defaults::provider_response_timeout")]
     pub response_timeout: String,
+    #[serde(default)]
+    pub reprompt_on_no_commands: bool,
+    #[serde(default)]
+    pub min_timeout: Option<String>,
+    #[serde(default)]
+    pub inner_loop_max: u32,
+    #[serde(default)]
+    pub timeout_retries: u32,
+    /// Model for `ollama run`; falls back to `AUTOCODE_OLLAMA_MODEL`.
+    #[serde(default)]
+    pub ollama_model: Option<String>,
+    /// Pinned model for `claude --model`; also used by ollama when `ollama_model` is unset.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Provider calls allowed in flight at once across the process.
+    #[serde(default = "defaults::provider_max_concurrency")]
+    pub max_concurrency: usize,
 }
 
 impl ProviderConfig {
@@ -128,21 +349,80 @@ impl ProviderConfig {
             )
         })
     }
+
+    pub fn min_timeout_duration(&self) -> Result<Option<Duration>> {
+        self.min_timeout
+            .as_deref()
+            .map(|raw| {
+                parse_duration(raw)
+                    .with_context(|| format!("invalid provider.min_timeout: {}", raw))
+            })
+            .transpose()
+    }
+
+    /// Model to hand to the provider CLI started as `command`.
+    pub fn model_for(&self, command: &str) -> Option<String> {
+        match command.trim() {
+            "ollama" => self.ollama_model.clone().or_else(|| self.model.clone()),
+            _ => self.model.clone(),
+        }
+    }
 }
 
 impl Default for ProviderConfig {
     fn default() -> Self {
         Self {
             response_timeout: defaults::provider_response_timeout(),
+            reprompt_on_no_commands: false,
+            min_timeout: None,
+            inner_loop_max: 0,
+            timeout_retries: 0,
+            ollama_model: None,
+            model: None,
+            max_concurrency: defaults::provider_max_concurrency(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessConfig {
+    #[serde(default = "defaults::cmd_markers")]
+    pub cmd_markers: Vec<String>,
+    #[serde(default = "defaults::noop_sentinel")]
+    pub noop_sentinel: String,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            cmd_markers: defaults::cmd_markers(),
+            noop_sentinel: defaults::noop_sentinel(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptConfig {
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+    /// Appends the git file list and uncommitted diff to requirement prompts.
+    #[serde(default)]
+    pub workspace_context: bool,
+}
+
 mod defaults {
     pub fn max_runtime() -> String {
         "10m".to_string()
     }
 
+    pub fn max_stagnant_iterations() -> u32 {
+        5
+    }
+
+    pub fn acceptance_retry_backoff() -> String {
+        "0s".to_string()
+    }
+
     pub fn command_timeout() -> String {
         "10m".to_string()
     }
@@ -151,6 +431,10 @@ mod defaults {
         3
     }
 
+    pub fn shell() -> String {
+        "auto".to_string()
+    }
+
     pub fn checkpoint_enabled() -> bool {
         true
     }
@@ -182,4 +466,121 @@ mod defaults {
     pub fn provider_response_timeout() -> String {
         "10m".to_string()
     }
+
+    pub fn provider_max_concurrency() -> usize {
+        1
+    }
+
+    pub fn max_requirement_attempts() -> u32 {
+        1
+    }
+
+    pub fn max_parallel_acceptance() -> usize {
+        4
+    }
+
+    /// `rm -rf /` (or `/*`, `~`), the classic fork bomb, and piping a download into a shell.
+    pub fn denied_patterns() -> Vec<String> {
+        [
+            r"\brm\s+(?:-[A-Za-z-]+\s+)*(?:/\*?|~/?)(?:\s|;|&|\||$)",
+            r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:",
+            r"\b(?:curl|wget)\b[^|]*\|\s*(?:sudo\s+)?(?:ba|da|z)?sh\b",
+        ]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+    }
+
+    pub fn cmd_markers() -> Vec<String> {
+        vec!["CMD:".to_string(), "$".to_string()]
+    }
+
+    pub fn noop_sentinel() -> String {
+        "# no-op".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::AppConfig;
+    use crate::plugin::prd_runner::core::executor::CommandExecutor;
+
+    #[test]
+    fn loads_config_toml_and_executor_uses_it() -> Result<()> {
+        let tmp = TempDir::new()?;
+        assert_eq!(
+            AppConfig::load_from_dir(tmp.path())?
+                .execution
+                .command_timeout,
+            "10m"
+        );
+
+        std::fs::create_dir_all(tmp.path().join(".autocode"))?;
+        std::fs::write(
+            tmp.path().join(".autocode/config.toml"),
+            "[tui.theme]\nstatus = \"grey\"\n\n[execution]\ncommand_timeout = \"300ms\"\nmax_retry = 0\n\n[checkpoint]\nmax_checkpoints = 3\n",
+        )?;
+        let config = AppConfig::load_from_dir(tmp.path())?;
+        assert_eq!(config.checkpoint.max_checkpoints, 3);
+        assert_eq!(config.convergence.max_runtime, "10m");
+
+        let executor = CommandExecutor::new(config.execution, tmp.path())?;
+        let result = executor.run("sleep 5")?;
+        assert!(result.timed_out);
+        assert!(result.duration_ms < 3000);
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_config_file_overrides_default_location() -> Result<()> {
+        let tmp = TempDir::new()?;
+        std::fs::create_dir_all(tmp.path().join(".autocode"))?;
+        std::fs::write(
+            tmp.path().join(".autocode/config.toml"),
+            "[checkpoint]\nmax_checkpoints = 3\n",
+        )?;
+        std::fs::create_dir_all(tmp.path().join("profiles"))?;
+        std::fs::write(
+            tmp.path().join("profiles/fast.toml"),
+            "[convergence]\nmax_runtime = \"2m\"\n\n[checkpoint]\nmax_checkpoints = 7\n",
+        )?;
+
+        let config = AppConfig::load(tmp.path(), Some(Path::new("profiles/fast.toml")))?;
+        assert_eq!(config.convergence.max_runtime, "2m");
+        assert_eq!(config.checkpoint.max_checkpoints, 7);
+        assert_eq!(
+            AppConfig::load(tmp.path(), None)?
+                .checkpoint
+                .max_checkpoints,
+            3
+        );
+
+        let err = AppConfig::load(tmp.path(), Some(Path::new("profiles/missing.toml")))
+            .expect_err("missing explicit config must fail");
+        assert!(err.to_string().contains("config file not found"));
+        Ok(())
+    }
+
+    #[test]
+    fn execution_env_table_reaches_commands() -> Result<()> {
+        let tmp = TempDir::new()?;
+        std::fs::create_dir_all(tmp.path().join(".autocode"))?;
+        std::fs::write(
+            tmp.path().join(".autocode/config.toml"),
+            "[execution]\nmax_retry = 0\n\n[execution.env]\nMY_VAR = \"from-config\"\nRUST_BACKTRACE = \"1\"\n",
+        )?;
+        let config = AppConfig::load_from_dir(tmp.path())?;
+        assert_eq!(config.execution.env.len(), 2);
+
+        let executor = CommandExecutor::new(config.execution, tmp.path())?;
+        let result = executor.run("echo $MY_VAR $RUST_BACKTRACE")?;
+        assert!(result.success());
+        assert_eq!(result.stdout.trim(), "from-config 1");
+        Ok(())
+    }
 }
@@ -1,29 +1,121 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::Read;
 use std::path::Path;
 
 use anyhow::{anyhow, bail, Context, Result};
 use regex::Regex;
 
-use super::prd::{AcceptanceCriterion, PrdDocument, Requirement};
+use super::prd::{AcceptanceCriterion, AcceptancePhase, PrdDocument, Requirement};
+use crate::plugin::prd_runner::loop_engine::pass_condition::{closes_regex, opens_regex};
+
+pub const STDIN_PATH: &str = "-";
+
+const CONTEXT_HEADINGS: [&str; 2] = ["项目上下文", "project context"];
+const REQUIREMENTS_HEADINGS: [&str; 2] = ["需求列表", "requirements"];
+const ACCEPTANCE_HEADINGS: [&str; 2] = ["验收标准", "acceptance criteria"];
+
+/// Content the parser skipped that probably does not mean what the author intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    UnrecognizedHeading { line: String },
+    UnknownRequirementField { req: String, field: String },
+    TaskOutsideRequirement { task: String },
+    DuplicateRequirementId { id: String },
+    IgnoredAcceptanceRow { row: String },
+    UnknownAcceptancePhase { name: String, value: String },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::UnrecognizedHeading { line } => write!(
+                f,
+                "heading '{}' in 需求列表 is not recognized as a requirement (expected `### REQ-xxx: title`)",
+                line
+            ),
+            ParseWarning::UnknownRequirementField { req, field } => {
+                write!(f, "{}: unknown field '{}' ignored", req, field)
+            }
+            ParseWarning::TaskOutsideRequirement { task } => {
+                write!(f, "task '{}' appears before any requirement and is ignored", task)
+            }
+            ParseWarning::DuplicateRequirementId { id } => write!(
+                f,
+                "requirement id {} is defined more than once; progress is tracked as one requirement",
+                id
+            ),
+            ParseWarning::IgnoredAcceptanceRow { row } => write!(
+                f,
+                "acceptance row '{}' ignored (needs name, 验证命令 and 通过条件)",
+                row
+            ),
+            ParseWarning::UnknownAcceptancePhase { name, value } => write!(
+                f,
+                "acceptance '{}': unknown 阶段 '{}', using per-iteration (expected per-iteration or final)",
+                name, value
+            ),
+        }
+    }
+}
 
 pub fn parse_prd_file(path: &Path) -> Result<PrdDocument> {
+    parse_prd_file_with_warnings(path).map(|(doc, _)| doc)
+}
+
+pub fn parse_prd_file_with_warnings(path: &Path) -> Result<(PrdDocument, Vec<ParseWarning>)> {
+    if path.as_os_str() == STDIN_PATH {
+        let content = read_prd_reader(std::io::stdin().lock())?;
+        return parse_prd_str_with_warnings(&content);
+    }
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read PRD file {}", path.display()))?;
-    parse_prd_str(&content)
+    if content.trim().is_empty() {
+        bail!(
+            "{} is empty; run `autocode prd init` to scaffold a template",
+            path.display()
+        );
+    }
+    parse_prd_str_with_warnings(&content)
+}
+
+pub fn parse_prd_reader(reader: impl Read) -> Result<PrdDocument> {
+    parse_prd_str(&read_prd_reader(reader)?)
+}
+
+fn read_prd_reader(mut reader: impl Read) -> Result<String> {
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .context("failed to read PRD from stdin")?;
+    Ok(content)
 }
 
 pub fn parse_prd_str(markdown: &str) -> Result<PrdDocument> {
+    parse_prd_str_with_warnings(markdown).map(|(doc, _)| doc)
+}
+
+pub fn parse_prd_str_with_warnings(markdown: &str) -> Result<(PrdDocument, Vec<ParseWarning>)> {
+    if markdown.trim().is_empty() {
+        bail!("PRD is empty; run `autocode prd init` to scaffold a template");
+    }
     let lines: Vec<&str> = markdown.lines().collect();
+    let mut warnings = Vec::new();
 
     let project_name = extract_project_name(&lines);
-    let project_context = extract_section_text(&lines, "项目上下文")
-        .ok_or_else(|| anyhow!("missing required section heading: 项目上下文"))?;
-    let requirement_section = extract_section_text(&lines, "需求列表")
-        .ok_or_else(|| anyhow!("missing required section heading: 需求列表"))?;
-    let acceptance_section = extract_section_text(&lines, "验收标准")
-        .ok_or_else(|| anyhow!("missing required section heading: 验收标准"))?;
-
-    let requirements = parse_requirements(requirement_section.as_lines())?;
-    let acceptance_criteria = parse_acceptance_criteria(acceptance_section.as_lines())?;
+    let project_context = extract_section_text(&lines, &CONTEXT_HEADINGS).ok_or_else(|| {
+        anyhow!("missing required section heading: 项目上下文 (or Project Context)")
+    })?;
+    let requirement_section = extract_section_text(&lines, &REQUIREMENTS_HEADINGS)
+        .ok_or_else(|| anyhow!("missing required section heading: 需求列表 (or Requirements)"))?;
+    let acceptance_section =
+        extract_section_text(&lines, &ACCEPTANCE_HEADINGS).ok_or_else(|| {
+            anyhow!("missing required section heading: 验收标准 (or Acceptance Criteria)")
+        })?;
+
+    let requirements = parse_requirements(requirement_section.as_lines(), &mut warnings)?;
+    let acceptance_criteria =
+        parse_acceptance_criteria(acceptance_section.as_lines(), &mut warnings)?;
 
     let doc = PrdDocument {
         project_name,
@@ -34,7 +126,7 @@ pub fn parse_prd_str(markdown: &str) -> Result<PrdDocument> {
     };
 
     doc.validate()?;
-    Ok(doc)
+    Ok((doc, warnings))
 }
 
 #[derive(Debug)]
@@ -48,13 +140,19 @@ impl Section {
     }
 }
 
-fn extract_section_text(lines: &[&str], heading_keyword: &str) -> Option<Section> {
+/// Finds the `## ` section whose heading contains any of `heading_keywords` (case-insensitive).
+fn extract_section_text(lines: &[&str], heading_keywords: &[&str]) -> Option<Section> {
     let mut start = None;
     let mut end = lines.len();
 
     for (idx, line) in lines.iter().enumerate() {
         let trimmed = line.trim_start();
-        if trimmed.starts_with("## ") && trimmed.contains(heading_keyword) {
+        let lower = trimmed.to_lowercase();
+        if trimmed.starts_with("## ")
+            && heading_keywords
+                .iter()
+                .any(|keyword| lower.contains(keyword))
+        {
             start = Some(idx + 1);
             continue;
         }
@@ -71,7 +169,8 @@ fn extract_section_text(lines: &[&str], heading_keyword: &str) -> Option<Section
 }
 
 fn extract_project_name(lines: &[&str]) -> Option<String> {
-    let regex = Regex::new(r"^>\s*项目名称[:：]\s*(.+?)\s*(?:<!--.*)?$").ok()?;
+    let regex =
+        Regex::new(r"(?i)^>\s*(?:项目名称|project name)\s*[:：]\s*(.+?)\s*(?:<!--.*)?$").ok()?;
     for line in lines {
         let trimmed = line.trim();
         if let Some(caps) = regex.captures(trimmed) {
@@ -81,12 +180,16 @@ fn extract_project_name(lines: &[&str]) -> Option<String> {
     None
 }
 
-fn parse_requirements(lines: Vec<&str>) -> Result<Vec<Requirement>> {
+fn parse_requirements(
+    lines: Vec<&str>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<Requirement>> {
     let req_heading = Regex::new(r"^###\s*(REQ-[A-Za-z0-9_-]+)\s*:\s*(.+?)\s*$")
         .context("failed to compile requirement heading regex")?;
 
     let mut reqs = Vec::new();
     let mut current: Option<RequirementBuilder> = None;
+    let mut seen_ids = BTreeSet::new();
 
     for line in lines {
         let trimmed = line.trim();
@@ -95,34 +198,70 @@ fn parse_requirements(lines: Vec<&str>) -> Result<Vec<Requirement>> {
             if let Some(builder) = current.take() {
                 reqs.push(builder.build()?);
             }
+            let id = caps[1].trim().to_string();
+            if !seen_ids.insert(id.clone()) {
+                warnings.push(ParseWarning::DuplicateRequirementId { id });
+            }
 
             current = Some(RequirementBuilder {
                 id: caps[1].trim().to_string(),
                 title: caps[2].trim().to_string(),
                 priority: None,
                 description: None,
-                validate_command: None,
+                validate_commands: Vec::new(),
                 pass_condition: None,
                 tasks: Vec::new(),
+                artifacts: Vec::new(),
+                provider_timeout: None,
+                depends_on: Vec::new(),
+                workdir: None,
+            });
+            continue;
+        }
+
+        if trimmed.starts_with("###") {
+            warnings.push(ParseWarning::UnrecognizedHeading {
+                line: trimmed.to_string(),
             });
             continue;
         }
 
         let Some(builder) = current.as_mut() else {
+            if let Some(task) = parse_task(trimmed) {
+                warnings.push(ParseWarning::TaskOutsideRequirement { task });
+            }
             continue;
         };
 
         if let Some((key, value)) = parse_two_column_row(trimmed) {
             let norm_key = strip_markdown(&key).to_lowercase();
-            let norm_val = strip_markdown(&value);
-            if norm_key.contains("优先级") {
+            let norm_val = strip_emphasis(&value);
+            if norm_key.contains("优先级") || norm_key.contains("priority") {
                 builder.priority = Some(norm_val);
-            } else if norm_key.contains("描述") {
+            } else if norm_key.contains("描述") || norm_key.contains("description") {
                 builder.description = Some(norm_val);
-            } else if norm_key.contains("验证命令") {
-                builder.validate_command = Some(strip_code_fence(&norm_val));
-            } else if norm_key.contains("通过条件") {
+            } else if norm_key.contains("验证命令") || norm_key.contains("validate command") {
+                builder
+                    .validate_commands
+                    .extend(parse_validate_commands(&norm_val));
+            } else if norm_key.contains("通过条件") || norm_key.contains("pass condition") {
                 builder.pass_condition = Some(norm_val);
+            } else if norm_key.contains("工作目录") || norm_key == "cwd" || norm_key == "workdir"
+            {
+                let dir = strip_code_fence(&norm_val);
+                let dir = dir.trim().trim_end_matches('/');
+                builder.workdir = (!dir.is_empty() && dir != ".").then(|| dir.to_string());
+            } else if norm_key.contains("超时") || norm_key.contains("timeout") {
+                builder.provider_timeout = Some(strip_code_fence(&norm_val));
+            } else if norm_key.contains("产出物") || norm_key.contains("artifacts") {
+                builder.artifacts.extend(parse_list_cell(&norm_val));
+            } else if norm_key.contains("依赖") || norm_key.contains("depends") {
+                builder.depends_on.extend(parse_list_cell(&norm_val));
+            } else if !norm_key.contains("字段") && norm_key != "field" {
+                warnings.push(ParseWarning::UnknownRequirementField {
+                    req: builder.id.clone(),
+                    field: strip_markdown(&key),
+                });
             }
             continue;
         }
@@ -137,13 +276,16 @@ fn parse_requirements(lines: Vec<&str>) -> Result<Vec<Requirement>> {
     }
 
     if reqs.is_empty() {
-        bail!("no REQ-* entries found in 需求列表 section");
+        bail!("需求列表 section has no requirements; add entries like `### REQ-001: title`");
     }
 
     Ok(reqs)
 }
 
-fn parse_acceptance_criteria(lines: Vec<&str>) -> Result<Vec<AcceptanceCriterion>> {
+fn parse_acceptance_criteria(
+    lines: Vec<&str>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<AcceptanceCriterion>> {
     let mut criteria = Vec::new();
 
     for line in lines {
@@ -152,31 +294,56 @@ fn parse_acceptance_criteria(lines: Vec<&str>) -> Result<Vec<AcceptanceCriterion
             continue;
         };
 
-        if cells.len() < 3 {
+        if is_separator_row(&cells) {
             continue;
         }
 
-        if is_separator_row(&cells) {
+        let first = strip_markdown(&cells[0]);
+        if first.contains("标准")
+            || first.contains("字段")
+            || matches!(
+                first.to_lowercase().as_str(),
+                "criterion" | "criteria" | "name" | "field"
+            )
+        {
             continue;
         }
 
-        let first = strip_markdown(&cells[0]);
-        if first.contains("标准") || first.contains("字段") {
+        if cells.len() < 3 {
+            warnings.push(ParseWarning::IgnoredAcceptanceRow {
+                row: trimmed.to_string(),
+            });
             continue;
         }
 
         let name = first;
-        let validate_command = strip_code_fence(&strip_markdown(&cells[1]));
-        let pass_condition = strip_markdown(&cells[2]);
+        let validate_command = strip_code_fence(&strip_emphasis(&cells[1]));
+        let pass_condition = strip_emphasis(&cells[2]);
 
         if name.is_empty() || validate_command.is_empty() {
+            warnings.push(ParseWarning::IgnoredAcceptanceRow {
+                row: trimmed.to_string(),
+            });
             continue;
         }
 
+        let raw_phase = cells
+            .get(3)
+            .map(|cell| strip_emphasis(cell))
+            .unwrap_or_default();
+        let phase = AcceptancePhase::parse(&raw_phase).unwrap_or_else(|| {
+            warnings.push(ParseWarning::UnknownAcceptancePhase {
+                name: name.clone(),
+                value: raw_phase.clone(),
+            });
+            AcceptancePhase::PerIteration
+        });
+
         criteria.push(AcceptanceCriterion {
             name,
             validate_command,
             pass_condition,
+            phase,
         });
     }
 
@@ -212,12 +379,9 @@ fn parse_table_row(line: &str) -> Option<Vec<String>> {
         return None;
     }
 
-    let cells = trimmed
-        .trim_start_matches('|')
-        .trim_end_matches('|')
-        .split('|')
-        .map(|v| v.trim().to_string())
-        .collect::<Vec<String>>();
+    let cells = split_table_cells(trimmed, true).unwrap_or_else(|| {
+        split_table_cells(trimmed, false).expect("unprotected split always closes")
+    });
 
     if cells.len() < 2 {
         return None;
@@ -226,6 +390,70 @@ fn parse_table_row(line: &str) -> Option<Vec<String>> {
     Some(cells)
 }
 
+/// Splits a row on its cell pipes. A GFM-escaped `\|` is kept as `|`, and with `protect`
+/// so is a pipe inside `` `code` ``, a quoted needle or a `匹配 /…/` regex, so
+/// `退出码 = 0 \|\| 包含 'a|b'` stays one cell. Returns `None` when a protected span is left open.
+fn split_table_cells(row: &str, protect: bool) -> Option<Vec<String>> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quote = None;
+    let mut in_regex = false;
+    let mut escaped = false;
+    let mut chars = row.char_indices().peekable();
+    let mut ends_with_pipe = false;
+    while let Some((index, ch)) = chars.next() {
+        ends_with_pipe = false;
+        if ch == '\\' && chars.peek().is_some_and(|(_, next)| *next == '|') {
+            chars.next();
+            cell.push('|');
+            continue;
+        }
+        if in_regex {
+            let rest = &row[index + ch.len_utf8()..];
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '/' && (rest.trim_start().starts_with('|') || closes_regex(rest)) {
+                in_regex = false;
+            }
+            cell.push(ch);
+            continue;
+        }
+        if let Some(open) = quote {
+            if ch == open {
+                quote = None;
+            }
+            cell.push(ch);
+            continue;
+        }
+        if ch == '|' {
+            cells.push(cell.trim().to_string());
+            cell.clear();
+            ends_with_pipe = true;
+            continue;
+        }
+        let opens_quote = ch == '`'
+            || (matches!(ch, '\'' | '"') && cell.chars().last().is_none_or(char::is_whitespace));
+        if protect && opens_quote {
+            quote = Some(ch);
+        } else if protect && ch == '/' && opens_regex(&cell) {
+            in_regex = true;
+        }
+        cell.push(ch);
+    }
+    if quote.is_some() || in_regex {
+        return None;
+    }
+    if !ends_with_pipe {
+        cells.push(cell.trim().to_string());
+    }
+    if row.starts_with('|') && !cells.is_empty() {
+        cells.remove(0);
+    }
+    Some(cells)
+}
+
 fn is_separator_row(cells: &[String]) -> bool {
     cells
         .iter()
@@ -242,25 +470,64 @@ fn strip_markdown(input: &str) -> String {
         .to_string()
 }
 
+/// Unwraps a value cell written as `**…**`; unlike [`strip_markdown`] it keeps inner `*`,
+/// which commands and `匹配 /…/` regexes need.
+fn strip_emphasis(input: &str) -> String {
+    let trimmed = input.trim();
+    trimmed
+        .strip_prefix("**")
+        .and_then(|inner| inner.strip_suffix("**"))
+        .unwrap_or(trimmed)
+        .trim()
+        .to_string()
+}
+
 fn strip_code_fence(input: &str) -> String {
     input.trim().trim_matches('`').trim().to_string()
 }
 
+/// Splits a `验证命令` cell holding several commands separated by `<br>` or newlines.
+fn parse_validate_commands(value: &str) -> Vec<String> {
+    let line_break = Regex::new(r"(?i)<br\s*/?>").expect("line break regex must compile");
+    line_break
+        .replace_all(value, "\n")
+        .lines()
+        .map(strip_code_fence)
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Splits a list cell like `` `dist/app`, `docs/api.md` `` (`产出物`, `依赖`) into items.
+fn parse_list_cell(value: &str) -> Vec<String> {
+    value
+        .split([',', '，', '、', ';', '；'])
+        .map(|item| item.trim().trim_matches('`').trim())
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 #[derive(Debug)]
 struct RequirementBuilder {
     id: String,
     title: String,
     priority: Option<String>,
     description: Option<String>,
-    validate_command: Option<String>,
+    validate_commands: Vec<String>,
     pass_condition: Option<String>,
     tasks: Vec<String>,
+    artifacts: Vec<String>,
+    provider_timeout: Option<String>,
+    depends_on: Vec<String>,
+    workdir: Option<String>,
 }
 
 impl RequirementBuilder {
     fn build(self) -> Result<Requirement> {
         let validate_command = self
-            .validate_command
+            .validate_commands
+            .first()
+            .cloned()
             .ok_or_else(|| anyhow!("{} missing 验证命令", self.id))?;
         let pass_condition = self
             .pass_condition
@@ -272,15 +539,23 @@ impl RequirementBuilder {
             priority: self.priority,
             description: self.description.unwrap_or(self.title),
             validate_command,
+            validate_commands: self.validate_commands,
             pass_condition,
             tasks: self.tasks,
+            artifacts: self.artifacts,
+            provider_timeout: self.provider_timeout,
+            depends_on: self.depends_on,
+            workdir: self.workdir,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_prd_str;
+    use super::{
+        parse_prd_file, parse_prd_reader, parse_prd_str, parse_prd_str_with_warnings, ParseWarning,
+    };
+    use crate::plugin::prd_runner::config::prd::AcceptancePhase;
 
     #[test]
     fn parse_minimal_prd() {
@@ -315,6 +590,195 @@ mod tests {
         assert_eq!(doc.acceptance_criteria.len(), 1);
     }
 
+    #[test]
+    fn keeps_escaped_and_quoted_pipes_in_pass_conditions() {
+        let src = r#"
+# PRD: test
+
+## 1. 项目上下文
+- type: rust
+
+## 2. 需求列表
+### REQ-001: build
+| 字段 | 值 |
+|------|-----|
+| **验证命令** | `echo ok | tee out.log` |
+| **通过条件** | 退出码 = 0 \|\| 包含 'a|b' |
+
+## 3. 验收标准
+| 标准 | 验证命令 | 通过条件 |
+|------|----------|----------|
+| 构建成功 | `echo ok` | 退出码 = 0 \|\| 包含 "ok" |
+"#;
+
+        let doc = parse_prd_str(src).expect("expected parser to succeed");
+        let req = &doc.requirements[0];
+        assert_eq!(req.validation_commands(), ["echo ok | tee out.log"]);
+        assert_eq!(req.pass_condition, "退出码 = 0 || 包含 'a|b'");
+        assert_eq!(
+            doc.acceptance_criteria[0].pass_condition,
+            "退出码 = 0 || 包含 \"ok\""
+        );
+    }
+
+    #[test]
+    fn keeps_regex_pass_conditions_intact() {
+        let src = r#"
+# PRD: test
+
+## 1. 项目上下文
+- type: rust
+
+## 2. 需求列表
+### REQ-001: build
+| 字段 | 值 |
+|------|-----|
+| **验证命令** | `cargo test` |
+| **通过条件** | 匹配 /error.*\d+/ |
+
+## 3. 验收标准
+| 标准 | 验证命令 | 通过条件 | 阶段 |
+|------|----------|----------|------|
+| 构建成功 | `ls *.rs` | matches /ok|done/ && 退出码 = 0 | final |
+"#;
+
+        let doc = parse_prd_str(src).expect("expected parser to succeed");
+        assert_eq!(doc.requirements[0].pass_condition, "匹配 /error.*\\d+/");
+        let criterion = &doc.acceptance_criteria[0];
+        assert_eq!(criterion.validate_command, "ls *.rs");
+        assert_eq!(criterion.pass_condition, "matches /ok|done/ && 退出码 = 0");
+        assert_eq!(criterion.phase, AcceptancePhase::Final);
+    }
+
+    #[test]
+    fn parses_english_prd_like_chinese_one() {
+        let en = r#"
+# PRD: test
+> Project Name: demo
+
+## 1. Project Context
+- type: rust
+
+## 2. Requirements
+### REQ-001: build
+| Field | Value |
+|------|-----|
+| **Priority** | high |
+| **Description** | run build |
+| **Validate Command** | `echo ok` |
+| **Pass Condition** | 退出码 = 0 |
+
+- [ ] do x
+
+## 3. Acceptance Criteria
+| Criterion | Validate Command | Pass Condition |
+|------|----------|----------|
+| 构建成功 | `echo ok` | 退出码 = 0 |
+"#;
+        let zh = "# PRD: test\n> 项目名称：demo\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: build\n| 字段 | 值 |\n|------|-----|\n| **优先级** | high |\n| **描述** | run build |\n| **验证命令** | `echo ok` |\n| **通过条件** | 退出码 = 0 |\n\n- [ ] do x\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建成功 | `echo ok` | 退出码 = 0 |\n";
+
+        let (en_doc, warnings) = parse_prd_str_with_warnings(en).expect("english prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        let zh_doc = parse_prd_str(zh).expect("chinese prd should parse");
+        assert_eq!(en_doc.project_name, zh_doc.project_name);
+        assert_eq!(en_doc.project_context, zh_doc.project_context);
+        assert_eq!(en_doc.requirements, zh_doc.requirements);
+        assert_eq!(en_doc.acceptance_criteria, zh_doc.acceptance_criteria);
+    }
+
+    #[test]
+    fn parses_requirement_artifacts() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: web\n\n## 2. 需求列表\n### REQ-001: bundle\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `npm run build` |\n| **通过条件** | 退出码 = 0 |\n| **产出物** | `dist/app.js`, `docs/api.md`、coverage/ |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建 | `npm run build` | 退出码 = 0 |\n";
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(
+            doc.requirements[0].artifacts,
+            vec!["dist/app.js", "docs/api.md", "coverage/"]
+        );
+    }
+
+    #[test]
+    fn parses_multiple_validate_commands() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: build\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo build` |\n| **验证命令** | `cargo test`<br>`cargo clippy` |\n| **通过条件** | 退出码 = 0 |\n### REQ-002: docs\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo doc` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建 | `cargo build` | 退出码 = 0 |\n";
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(doc.requirements[0].validate_command, "cargo build");
+        assert_eq!(
+            doc.requirements[0].validation_commands(),
+            vec!["cargo build", "cargo test", "cargo clippy"]
+        );
+        assert_eq!(doc.requirements[1].validation_commands(), vec!["cargo doc"]);
+    }
+
+    #[test]
+    fn orders_requirements_by_dependencies() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: api\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `true` |\n| **通过条件** | 退出码 = 0 |\n| **依赖** | REQ-002 |\n### REQ-002: model\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `true` |\n| **通过条件** | 退出码 = 0 |\n### REQ-003: ui\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `true` |\n| **通过条件** | 退出码 = 0 |\n| **depends** | REQ-001, REQ-002 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建 | `true` | 退出码 = 0 |\n";
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(doc.requirements[2].depends_on, vec!["REQ-001", "REQ-002"]);
+        let order: Vec<&str> = doc
+            .requirements_in_dependency_order()
+            .unwrap()
+            .iter()
+            .map(|req| req.id.as_str())
+            .collect();
+        assert_eq!(order, vec!["REQ-002", "REQ-001", "REQ-003"]);
+
+        let cyclic = src.replace(
+            "| **通过条件** | 退出码 = 0 |\n### REQ-003",
+            "| **通过条件** | 退出码 = 0 |\n| **依赖** | REQ-003 |\n### REQ-003",
+        );
+        let err = parse_prd_str(&cyclic).unwrap_err().to_string();
+        assert!(err.contains("dependency cycle"), "{err}");
+        assert!(err.contains("REQ-002 -> REQ-003"), "{err}");
+
+        let unknown = src.replace("| REQ-002 |", "| REQ-009 |");
+        assert!(parse_prd_str(&unknown).is_err());
+    }
+
+    #[test]
+    fn parses_requirement_provider_timeout() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: scaffold\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo check` |\n| **通过条件** | 退出码 = 0 |\n| **超时** | 5m |\n### REQ-002: feature\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo test` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建 | `cargo build` | 退出码 = 0 |\n";
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(doc.requirements[0].provider_timeout.as_deref(), Some("5m"));
+        assert_eq!(
+            doc.requirements[0].provider_timeout_duration().unwrap(),
+            Some(std::time::Duration::from_secs(300))
+        );
+        assert_eq!(doc.requirements[1].provider_timeout, None);
+
+        let invalid = src.replace("| 5m |", "| soon |");
+        assert!(parse_prd_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parses_requirement_workdir() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: foo\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo test` |\n| **通过条件** | 退出码 = 0 |\n| **工作目录** | `crates/foo/` |\n### REQ-002: bar\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo test` |\n| **通过条件** | 退出码 = 0 |\n| **cwd** | crates/bar |\n### REQ-003: root\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `cargo build` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建 | `cargo build` | 退出码 = 0 |\n";
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("prd should parse");
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(doc.requirements[0].workdir.as_deref(), Some("crates/foo"));
+        assert_eq!(doc.requirements[1].workdir.as_deref(), Some("crates/bar"));
+        assert_eq!(doc.requirements[2].workdir, None);
+        let root = std::path::Path::new("/ws");
+        assert_eq!(
+            doc.requirements[0].resolve_workdir(root),
+            root.join("crates/foo")
+        );
+        assert_eq!(doc.requirements[2].resolve_workdir(root), root);
+
+        for escaping in ["../other", "/etc"] {
+            let invalid = src.replace("crates/bar", escaping);
+            let err = parse_prd_str(&invalid).unwrap_err().to_string();
+            assert!(err.contains("inside the workspace"), "{err}");
+        }
+    }
+
     #[test]
     fn supports_markdown_tables_without_trailing_pipe() {
         let src = r#"
@@ -345,4 +809,106 @@ mod tests {
         assert_eq!(doc.requirements.len(), 1);
         assert_eq!(doc.acceptance_criteria.len(), 1);
     }
+
+    #[test]
+    fn parses_prd_from_reader() {
+        let src = "# PRD: piped\n> 项目名称：piped\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: build\n| 字段 | 值 |\n|------|-----|\n| **描述** | run build |\n| **验证命令** | `echo ok` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 构建成功 | `echo ok` | 退出码 = 0 |\n";
+
+        let doc = parse_prd_reader(std::io::Cursor::new(src)).expect("expected parser to succeed");
+        assert_eq!(doc.project_name.as_deref(), Some("piped"));
+        assert_eq!(doc.raw_markdown, src);
+    }
+
+    #[test]
+    fn rejects_empty_prd_with_init_hint() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let path = tmp.path().join("PRD.md");
+        std::fs::write(&path, "  \n\t\n").expect("write prd");
+
+        let err = parse_prd_file(&path).expect_err("empty PRD must fail");
+        assert!(err.to_string().contains("PRD.md is empty"));
+        assert!(err.to_string().contains("autocode prd init"));
+        let err = parse_prd_str("").expect_err("empty PRD must fail");
+        assert!(err.to_string().contains("PRD is empty"));
+    }
+
+    #[test]
+    fn headings_without_requirements_are_not_a_missing_section() {
+        let src = "# PRD\n\n## 1. 项目上下文\n\n## 2. 需求列表\n\n## 3. 验收标准\n";
+        let err = parse_prd_str(src).expect_err("no requirements must fail");
+        assert!(err
+            .to_string()
+            .contains("需求列表 section has no requirements"));
+        assert!(!err.to_string().contains("missing required section"));
+    }
+
+    #[test]
+    fn parses_acceptance_phase_column() {
+        let src = "# PRD\n\n## 1. 项目上下文\n- type: rust\n\n## 2. 需求列表\n### REQ-001: build\n| **验证命令** | `echo ok` |\n| **通过条件** | 退出码 = 0 |\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 | 阶段 |\n|------|----------|----------|------|\n| 单测 | `cargo test` | 退出码 = 0 | |\n| e2e | `make e2e` | 退出码 = 0 | final |\n";
+
+        let doc = parse_prd_str(src).expect("expected parser to succeed");
+        assert_eq!(
+            doc.acceptance_criteria[0].phase,
+            AcceptancePhase::PerIteration
+        );
+        assert_eq!(doc.acceptance_criteria[1].phase, AcceptancePhase::Final);
+    }
+
+    #[test]
+    fn reports_content_the_parser_ignored() {
+        let src = r#"
+# PRD: test
+> 项目名称：demo
+
+## 1. 项目上下文
+- type: rust
+
+## 2. 需求列表
+- [ ] stray task
+
+### REQ-001: build
+| 字段 | 值 |
+|------|-----|
+| **负责人** | alice |
+| **验证命令** | `echo ok` |
+| **通过条件** | 退出码 = 0 |
+
+### REQ-001: build again
+| **验证命令** | `echo again` |
+| **通过条件** | 退出码 = 0 |
+
+### REQ-002 missing colon
+
+## 3. 验收标准
+| 标准 | 验证命令 | 通过条件 |
+|------|----------|----------|
+| 构建成功 | `echo ok` | 退出码 = 0 |
+| 缺少命令 |  | 退出码 = 0 |
+"#;
+
+        let (doc, warnings) = parse_prd_str_with_warnings(src).expect("expected parser to succeed");
+        assert_eq!(doc.requirements.len(), 2);
+        assert_eq!(doc.acceptance_criteria.len(), 1);
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning::TaskOutsideRequirement {
+                    task: "stray task".to_string()
+                },
+                ParseWarning::UnknownRequirementField {
+                    req: "REQ-001".to_string(),
+                    field: "负责人".to_string()
+                },
+                ParseWarning::DuplicateRequirementId {
+                    id: "REQ-001".to_string()
+                },
+                ParseWarning::UnrecognizedHeading {
+                    line: "### REQ-002 missing colon".to_string()
+                },
+                ParseWarning::IgnoredAcceptanceRow {
+                    row: "| 缺少命令 |  | 退出码 = 0 |".to_string()
+                },
+            ]
+        );
+    }
 }
@@ -1,4 +1,8 @@
-use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,13 +32,31 @@ impl PrdDocument {
             if req.id.trim().is_empty() {
                 bail!("requirement has empty id");
             }
-            if req.validate_command.trim().is_empty() {
+            if req
+                .validation_commands()
+                .iter()
+                .any(|command| command.trim().is_empty())
+            {
                 bail!("requirement {} missing 验证命令", req.id);
             }
             if req.pass_condition.trim().is_empty() {
                 bail!("requirement {} missing 通过条件", req.id);
             }
+            req.provider_timeout_duration()?;
+            if let Some(dir) = &req.workdir {
+                let inside = Path::new(dir)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+                if !inside {
+                    bail!(
+                        "requirement {} 工作目录 '{}' must be a relative path inside the workspace",
+                        req.id,
+                        dir
+                    );
+                }
+            }
         }
+        self.requirements_in_dependency_order()?;
 
         for criterion in &self.acceptance_criteria {
             if criterion.validate_command.trim().is_empty() {
@@ -44,22 +66,133 @@ impl PrdDocument {
 
         Ok(())
     }
+
+    /// Document order, except that every requirement comes after the ones it depends on.
+    pub fn requirements_in_dependency_order(&self) -> Result<Vec<&Requirement>> {
+        fn visit<'a>(
+            req: &'a Requirement,
+            by_id: &HashMap<&str, &'a Requirement>,
+            visited: &mut HashSet<&'a str>,
+            path: &mut Vec<&'a str>,
+            ordered: &mut Vec<&'a Requirement>,
+        ) -> Result<()> {
+            if visited.contains(req.id.as_str()) {
+                return Ok(());
+            }
+            if let Some(start) = path.iter().position(|id| *id == req.id) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(&req.id);
+                bail!("requirement dependency cycle: {}", cycle.join(" -> "));
+            }
+            path.push(&req.id);
+            for dep in &req.depends_on {
+                let Some(dep_req) = by_id.get(dep.as_str()) else {
+                    bail!(
+                        "requirement {} depends on unknown requirement {}",
+                        req.id,
+                        dep
+                    );
+                };
+                visit(dep_req, by_id, visited, path, ordered)?;
+            }
+            path.pop();
+            visited.insert(&req.id);
+            ordered.push(req);
+            Ok(())
+        }
+
+        let by_id: HashMap<&str, &Requirement> = self
+            .requirements
+            .iter()
+            .map(|req| (req.id.as_str(), req))
+            .collect();
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::with_capacity(self.requirements.len());
+        for req in &self.requirements {
+            visit(req, &by_id, &mut visited, &mut Vec::new(), &mut ordered)?;
+        }
+        Ok(ordered)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Requirement {
     pub id: String,
     pub title: String,
     pub priority: Option<String>,
     pub description: String,
     pub validate_command: String,
+    /// Every validation command in order; empty means only `validate_command` is run.
+    #[serde(default)]
+    pub validate_commands: Vec<String>,
     pub pass_condition: String,
     pub tasks: Vec<String>,
+    /// Workspace-relative paths copied into each checkpoint's `artifacts/` directory.
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    /// Overrides `provider.timeout` for this requirement, e.g. `5m`.
+    #[serde(default)]
+    pub provider_timeout: Option<String>,
+    /// Requirement ids that must be `Done` before this one is attempted.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Workspace-relative directory the AI and validation commands run in, e.g. `crates/foo`.
+    #[serde(default)]
+    pub workdir: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Requirement {
+    pub fn validation_commands(&self) -> Vec<&str> {
+        if self.validate_commands.is_empty() {
+            vec![self.validate_command.as_str()]
+        } else {
+            self.validate_commands.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Where this requirement's commands run: `workspace` or its `工作目录` below it.
+    pub fn resolve_workdir(&self, workspace: &Path) -> PathBuf {
+        match &self.workdir {
+            Some(dir) => workspace.join(dir),
+            None => workspace.to_path_buf(),
+        }
+    }
+
+    pub fn provider_timeout_duration(&self) -> Result<Option<Duration>> {
+        self.provider_timeout
+            .as_deref()
+            .map(|raw| {
+                humantime::parse_duration(raw)
+                    .with_context(|| format!("requirement {} has invalid 超时 '{}'", self.id, raw))
+            })
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AcceptanceCriterion {
     pub name: String,
     pub validate_command: String,
     pub pass_condition: String,
+    #[serde(default)]
+    pub phase: AcceptancePhase,
+}
+
+/// When an acceptance criterion runs: every iteration, or only once requirements are done.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AcceptancePhase {
+    #[default]
+    PerIteration,
+    Final,
+}
+
+impl AcceptancePhase {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "per-iteration" | "每轮" => Some(AcceptancePhase::PerIteration),
+            "final" | "最终" => Some(AcceptancePhase::Final),
+            _ => None,
+        }
+    }
 }
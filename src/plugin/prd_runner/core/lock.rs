@@ -0,0 +1,90 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+pub const RUN_LOCK_FILE: &str = "run.lock";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LockHolder {
+    pid: u32,
+    started_at: String,
+}
+
+/// Advisory lock keeping a single loop per workspace; released on drop.
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+}
+
+impl RunLock {
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let holder = std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|raw| serde_json::from_str::<LockHolder>(&raw).ok())
+                    .map(|holder| format!("pid={}, started_at={}", holder.pid, holder.started_at))
+                    .unwrap_or_else(|| "holder unknown".to_string());
+                bail!(
+                    "another run is active in this workspace ({}). lock file: {} (remove it if that process is no longer running)",
+                    holder,
+                    path.display()
+                );
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to create {}", path.display()))
+            }
+        };
+
+        let lock = Self {
+            path: path.to_path_buf(),
+        };
+        let holder = LockHolder {
+            pid: std::process::id(),
+            started_at: Utc::now().to_rfc3339(),
+        };
+        let content = serde_json::to_string(&holder).context("failed to serialize run lock")?;
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(lock)
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::RunLock;
+
+    #[test]
+    fn second_holder_is_rejected_until_release() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let path = tmp.path().join(".autocode/run.lock");
+
+        let first = RunLock::acquire(&path)?;
+        let err = RunLock::acquire(&path).expect_err("lock is held");
+        assert!(format!("{:#}", err).contains(&format!("pid={}", std::process::id())));
+
+        drop(first);
+        assert!(!path.exists());
+        RunLock::acquire(&path)?;
+        Ok(())
+    }
+}
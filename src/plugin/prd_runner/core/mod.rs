@@ -1,3 +1,9 @@
 pub mod executor;
+pub mod lock;
 pub mod process;
+pub mod prompt;
 pub mod provider;
+pub mod retry_budget;
+#[cfg(test)]
+pub mod scripted;
+pub mod semaphore;
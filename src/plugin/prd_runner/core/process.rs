@@ -1,12 +1,19 @@
 use std::path::Path;
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
 use regex::Regex;
 
 use crate::plugin::prd_runner::config::prd::{PrdDocument, Requirement};
-use crate::plugin::prd_runner::core::provider::Provider;
+use crate::plugin::prd_runner::config::{ProcessConfig, PromptConfig};
+use crate::plugin::prd_runner::core::prompt::{
+    fit_prompt, FittedPrompt, PromptParts, PromptSection, PromptSectionKind,
+};
+use crate::plugin::prd_runner::core::provider::{is_provider_timeout, Provider, TokenUsage};
+use crate::plugin::prd_runner::core::retry_budget::RetryBudget;
+use crate::plugin::prd_runner::core::semaphore::PROVIDER_SLOTS;
 
 #[derive(Debug, Clone)]
 pub struct AiInstruction {
@@ -18,6 +25,129 @@ pub struct AiProcess {
     provider: Box<dyn Provider>,
     response_timeout: Duration,
     dry_run: bool,
+    markers: CommandMarkers,
+    prompt_config: PromptConfig,
+    timeout_retries: u32,
+    deadline: Option<Instant>,
+    retry_events: Vec<String>,
+    retry_budget: RetryBudget,
+    /// Sum of usage reported by the provider; `None` until it reports any.
+    token_usage: Option<TokenUsage>,
+    /// Contents of `PROMPT_TEMPLATE_FILE`, replacing the builtin requirement prompt.
+    prompt_template: Option<String>,
+    /// Limit on [`PROVIDER_SLOTS`] while this process waits on the provider.
+    max_concurrency: usize,
+}
+
+/// Workspace-relative prompt template; placeholders are listed in `render_prompt_template`.
+pub const PROMPT_TEMPLATE_FILE: &str = ".autocode/prompt.md";
+
+const TIMEOUT_RETRY_BASE_BACKOFF: Duration = Duration::from_millis(200);
+const TIMEOUT_RETRY_MAX_JITTER_MS: u64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct CommandMarkers {
+    line_patterns: Vec<Regex>,
+    noop_line: String,
+    noop_sentinel: String,
+}
+
+impl CommandMarkers {
+    pub fn new(config: &ProcessConfig) -> Result<Self> {
+        let line_patterns = config
+            .cmd_markers
+            .iter()
+            .map(|marker| marker_regex(marker))
+            .collect::<Result<Vec<_>>>()?;
+        let noop_line = match config.cmd_markers.first() {
+            Some(marker) => format!("{} {}", marker, config.noop_sentinel),
+            None => config.noop_sentinel.clone(),
+        };
+
+        Ok(Self {
+            line_patterns,
+            noop_line,
+            noop_sentinel: config.noop_sentinel.trim().to_string(),
+        })
+    }
+}
+
+/// Fills `{project}`, `{workdir}`, `{req_id}`, `{title}`, `{description}`, `{tasks}`,
+/// `{validate}`, `{pass_condition}` and `{noop}`; other `{...}` text is left untouched.
+fn render_prompt_template(
+    template: &str,
+    prd: &PrdDocument,
+    req: &Requirement,
+    workdir: &Path,
+    noop_line: &str,
+) -> String {
+    let placeholder = Regex::new(r"\{([a-z_]+)\}").expect("placeholder regex is valid");
+    placeholder
+        .replace_all(template, |caps: &regex::Captures| match &caps[1] {
+            "project" => prd.project_name.as_deref().unwrap_or("unknown").to_string(),
+            "workdir" => req.resolve_workdir(workdir).display().to_string(),
+            "req_id" => req.id.clone(),
+            "title" => req.title.clone(),
+            "description" => req.description.clone(),
+            "tasks" => req
+                .tasks
+                .iter()
+                .map(|task| format!("- {}", task))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "validate" => req.validation_commands().join(" && "),
+            "pass_condition" => req.pass_condition.clone(),
+            "noop" => noop_line.to_string(),
+            _ => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Tracked files and uncommitted changes of a git workspace; none outside a git repository.
+fn workspace_sections(workdir: &Path) -> Vec<PromptSection> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(workdir)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_string()
+            })
+            .filter(|text| !text.is_empty())
+    };
+
+    let mut sections = Vec::new();
+    if let Some(files) = git(&["ls-files", "--cached", "--others", "--exclude-standard"]) {
+        sections.push(PromptSection {
+            kind: PromptSectionKind::FileTree,
+            content: files,
+        });
+    }
+    if let Some(diff) = git(&["diff", "HEAD"]) {
+        sections.push(PromptSection {
+            kind: PromptSectionKind::GitDiff,
+            content: diff,
+        });
+    }
+    sections
+}
+
+fn marker_regex(marker: &str) -> Result<Regex> {
+    let marker = marker.trim();
+    // `CMD:` tolerates spaces around the colon; other markers need whitespace before the command.
+    let prefix = match marker.strip_suffix(':') {
+        Some(base) => format!(r"{}\s*:\s*", regex::escape(base.trim_end())),
+        None => format!(r"{}\s+", regex::escape(marker)),
+    };
+    Regex::new(&format!(r"(?m)^\s*{}(.+?)\s*$", prefix))
+        .with_context(|| format!("failed to compile command marker regex for '{}'", marker))
 }
 
 impl AiProcess {
@@ -34,10 +164,92 @@ impl AiProcess {
             provider,
             response_timeout,
             dry_run,
+            markers: CommandMarkers::new(&ProcessConfig::default())?,
+            prompt_config: PromptConfig::default(),
+            timeout_retries: 0,
+            deadline: None,
+            retry_events: Vec::new(),
+            retry_budget: RetryBudget::default(),
+            token_usage: None,
+            prompt_template: None,
+            max_concurrency: 1,
         })
     }
 
-    pub fn build_prompt(&self, prd: &PrdDocument, req: &Requirement, workdir: &Path) -> String {
+    /// Uses `<workdir>/.autocode/prompt.md` for requirement prompts when it exists.
+    pub fn load_prompt_template(&mut self, workdir: &Path) -> Result<()> {
+        let path = workdir.join(PROMPT_TEMPLATE_FILE);
+        self.prompt_template = if path.is_file() {
+            Some(
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?,
+            )
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    pub fn set_process_config(&mut self, config: &ProcessConfig) -> Result<()> {
+        self.markers = CommandMarkers::new(config)?;
+        Ok(())
+    }
+
+    pub fn set_max_concurrency(&mut self, limit: usize) {
+        self.max_concurrency = limit;
+    }
+
+    pub fn set_timeout_retries(&mut self, retries: u32) {
+        self.timeout_retries = retries;
+    }
+
+    /// Timeout retries are only attempted while they still fit before `deadline`.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    pub fn set_retry_budget(&mut self, budget: RetryBudget) {
+        self.retry_budget = budget;
+    }
+
+    pub fn take_retry_events(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.retry_events)
+    }
+
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        self.token_usage
+    }
+
+    pub fn set_prompt_config(&mut self, config: &PromptConfig) {
+        self.prompt_config = config.clone();
+    }
+
+    /// Fits `prompt` to `prompt.max_chars`; every prompt goes through here before it is sent.
+    pub fn fit_prompt(&self, prompt: &PromptParts) -> FittedPrompt {
+        fit_prompt(&prompt.core, &prompt.sections, self.prompt_config.max_chars)
+    }
+
+    pub fn build_prompt(
+        &self,
+        prd: &PrdDocument,
+        req: &Requirement,
+        workdir: &Path,
+    ) -> PromptParts {
+        let core = match &self.prompt_template {
+            Some(template) => {
+                render_prompt_template(template, prd, req, workdir, &self.markers.noop_line)
+            }
+            None => self.builtin_prompt(prd, req, workdir),
+        };
+        let sections = if self.prompt_config.workspace_context {
+            workspace_sections(workdir)
+        } else {
+            Vec::new()
+        };
+        PromptParts { core, sections }
+    }
+
+    fn builtin_prompt(&self, prd: &PrdDocument, req: &Requirement, workdir: &Path) -> String {
         let mut text = format!(
             "You are coding for project '{}'.\n\
 Workspace root: {}\n\
@@ -57,9 +269,15 @@ Respond with a short plan and shell commands in fenced bash blocks.\n",
                 .map(|task| format!("- {}", task))
                 .collect::<Vec<String>>()
                 .join("\n"),
-            req.validate_command,
+            req.validation_commands().join(" && "),
             req.pass_condition,
         );
+        if let Some(dir) = &req.workdir {
+            text.push_str(&format!(
+                "Working directory: {} (relative to workspace root; commands and validation run there)\n",
+                dir
+            ));
+        }
 
         text.push_str(
             "\nOutput rules:\n\
@@ -68,11 +286,48 @@ Respond with a short plan and shell commands in fenced bash blocks.\n",
 - Prefer relative paths from workspace root.\n\
 - Put executable commands inside ```bash fenced blocks.\n\
 - If you think requirement is already complete, still output a verification command in a bash block.\n\
-- If nothing can be executed, output exactly: CMD: # no-op\n",
+- If nothing can be executed, output exactly: ",
         );
+        text.push_str(&self.markers.noop_line);
+        text.push('\n');
         text
     }
 
+    pub fn build_no_commands_reprompt(&self, prompt: &PromptParts) -> PromptParts {
+        let core = format!(
+            "{}\nYour previous reply contained no executable commands.\n\
+You must output commands in a bash fenced block (```bash ... ```).\n\
+If nothing can be executed, output exactly: {}\n",
+            prompt.core, self.markers.noop_line
+        );
+        PromptParts {
+            core,
+            sections: prompt.sections.clone(),
+        }
+    }
+
+    pub fn build_inner_loop_prompt(
+        &self,
+        prompt: &PromptParts,
+        command_outputs: &[String],
+        error: Option<&str>,
+    ) -> PromptParts {
+        let core = format!(
+            "{}\nValidation still fails: {}\n\
+React to the output of your previous commands below and reply with the next commands in a bash fenced block.\n\
+If nothing can be executed, output exactly: {}\n",
+            prompt.core,
+            error.unwrap_or("pass condition not satisfied"),
+            self.markers.noop_line
+        );
+        let mut sections = prompt.sections.clone();
+        sections.push(PromptSection {
+            kind: PromptSectionKind::History,
+            content: command_outputs.join("\n\n"),
+        });
+        PromptParts { core, sections }
+    }
+
     pub fn generate_instruction(&mut self, prompt: &str) -> Result<AiInstruction> {
         if self.dry_run {
             return Ok(AiInstruction {
@@ -81,15 +336,58 @@ Respond with a short plan and shell commands in fenced bash blocks.\n",
             });
         }
 
-        self.provider
-            .send(prompt)
-            .context("failed to send prompt to provider")?;
-        let output = self
-            .provider
-            .read_output(self.response_timeout)
-            .context("failed to read output from provider")?;
+        let mut timeout = self.response_timeout;
+        let mut retry = 0;
+        let output = loop {
+            let result = {
+                let _slot = PROVIDER_SLOTS.acquire(self.max_concurrency);
+                self.provider
+                    .send(prompt)
+                    .context("failed to send prompt to provider")?;
+                self.provider.read_output(timeout)
+            };
+            if let Some(usage) = self.provider.take_usage() {
+                self.token_usage
+                    .get_or_insert_with(TokenUsage::default)
+                    .add(usage);
+            }
+            let err = match result {
+                Ok(output) => break output,
+                Err(err) => err,
+            };
+            if retry >= self.timeout_retries || !is_provider_timeout(&err) {
+                return Err(err.context("failed to read output from provider"));
+            }
 
-        let commands = extract_commands_from_output(&output)?;
+            retry += 1;
+            let backoff = TIMEOUT_RETRY_BASE_BACKOFF * 2u32.pow(retry - 1) + retry_jitter();
+            if let Some(deadline) = self.deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left <= backoff {
+                    return Err(err.context(format!(
+                        "failed to read output from provider (no runtime left for timeout retry {})",
+                        retry
+                    )));
+                }
+                timeout = timeout.min(left - backoff);
+            }
+            if !self.retry_budget.try_consume() {
+                return Err(err.context(
+                    "failed to read output from provider (run-wide retry budget exhausted)",
+                ));
+            }
+            self.retry_events.push(format!(
+                "retry={}/{} backoff_ms={} timeout_secs={} after: {}",
+                retry,
+                self.timeout_retries,
+                backoff.as_millis(),
+                timeout.as_secs(),
+                err
+            ));
+            std::thread::sleep(backoff);
+        };
+
+        let commands = extract_commands(&output, &self.markers)?;
         Ok(AiInstruction {
             raw_output: output,
             commands,
@@ -105,7 +403,20 @@ Respond with a short plan and shell commands in fenced bash blocks.\n",
     }
 }
 
+fn retry_jitter() -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos() as u64)
+        .unwrap_or(0);
+    Duration::from_millis(nanos % (TIMEOUT_RETRY_MAX_JITTER_MS + 1))
+}
+
+#[cfg(test)]
 fn extract_commands_from_output(output: &str) -> Result<Vec<String>> {
+    extract_commands(output, &CommandMarkers::new(&ProcessConfig::default())?)
+}
+
+fn extract_commands(output: &str, markers: &CommandMarkers) -> Result<Vec<String>> {
     let mut commands = Vec::new();
     let normalized = normalize_model_output(output)?;
 
@@ -136,20 +447,15 @@ fn extract_commands_from_output(output: &str) -> Result<Vec<String>> {
         }
     }
 
-    let cmd_regex =
-        Regex::new(r"(?m)^\s*CMD\s*:\s*(.+?)\s*$").context("failed to compile CMD line regex")?;
-    for cap in cmd_regex.captures_iter(&normalized) {
-        commands.push(cap[1].trim().to_string());
-    }
-
-    let shell_prompt_regex =
-        Regex::new(r"(?m)^\s*\$\s+(.+?)\s*$").context("failed to compile $ command regex")?;
-    for cap in shell_prompt_regex.captures_iter(&normalized) {
-        commands.push(cap[1].trim().to_string());
+    for pattern in &markers.line_patterns {
+        for cap in pattern.captures_iter(&normalized) {
+            commands.push(cap[1].trim().to_string());
+        }
     }
 
     commands.retain(|cmd| !cmd.is_empty());
     commands.retain(|cmd| !is_comment_only_command(cmd));
+    commands.retain(|cmd| cmd.trim() != markers.noop_sentinel);
     commands.dedup();
 
     Ok(commands)
@@ -208,7 +514,18 @@ fn is_comment_only_command(command: &str) -> bool {
 mod tests {
     use anyhow::Result;
 
-    use super::extract_commands_from_output;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{
+        extract_commands, extract_commands_from_output, AiProcess, CommandMarkers,
+        PROMPT_TEMPLATE_FILE,
+    };
+    use crate::plugin::prd_runner::config::parser::parse_prd_str;
+    use crate::plugin::prd_runner::config::ProcessConfig;
+    use crate::plugin::prd_runner::core::provider::Provider;
+    use crate::plugin::prd_runner::core::scripted::ScriptedProvider;
 
     #[test]
     fn extracts_commands_from_fenced_block() -> Result<()> {
@@ -289,4 +606,123 @@ cargo test
         assert_eq!(commands, vec!["# validate only\ncargo test"]);
         Ok(())
     }
+
+    #[test]
+    fn recognizes_custom_markers_and_sentinel() -> Result<()> {
+        let markers = CommandMarkers::new(&ProcessConfig {
+            cmd_markers: vec!["RUN>".to_string(), "EXEC:".to_string()],
+            noop_sentinel: "NOTHING".to_string(),
+        })?;
+        let output = "RUN> cargo build\nEXEC : cargo test\nRUN> NOTHING\nCMD: ignored";
+        let commands = extract_commands(output, &markers)?;
+        assert_eq!(commands, vec!["cargo build", "cargo test"]);
+        Ok(())
+    }
+
+    #[test]
+    fn renders_custom_prompt_template() -> Result<()> {
+        let tmp = tempfile::TempDir::new()?;
+        let prd = parse_prd_str("# PRD\n> 项目名称：shop\n\n## 1. 项目上下文\n- type: node\n\n## 2. 需求列表\n### REQ-007: cart\n| 字段 | 值 |\n|------|-----|\n| **验证命令** | `npm test` |\n| **通过条件** | 退出码 = 0 |\n- [ ] add cart store\n- [ ] wire checkout\n\n## 3. 验收标准\n| 标准 | 验证命令 | 通过条件 |\n|------|----------|----------|\n| 测试 | `npm test` | 退出码 = 0 |\n")?;
+        let req = &prd.requirements[0];
+        let mut process = AiProcess::new(
+            Box::new(ScriptedProvider::new(&[])),
+            Duration::from_secs(1),
+            true,
+        )?;
+
+        process.load_prompt_template(tmp.path())?;
+        let builtin = process.build_prompt(&prd, req, tmp.path());
+        assert!(builtin.core.contains("Output rules"));
+
+        std::fs::create_dir_all(tmp.path().join(".autocode"))?;
+        std::fs::write(
+            tmp.path().join(PROMPT_TEMPLATE_FILE),
+            "Project {project}: {req_id}\n{tasks}\nCheck with `{validate}`; keep {braces}.\n",
+        )?;
+        process.load_prompt_template(tmp.path())?;
+        let prompt = process.build_prompt(&prd, req, tmp.path()).core;
+        assert_eq!(
+            prompt,
+            "Project shop: REQ-007\n- add cart store\n- wire checkout\nCheck with `npm test`; keep {braces}.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn retries_after_provider_timeout() -> Result<()> {
+        let provider = ScriptedProvider::new(&["CMD: cargo test"]).timing_out_first(1);
+        let mut process = AiProcess::new(Box::new(provider), Duration::from_millis(50), false)?;
+        assert!(process.generate_instruction("build").is_err());
+
+        let provider = ScriptedProvider::new(&["CMD: cargo test"]).timing_out_first(1);
+        let prompts = provider.prompts();
+        let mut process = AiProcess::new(Box::new(provider), Duration::from_millis(50), false)?;
+        process.set_timeout_retries(1);
+        let instruction = process.generate_instruction("build")?;
+
+        assert_eq!(instruction.commands, vec!["cargo test"]);
+        assert_eq!(prompts.lock().expect("prompts lock").len(), 2);
+        let events = process.take_retry_events();
+        assert_eq!(events.len(), 1);
+        assert!(events[0].starts_with("retry=1/1"));
+        assert!(events[0].contains("timed out"));
+        Ok(())
+    }
+
+    /// Replies after a short delay while counting how many calls overlap.
+    struct OverlapProvider {
+        active: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl Provider for OverlapProvider {
+        fn name(&self) -> &str {
+            "overlap"
+        }
+        fn start(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn restart(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn send(&mut self, _input: &str) -> Result<()> {
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            Ok(())
+        }
+        fn read_output(&mut self, _timeout: Duration) -> Result<String> {
+            std::thread::sleep(Duration::from_millis(100));
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok("CMD: true".to_string())
+        }
+        fn is_waiting_for_input(&self, _output: &str) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn provider_calls_serialize_at_max_concurrency_one() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handles = (0..2)
+            .map(|_| {
+                let provider = OverlapProvider {
+                    active: Arc::clone(&active),
+                    peak: Arc::clone(&peak),
+                };
+                std::thread::spawn(move || {
+                    let mut process =
+                        AiProcess::new(Box::new(provider), Duration::from_secs(5), false)
+                            .expect("process");
+                    process.set_max_concurrency(1);
+                    process.generate_instruction("build").expect("instruction");
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().expect("provider thread");
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
 }
@@ -0,0 +1,149 @@
+/// Optional prompt sections, declared in the order they are trimmed when over budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PromptSectionKind {
+    FileTree,
+    GitDiff,
+    History,
+}
+
+impl PromptSectionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromptSectionKind::FileTree => "file_tree",
+            PromptSectionKind::GitDiff => "git_diff",
+            PromptSectionKind::History => "history",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            PromptSectionKind::FileTree => "Workspace files",
+            PromptSectionKind::GitDiff => "Current git diff",
+            PromptSectionKind::History => "Previous command output",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptSection {
+    pub kind: PromptSectionKind,
+    pub content: String,
+}
+
+/// A prompt before fitting: the part that is never cut plus its trimmable sections.
+#[derive(Debug, Clone)]
+pub struct PromptParts {
+    pub core: String,
+    pub sections: Vec<PromptSection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FittedPrompt {
+    pub text: String,
+    pub truncated: Vec<String>,
+}
+
+const TRUNCATED_MARKER: &str = "...[truncated]";
+
+/// Appends `sections` to `core`, shrinking the lowest-priority sections first so the
+/// result fits `max_chars`. The core is never cut, even if it alone exceeds the budget.
+pub fn fit_prompt(
+    core: &str,
+    sections: &[PromptSection],
+    max_chars: Option<usize>,
+) -> FittedPrompt {
+    let mut contents = sections
+        .iter()
+        .map(|section| section.content.clone())
+        .collect::<Vec<_>>();
+    let mut truncated = Vec::new();
+
+    if let Some(max_chars) = max_chars {
+        let mut order = (0..sections.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&idx| sections[idx].kind);
+
+        for idx in order {
+            let total = render(core, sections, &contents).chars().count();
+            if total <= max_chars {
+                break;
+            }
+            let before = contents[idx].chars().count();
+            let keep = before.saturating_sub(total - max_chars + TRUNCATED_MARKER.len());
+            let kind = sections[idx].kind;
+            if keep == 0 {
+                contents[idx].clear();
+                truncated.push(format!("{} (dropped {} chars)", kind.as_str(), before));
+            } else {
+                contents[idx] = shrink(&contents[idx], keep, kind);
+                truncated.push(format!("{} ({} -> {} chars)", kind.as_str(), before, keep));
+            }
+        }
+    }
+
+    FittedPrompt {
+        text: render(core, sections, &contents),
+        truncated,
+    }
+}
+
+fn render(core: &str, sections: &[PromptSection], contents: &[String]) -> String {
+    let mut text = core.to_string();
+    for (section, content) in sections.iter().zip(contents) {
+        if content.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n{}:\n{}\n", section.kind.title(), content));
+    }
+    text
+}
+
+fn shrink(content: &str, keep: usize, kind: PromptSectionKind) -> String {
+    let chars = content.chars().collect::<Vec<_>>();
+    // Recent output matters most in history; listings and diffs read top-down.
+    if kind == PromptSectionKind::History {
+        let tail = chars[chars.len() - keep..].iter().collect::<String>();
+        format!("{}{}", TRUNCATED_MARKER, tail)
+    } else {
+        let head = chars[..keep].iter().collect::<String>();
+        format!("{}{}", head, TRUNCATED_MARKER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit_prompt, PromptSection, PromptSectionKind};
+
+    #[test]
+    fn trims_optional_sections_before_touching_core() {
+        let core = "Requirement: REQ-001 - build\nValidation command: cargo test\n";
+        let sections = vec![
+            PromptSection {
+                kind: PromptSectionKind::History,
+                content: format!("{}latest error", "h".repeat(200)),
+            },
+            PromptSection {
+                kind: PromptSectionKind::FileTree,
+                content: "src/main.rs\n".repeat(50),
+            },
+            PromptSection {
+                kind: PromptSectionKind::GitDiff,
+                content: "+line\n".repeat(50),
+            },
+        ];
+
+        let fitted = fit_prompt(core, &sections, Some(200));
+        assert!(fitted.text.starts_with(core));
+        assert!(fitted.text.chars().count() <= 200);
+        assert!(!fitted.text.contains("src/main.rs"));
+        assert!(!fitted.text.contains("+line"));
+        assert!(fitted.text.ends_with("latest error\n"));
+        assert_eq!(fitted.truncated.len(), 3);
+        assert!(fitted.truncated[0].starts_with("file_tree"));
+        assert!(fitted.truncated[1].starts_with("git_diff"));
+        assert!(fitted.truncated[2].starts_with("history"));
+
+        let unlimited = fit_prompt(core, &sections, None);
+        assert!(unlimited.truncated.is_empty());
+        assert!(unlimited.text.contains("src/main.rs"));
+    }
+}
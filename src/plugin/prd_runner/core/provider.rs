@@ -1,14 +1,64 @@
 use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Output, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context, Result};
 use serde_json::Value;
 use tracing::info;
 
+use crate::provider::split_provider_command;
 use crate::runtime::signal;
 
+const STDERR_TAIL_CHARS: usize = 800;
+pub const OLLAMA_MODEL_ENV: &str = "AUTOCODE_OLLAMA_MODEL";
+pub const MODEL_ENV: &str = "AUTOCODE_MODEL";
+
+/// Error raised when a provider process is killed for exceeding its response timeout.
+#[derive(Debug)]
+pub struct ProviderTimeout {
+    pub timeout: Duration,
+    pub stderr: Option<String>,
+}
+
+impl std::fmt::Display for ProviderTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "provider timed out after {:?}", self.timeout)?;
+        if let Some(stderr) = &self.stderr {
+            write!(f, " [provider-stderr] {}", stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProviderTimeout {}
+
+/// Tokens a provider reported for one reply (Claude's `usage` envelope).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    /// Prompt tokens, including cache reads and writes.
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn total(self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+
+    pub fn add(&mut self, other: TokenUsage) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+    }
+}
+
+pub fn is_provider_timeout(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<ProviderTimeout>())
+}
+
 pub trait Provider: Send {
     fn name(&self) -> &str;
     fn start(&mut self) -> Result<()>;
@@ -16,6 +66,22 @@ pub trait Provider: Send {
     fn send(&mut self, input: &str) -> Result<()>;
     fn read_output(&mut self, timeout: Duration) -> Result<String>;
     fn is_waiting_for_input(&self, output: &str) -> bool;
+
+    /// Usage reported with the last reply, cleared on read; `None` when the provider has none.
+    fn take_usage(&mut self) -> Option<TokenUsage> {
+        None
+    }
+
+    /// Reads the reply while passing partial output to `on_chunk`; defaults to one final chunk.
+    fn read_output_streaming(
+        &mut self,
+        timeout: Duration,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let output = self.read_output(timeout)?;
+        on_chunk(&output);
+        Ok(output)
+    }
 }
 
 #[derive(Debug)]
@@ -23,12 +89,15 @@ pub struct CliPrintProvider {
     command: String,
     workdir: PathBuf,
     pending_prompt: Option<String>,
+    model: Option<String>,
+    last_usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProviderFlavor {
     Claude,
     Opencode,
+    Ollama,
     Generic,
 }
 
@@ -36,6 +105,13 @@ enum ProviderFlavor {
 struct ClaudeParsedOutput {
     text: String,
     is_error: bool,
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone)]
+struct OpencodeParsedOutput {
+    text: String,
+    is_error: bool,
 }
 
 impl CliPrintProvider {
@@ -44,118 +120,253 @@ impl CliPrintProvider {
             command,
             workdir: workdir.as_ref().to_path_buf(),
             pending_prompt: None,
+            model: None,
+            last_usage: None,
         }
     }
 
+    /// Sets the model: `claude --model <model>` and `ollama run <model>`; other CLIs ignore it.
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model.filter(|value| !value.trim().is_empty());
+        self
+    }
+
     fn flavor(&self) -> ProviderFlavor {
         match self.command.trim() {
             "claude" => ProviderFlavor::Claude,
             "opencode" => ProviderFlavor::Opencode,
+            "ollama" => ProviderFlavor::Ollama,
             _ => ProviderFlavor::Generic,
         }
     }
 
-    fn run_prompt(&self, prompt: &str, timeout: Duration) -> Result<String> {
+    fn run_prompt(&mut self, prompt: &str, timeout: Duration) -> Result<String> {
         if prompt.trim().is_empty() {
             bail!("provider prompt is empty");
         }
 
+        let command = self.build_command(prompt, false)?;
+        let output = run_with_timeout(command, timeout)
+            .with_context(|| format!("failed to execute {}", self.label()))?;
+        self.finish_output(output)
+    }
+
+    /// Like `run_prompt`, but hands each stdout line to `on_chunk` as soon as it is printed.
+    fn run_prompt_streaming(
+        &mut self,
+        prompt: &str,
+        timeout: Duration,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        if prompt.trim().is_empty() {
+            bail!("provider prompt is empty");
+        }
+
+        let flavor = self.flavor();
+        let command = self.build_command(prompt, true)?;
+        let mut on_line = |line: &str| match flavor {
+            // stream-json emits one event per line; only assistant text is worth showing.
+            ProviderFlavor::Claude => {
+                let text = serde_json::from_str::<Value>(line)
+                    .ok()
+                    .filter(|value| value.get("type").and_then(Value::as_str) == Some("assistant"))
+                    .and_then(|value| value.get("message").and_then(extract_text_from_message));
+                if let Some(text) = text {
+                    on_chunk(&format!("{}\n", text));
+                }
+            }
+            ProviderFlavor::Ollama => {
+                let text = strip_escape_codes(line);
+                if !text.trim().is_empty() {
+                    on_chunk(&format!("{}\n", text));
+                }
+            }
+            ProviderFlavor::Opencode | ProviderFlavor::Generic => {
+                on_chunk(&format!("{}\n", line));
+            }
+        };
+        let output = run_streaming_with_timeout(command, timeout, &mut on_line)
+            .with_context(|| format!("failed to execute {}", self.label()))?;
+        self.finish_output(output)
+    }
+
+    fn label(&self) -> String {
         match self.flavor() {
-            ProviderFlavor::Claude => self.run_claude(prompt, timeout),
-            ProviderFlavor::Opencode => self.run_opencode(prompt, timeout),
-            ProviderFlavor::Generic => self.run_generic(prompt, timeout),
+            ProviderFlavor::Generic => format!("provider '{}'", self.command),
+            _ => self.command.trim().to_string(),
         }
     }
 
-    fn run_claude(&self, prompt: &str, timeout: Duration) -> Result<String> {
-        let mut command = Command::new("claude");
-        apply_claude_config_fallback(&mut command, &self.workdir)?;
+    fn build_command(&self, prompt: &str, streaming: bool) -> Result<Command> {
+        let mut command = match self.flavor() {
+            ProviderFlavor::Claude => {
+                let mut command = Command::new("claude");
+                apply_claude_config_fallback(&mut command, &self.workdir)?;
+                command.arg("-p").arg("--output-format");
+                if streaming {
+                    command.arg("stream-json").arg("--verbose");
+                } else {
+                    command.arg("json");
+                }
+                command
+                    .arg("--input-format")
+                    .arg("text")
+                    .arg("--permission-mode")
+                    .arg("bypassPermissions");
+                if let Some(model) = &self.model {
+                    command.arg("--model").arg(model.trim());
+                }
+                command.arg(prompt);
+                command
+            }
+            ProviderFlavor::Opencode => {
+                // JSON arrives as one object at exit, so streaming keeps plain text.
+                // `opencode -p` has no model flag; it picks the model from its own config.
+                let mut command = Command::new("opencode");
+                if !streaming {
+                    command.arg("-f").arg("json");
+                }
+                command.arg("-p").arg(prompt);
+                command
+            }
+            ProviderFlavor::Ollama => {
+                let model = self
+                    .model
+                    .clone()
+                    .or_else(|| std::env::var(OLLAMA_MODEL_ENV).ok())
+                    .filter(|value| !value.trim().is_empty())
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "ollama provider needs a model: set {} or provider.ollama_model",
+                            OLLAMA_MODEL_ENV
+                        )
+                    })?;
+                let mut command = Command::new("ollama");
+                command.arg("run").arg(model.trim()).arg(prompt);
+                command
+            }
+            ProviderFlavor::Generic => {
+                let argv = split_provider_command(&self.command)?;
+                let mut command = Command::new(&argv[0]);
+                command.args(&argv[1..]).arg(prompt);
+                command
+            }
+        };
         command
-            .arg("-p")
-            .arg("--output-format")
-            .arg("json")
-            .arg("--input-format")
-            .arg("text")
-            .arg("--permission-mode")
-            .arg("bypassPermissions")
-            .arg(prompt)
             .current_dir(&self.workdir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        Ok(command)
+    }
 
-        let output = run_with_timeout(command, timeout).context("failed to execute claude")?;
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        let parsed = parse_claude_output(&stdout);
-        let normalized = parsed
-            .as_ref()
-            .map(|v| v.text.clone())
-            .unwrap_or_else(|_| stdout.trim().to_string());
-
-        if let Ok(parsed) = parsed {
-            if parsed.is_error {
-                if stderr.trim().is_empty() {
-                    return Err(anyhow!("claude returned error: {}", parsed.text));
+    fn finish_output(&mut self, output: Output) -> Result<String> {
+        match self.flavor() {
+            ProviderFlavor::Claude => claude_output_to_text(output, &mut self.last_usage),
+            ProviderFlavor::Opencode => opencode_output_to_text(output),
+            ProviderFlavor::Ollama => {
+                let text = output_to_text("ollama", output)?;
+                let parsed = parse_ollama_output(&text);
+                if parsed.is_empty() {
+                    bail!("provider 'ollama' returned empty output");
                 }
-                return Err(anyhow!(
-                    "claude returned error: {} [{}]",
-                    parsed.text,
-                    stderr.trim()
-                ));
+                Ok(parsed)
             }
+            ProviderFlavor::Generic => output_to_text(&self.command, output),
         }
+    }
+}
 
-        if normalized.is_empty() {
-            if !stderr.trim().is_empty() {
-                return Err(anyhow!("claude returned empty output: {}", stderr.trim()));
+fn claude_output_to_text(output: Output, usage: &mut Option<TokenUsage>) -> Result<String> {
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    let parsed = parse_claude_output(&stdout);
+    *usage = parsed.as_ref().ok().and_then(|v| v.usage);
+    let normalized = parsed
+        .as_ref()
+        .map(|v| v.text.clone())
+        .unwrap_or_else(|_| stdout.trim().to_string());
+
+    if let Ok(parsed) = parsed {
+        if parsed.is_error {
+            if stderr.trim().is_empty() {
+                return Err(anyhow!("claude returned error: {}", parsed.text));
             }
-
-            return Err(anyhow!("claude returned empty output"));
+            return Err(anyhow!(
+                "claude returned error: {} [{}]",
+                parsed.text,
+                stderr_tail(&stderr)
+            ));
         }
+    }
 
-        if output.status.success() {
-            return Ok(normalized);
+    if normalized.is_empty() {
+        if !stderr.trim().is_empty() {
+            return Err(anyhow!(
+                "claude returned empty output: {}",
+                stderr_tail(&stderr)
+            ));
         }
 
-        let message = if stderr.trim().is_empty() {
-            normalized
-        } else {
-            format!("{}\n\n[provider-stderr]\n{}", normalized, stderr.trim())
-        };
+        return Err(anyhow!("claude returned empty output"));
+    }
 
-        Err(anyhow!(
-            "claude exited with code {:?}: {}",
-            output.status.code(),
-            message
-        ))
+    if output.status.success() {
+        return Ok(normalized);
     }
 
-    fn run_opencode(&self, prompt: &str, timeout: Duration) -> Result<String> {
-        let mut command = Command::new("opencode");
-        command
-            .arg("-p")
-            .arg(prompt)
-            .current_dir(&self.workdir)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+    let message = if stderr.trim().is_empty() {
+        normalized
+    } else {
+        format!(
+            "{}\n\n[provider-stderr]\n{}",
+            normalized,
+            stderr_tail(&stderr)
+        )
+    };
 
-        let output = run_with_timeout(command, timeout).context("failed to execute opencode")?;
-        output_to_text("opencode", output)
-    }
+    Err(anyhow!(
+        "claude exited with code {:?}: {}",
+        output.status.code(),
+        message
+    ))
+}
 
-    fn run_generic(&self, prompt: &str, timeout: Duration) -> Result<String> {
-        let mut command = Command::new(&self.command);
-        command
-            .arg(prompt)
-            .current_dir(&self.workdir)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+/// Falls back to plain-text handling when stdout carries no opencode JSON payload.
+fn opencode_output_to_text(output: Output) -> Result<String> {
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let Ok(parsed) = parse_opencode_output(&stdout) else {
+        return output_to_text("opencode", output);
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
-        let output = run_with_timeout(command, timeout)
-            .with_context(|| format!("failed to execute provider '{}'", self.command))?;
-        output_to_text(&self.command, output)
+    if parsed.is_error {
+        if stderr.trim().is_empty() {
+            return Err(anyhow!("opencode returned error: {}", parsed.text));
+        }
+        return Err(anyhow!(
+            "opencode returned error: {} [{}]",
+            parsed.text,
+            stderr_tail(&stderr)
+        ));
+    }
+    if parsed.text.is_empty() {
+        if !stderr.trim().is_empty() {
+            return Err(anyhow!(
+                "opencode returned empty output: {}",
+                stderr_tail(&stderr)
+            ));
+        }
+        return Err(anyhow!("opencode returned empty output"));
     }
+    if output.status.success() {
+        return Ok(parsed.text);
+    }
+    Err(anyhow!(
+        "opencode exited with code {:?}: {}",
+        output.status.code(),
+        parsed.text
+    ))
 }
 
 impl Provider for CliPrintProvider {
@@ -189,12 +400,31 @@ impl Provider for CliPrintProvider {
     fn is_waiting_for_input(&self, _output: &str) -> bool {
         false
     }
+
+    fn take_usage(&mut self) -> Option<TokenUsage> {
+        self.last_usage.take()
+    }
+
+    fn read_output_streaming(
+        &mut self,
+        timeout: Duration,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let prompt = self
+            .pending_prompt
+            .take()
+            .context("provider has no pending prompt; send() must be called first")?;
+
+        self.run_prompt_streaming(&prompt, timeout, on_chunk)
+    }
 }
 
 fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<Output> {
     let mut child = command
         .spawn()
         .context("failed to spawn provider process")?;
+    let stdout = PipeReader::spawn(child.stdout.take());
+    let stderr = PipeReader::spawn(child.stderr.take());
 
     let timed_out = wait_with_timeout(&mut child, timeout)?;
     if timed_out {
@@ -202,14 +432,144 @@ fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<Output> {
             .kill()
             .context("failed to kill timed-out provider process")?;
         let _ = child.wait();
-        bail!("provider timed out after {:?}", timeout);
+        let stderr = String::from_utf8_lossy(&stderr.snapshot()).to_string();
+        return Err(ProviderTimeout {
+            timeout,
+            stderr: (!stderr.trim().is_empty()).then(|| stderr_tail(&stderr)),
+        }
+        .into());
     }
 
-    let output = child
-        .wait_with_output()
-        .context("failed to collect provider output")?;
+    let status = child.wait().context("failed to collect provider output")?;
 
-    Ok(output)
+    Ok(Output {
+        status,
+        stdout: stdout.finish(),
+        stderr: stderr.finish(),
+    })
+}
+
+/// Runs the provider like `run_with_timeout`, forwarding stdout lines to `on_line` as they arrive.
+fn run_streaming_with_timeout(
+    mut command: Command,
+    timeout: Duration,
+    on_line: &mut dyn FnMut(&str),
+) -> Result<Output> {
+    let mut child = command
+        .spawn()
+        .context("failed to spawn provider process")?;
+    let stderr = PipeReader::spawn(child.stderr.take());
+    let (line_tx, line_rx) = mpsc::channel::<String>();
+    let stdout_reader = child.stdout.take().map(|pipe| {
+        std::thread::spawn(move || {
+            for line in BufReader::new(pipe).lines() {
+                let Ok(line) = line else { break };
+                if line_tx.send(line).is_err() {
+                    break;
+                }
+            }
+        })
+    });
+
+    let started = Instant::now();
+    let mut stdout = Vec::new();
+    let mut forward = |line: String| {
+        on_line(&line);
+        stdout.extend_from_slice(line.as_bytes());
+        stdout.push(b'\n');
+    };
+    loop {
+        if signal::interrupted() {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("execution interrupted by Ctrl+C");
+        }
+
+        match line_rx.recv_timeout(Duration::from_millis(20)) {
+            Ok(line) => {
+                forward(line);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                if child
+                    .try_wait()
+                    .context("failed while waiting for provider process")?
+                    .is_some()
+                {
+                    break;
+                }
+                // stdout closed but the process is still running.
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+
+        if started.elapsed() >= timeout {
+            child
+                .kill()
+                .context("failed to kill timed-out provider process")?;
+            let _ = child.wait();
+            let stderr = String::from_utf8_lossy(&stderr.snapshot()).to_string();
+            return Err(ProviderTimeout {
+                timeout,
+                stderr: (!stderr.trim().is_empty()).then(|| stderr_tail(&stderr)),
+            }
+            .into());
+        }
+    }
+
+    let status = child.wait().context("failed to collect provider output")?;
+    if let Some(handle) = stdout_reader {
+        let _ = handle.join();
+    }
+    for line in line_rx.try_iter() {
+        forward(line);
+    }
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr: stderr.finish(),
+    })
+}
+
+/// Drains a child pipe on a background thread so a killed provider can still report
+/// what it wrote so far without blocking on processes that keep the pipe open.
+struct PipeReader {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PipeReader {
+    fn spawn(pipe: Option<impl Read + Send + 'static>) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let handle = pipe.map(|mut pipe| {
+            let buffer = Arc::clone(&buffer);
+            std::thread::spawn(move || {
+                let mut chunk = [0u8; 4096];
+                while let Ok(read) = pipe.read(&mut chunk) {
+                    if read == 0 {
+                        break;
+                    }
+                    if let Ok(mut buffer) = buffer.lock() {
+                        buffer.extend_from_slice(&chunk[..read]);
+                    }
+                }
+            })
+        });
+        Self { buffer, handle }
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        self.buffer.lock().map(|v| v.clone()).unwrap_or_default()
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        self.snapshot()
+    }
 }
 
 fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<bool> {
@@ -265,11 +625,11 @@ fn output_to_text(provider: &str, output: Output) -> Result<String> {
     }
 
     let message = if stdout.is_empty() {
-        stderr
+        stderr_tail(&stderr)
     } else if stderr.is_empty() {
         stdout
     } else {
-        format!("{}\n{}", stdout, stderr)
+        format!("{}\n{}", stdout, stderr_tail(&stderr))
     };
 
     Err(anyhow!(
@@ -280,6 +640,20 @@ fn output_to_text(provider: &str, output: Output) -> Result<String> {
     ))
 }
 
+/// Keeps the end of provider stderr, where CLIs usually print the actual failure.
+fn stderr_tail(stderr: &str) -> String {
+    let trimmed = stderr.trim();
+    let count = trimmed.chars().count();
+    if count <= STDERR_TAIL_CHARS {
+        return trimmed.to_string();
+    }
+    let tail = trimmed
+        .chars()
+        .skip(count - STDERR_TAIL_CHARS)
+        .collect::<String>();
+    format!("...{}", tail)
+}
+
 fn apply_claude_config_fallback(command: &mut Command, workdir: &Path) -> Result<()> {
     if std::env::var_os("CLAUDE_CONFIG_DIR").is_some() {
         return Ok(());
@@ -379,23 +753,31 @@ fn extract_claude_result(value: Value) -> Result<ClaudeParsedOutput> {
         .get("is_error")
         .and_then(Value::as_bool)
         .unwrap_or(false);
+    let usage = obj.get("usage").and_then(parse_claude_usage);
 
     if let Some(result) = obj.get("result") {
         return match result {
             Value::String(text) => Ok(ClaudeParsedOutput {
                 text: text.trim().to_string(),
                 is_error,
+                usage,
             }),
             other => Ok(ClaudeParsedOutput {
                 text: serde_json::to_string_pretty(other)?,
                 is_error,
+                usage,
             }),
         };
     }
 
     if let Some(message) = obj.get("message") {
         if let Some(text) = extract_text_from_message(message) {
-            return Ok(ClaudeParsedOutput { text, is_error });
+            let usage = usage.or_else(|| message.get("usage").and_then(parse_claude_usage));
+            return Ok(ClaudeParsedOutput {
+                text,
+                is_error,
+                usage,
+            });
         }
     }
 
@@ -410,6 +792,7 @@ fn extract_claude_result(value: Value) -> Result<ClaudeParsedOutput> {
             return Ok(ClaudeParsedOutput {
                 text: joined,
                 is_error,
+                usage,
             });
         }
     }
@@ -417,6 +800,104 @@ fn extract_claude_result(value: Value) -> Result<ClaudeParsedOutput> {
     Ok(ClaudeParsedOutput {
         text: serde_json::to_string_pretty(&value)?,
         is_error,
+        usage,
+    })
+}
+
+/// Accepts the single `{"response": ...}` object of `opencode -f json` as well as
+/// newline-delimited `text`/`error` events; output with any non-JSON line is not JSON mode.
+fn parse_opencode_output(stdout: &str) -> Result<OpencodeParsedOutput> {
+    let all = stdout.trim();
+    if let Ok(value) = serde_json::from_str::<Value>(all) {
+        if let Some(parsed) = extract_opencode_result(&value) {
+            return Ok(parsed);
+        }
+    }
+
+    let mut fragments = Vec::new();
+    let mut errors = Vec::new();
+    for line in stdout.lines().filter(|line| !line.trim().is_empty()) {
+        let Ok(value) = serde_json::from_str::<Value>(line.trim()) else {
+            bail!("opencode output is not newline-delimited JSON");
+        };
+        if let Some(parsed) = extract_opencode_result(&value) {
+            if parsed.is_error {
+                errors.push(parsed.text);
+            } else if !parsed.text.is_empty() {
+                fragments.push(parsed.text);
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(OpencodeParsedOutput {
+            text: errors.join("; "),
+            is_error: true,
+        });
+    }
+    if !fragments.is_empty() {
+        return Ok(OpencodeParsedOutput {
+            text: fragments.join("\n\n"),
+            is_error: false,
+        });
+    }
+
+    bail!("no opencode JSON payload found in provider output")
+}
+
+fn extract_opencode_result(value: &Value) -> Option<OpencodeParsedOutput> {
+    let obj = value.as_object()?;
+    if let Some(error) = obj.get("error").filter(|error| !error.is_null()) {
+        let text = match error {
+            Value::String(text) => text.trim().to_string(),
+            other => other
+                .get("message")
+                .or_else(|| other.get("data").and_then(|data| data.get("message")))
+                .and_then(Value::as_str)
+                .map(|text| text.trim().to_string())
+                .unwrap_or_else(|| other.to_string()),
+        };
+        return Some(OpencodeParsedOutput {
+            text,
+            is_error: true,
+        });
+    }
+    if let Some(text) = obj.get("response").and_then(Value::as_str) {
+        return Some(OpencodeParsedOutput {
+            text: text.trim().to_string(),
+            is_error: false,
+        });
+    }
+    let part = obj.get("part")?;
+    if part.get("type").and_then(Value::as_str) != Some("text") {
+        return Some(OpencodeParsedOutput {
+            text: String::new(),
+            is_error: false,
+        });
+    }
+    Some(OpencodeParsedOutput {
+        text: part.get("text").and_then(Value::as_str)?.trim().to_string(),
+        is_error: false,
+    })
+}
+
+/// Cache reads/writes are billed as input, so they count toward `input_tokens`.
+fn parse_claude_usage(usage: &Value) -> Option<TokenUsage> {
+    let field = |name: &str| usage.get(name).and_then(Value::as_u64);
+    let input = [
+        "input_tokens",
+        "cache_creation_input_tokens",
+        "cache_read_input_tokens",
+    ]
+    .iter()
+    .filter_map(|name| field(name))
+    .collect::<Vec<_>>();
+    let output = field("output_tokens");
+    if input.is_empty() && output.is_none() {
+        return None;
+    }
+    Some(TokenUsage {
+        input_tokens: input.iter().sum(),
+        output_tokens: output.unwrap_or(0),
     })
 }
 
@@ -444,11 +925,160 @@ fn extract_text_from_message(message: &Value) -> Option<String> {
     }
 }
 
+/// Cleans `ollama run` stdout: drops terminal escape codes and the thinking block of reasoning models.
+fn parse_ollama_output(stdout: &str) -> String {
+    let text = strip_escape_codes(stdout).replace("\r\n", "\n");
+    let visible = match (text.find("Thinking..."), text.find("...done thinking.")) {
+        (Some(start), Some(end)) if start < end => {
+            format!(
+                "{}{}",
+                &text[..start],
+                &text[end + "...done thinking.".len()..]
+            )
+        }
+        _ => text,
+    };
+    visible.trim().to_string()
+}
+
+fn strip_escape_codes(raw: &str) -> String {
+    let mut text = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\u{1b}' {
+            if chars.next_if_eq(&'[').is_some() {
+                for next in chars.by_ref() {
+                    if next.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        text.push(ch);
+    }
+
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
 
-    use super::parse_claude_output;
+    use super::{
+        opencode_output_to_text, parse_claude_output, parse_ollama_output, parse_opencode_output,
+        stderr_tail, CliPrintProvider, Provider, TokenUsage, STDERR_TAIL_CHARS,
+    };
+
+    #[test]
+    fn custom_command_runs_prompt_as_argument() -> Result<()> {
+        let tmp = tempfile::TempDir::new()?;
+        let reply = tmp.path().join("reply.md");
+        std::fs::write(&reply, "```bash\necho ok\n```\n")?;
+
+        let mut provider = CliPrintProvider::new("cat".to_string(), tmp.path());
+        provider.send(&reply.display().to_string())?;
+        let output = provider.read_output(std::time::Duration::from_secs(5))?;
+        assert_eq!(provider.name(), "cat");
+        assert_eq!(output, "```bash\necho ok\n```");
+        Ok(())
+    }
+
+    #[test]
+    fn claude_command_pins_configured_model() -> Result<()> {
+        let tmp = tempfile::TempDir::new()?;
+        let args = |provider: &CliPrintProvider| -> Result<Vec<String>> {
+            Ok(provider
+                .build_command("hi", false)?
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect())
+        };
+
+        let claude = CliPrintProvider::new("claude".to_string(), tmp.path())
+            .with_model(Some(" claude-sonnet-4-5 ".to_string()));
+        let argv = args(&claude)?;
+        let flag = argv
+            .iter()
+            .position(|arg| arg == "--model")
+            .expect("model flag");
+        assert_eq!(argv[flag + 1], "claude-sonnet-4-5");
+        assert_eq!(argv.last().map(String::as_str), Some("hi"));
+
+        let unpinned = CliPrintProvider::new("claude".to_string(), tmp.path());
+        assert!(!args(&unpinned)?.iter().any(|arg| arg == "--model"));
+
+        let generic = CliPrintProvider::new("cat".to_string(), tmp.path())
+            .with_model(Some("ignored".to_string()));
+        assert_eq!(args(&generic)?, vec!["hi"]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_command_keeps_its_own_arguments() -> Result<()> {
+        let tmp = tempfile::TempDir::new()?;
+        let mut provider =
+            CliPrintProvider::new("sh -c 'echo \"got: $0\"'".to_string(), tmp.path());
+        provider.send("hello world")?;
+        let output = provider.read_output(std::time::Duration::from_secs(5))?;
+        assert_eq!(output, "got: hello world");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn streams_lines_as_the_provider_prints_them() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, Instant};
+
+        let tmp = tempfile::TempDir::new()?;
+        let script = tmp.path().join("streaming-provider.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\necho first\nsleep 0.4\necho second\nsleep 0.4\necho \"done: $1\"\n",
+        )?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+
+        let mut provider = CliPrintProvider::new(script.display().to_string(), tmp.path());
+        provider.send("hello")?;
+        let started = Instant::now();
+        let mut chunks = Vec::new();
+        let output = provider.read_output_streaming(Duration::from_secs(10), &mut |chunk| {
+            chunks.push((chunk.to_string(), started.elapsed()));
+        })?;
+
+        assert_eq!(output, "first\nsecond\ndone: hello");
+        let texts = chunks
+            .iter()
+            .map(|(text, _)| text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["first\n", "second\n", "done: hello\n"]);
+        assert!(chunks[0].1 + Duration::from_millis(600) < chunks[2].1);
+
+        provider.send("hello")?;
+        let err = provider
+            .read_output_streaming(Duration::from_millis(200), &mut |_| {})
+            .expect_err("short timeout should fail");
+        assert!(super::is_provider_timeout(&err));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ollama_plain_text_output() {
+        let raw = "Here is the fix:\n\n```bash\necho ok\n```\n\n";
+        assert_eq!(
+            parse_ollama_output(raw),
+            "Here is the fix:\n\n```bash\necho ok\n```"
+        );
+    }
+
+    #[test]
+    fn parse_ollama_strips_escape_codes_and_thinking() {
+        let raw = "\u{1b}[?25l\u{1b}[2KThinking...\nplan the change\n...done thinking.\n\n```bash\ncargo test\n```\u{1b}[?25h\n";
+        assert_eq!(parse_ollama_output(raw), "```bash\ncargo test\n```");
+        assert!(parse_ollama_output("\u{1b}[?25l\u{1b}[?25h\n").is_empty());
+    }
 
     #[test]
     fn parse_claude_result_string() -> Result<()> {
@@ -459,6 +1089,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_claude_usage_counts_cache_as_input() -> Result<()> {
+        let raw = r#"{"type":"result","is_error":false,"result":"ok","usage":{"input_tokens":12,"cache_creation_input_tokens":300,"cache_read_input_tokens":88,"output_tokens":45}}"#;
+        let parsed = parse_claude_output(raw)?;
+        assert_eq!(
+            parsed.usage,
+            Some(TokenUsage {
+                input_tokens: 400,
+                output_tokens: 45,
+            })
+        );
+        assert_eq!(parsed.usage.map(TokenUsage::total), Some(445));
+
+        let raw = r#"{"type":"result","is_error":false,"result":"ok"}"#;
+        assert_eq!(parse_claude_output(raw)?.usage, None);
+        Ok(())
+    }
+
     #[test]
     fn parse_claude_result_errors() -> Result<()> {
         let raw = r#"{"type":"result","is_error":true,"errors":["not logged in"]}"#;
@@ -482,4 +1130,61 @@ mod tests {
         assert!(!parsed.is_error);
         Ok(())
     }
+
+    #[test]
+    fn parse_opencode_response_and_event_payloads() -> Result<()> {
+        let parsed =
+            parse_opencode_output("{\n  \"response\": \"```bash\\necho ok\\n```\\n\"\n}\n")?;
+        assert_eq!(parsed.text, "```bash\necho ok\n```");
+        assert!(!parsed.is_error);
+
+        let events = r#"{"type":"step_start","part":{"type":"step-start"}}
+{"type":"text","part":{"type":"text","text":"first part"}}
+{"type":"tool_use","part":{"type":"tool","tool":"bash"}}
+{"type":"text","part":{"type":"text","text":"second part\n"}}"#;
+        let parsed = parse_opencode_output(events)?;
+        assert_eq!(parsed.text, "first part\n\nsecond part");
+        assert!(!parsed.is_error);
+
+        let failed = r#"{"type":"error","error":{"name":"ProviderAuthError","data":{"message":"missing API key"}}}"#;
+        let parsed = parse_opencode_output(failed)?;
+        assert_eq!(parsed.text, "missing API key");
+        assert!(parsed.is_error);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn opencode_plain_text_falls_back_to_raw_stdout() -> Result<()> {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::{ExitStatus, Output};
+
+        let plain = "Sure:\n{\"response\": \"not the payload\"}\n```bash\necho ok\n```\n";
+        assert!(parse_opencode_output(plain).is_err());
+        let text = opencode_output_to_text(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: plain.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        })?;
+        assert_eq!(text, plain.trim());
+
+        let err = opencode_output_to_text(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: br#"{"error":"rate limited"}"#.to_vec(),
+            stderr: Vec::new(),
+        })
+        .expect_err("error payload");
+        assert_eq!(err.to_string(), "opencode returned error: rate limited");
+        Ok(())
+    }
+
+    #[test]
+    fn stderr_tail_keeps_last_chars() {
+        let long = format!("{}Not logged in", "x".repeat(STDERR_TAIL_CHARS * 2));
+        let tail = stderr_tail(&long);
+        assert!(tail.starts_with("..."));
+        assert!(tail.ends_with("Not logged in"));
+        assert_eq!(tail.chars().count(), STDERR_TAIL_CHARS + 3);
+        assert_eq!(stderr_tail("  short \n"), "short");
+    }
 }
@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Run-wide retry allowance shared by command, provider and acceptance retries.
+#[derive(Debug, Clone, Default)]
+pub struct RetryBudget {
+    inner: Arc<BudgetCounters>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetCounters {
+    limit: Option<u32>,
+    used: AtomicU32,
+    suppressed: AtomicU32,
+}
+
+impl RetryBudget {
+    /// `None` never runs out.
+    pub fn new(limit: Option<u32>) -> Self {
+        Self {
+            inner: Arc::new(BudgetCounters {
+                limit,
+                ..BudgetCounters::default()
+            }),
+        }
+    }
+
+    /// Claims one retry, or records a suppressed retry when the budget is spent.
+    pub fn try_consume(&self) -> bool {
+        let counters = &self.inner;
+        let claimed = counters
+            .used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                match counters.limit {
+                    Some(limit) if used >= limit => None,
+                    _ => Some(used.saturating_add(1)),
+                }
+            })
+            .is_ok();
+        if !claimed {
+            counters.suppressed.fetch_add(1, Ordering::SeqCst);
+        }
+        claimed
+    }
+
+    pub fn limit(&self) -> Option<u32> {
+        self.inner.limit
+    }
+
+    pub fn used(&self) -> u32 {
+        self.inner.used.load(Ordering::SeqCst)
+    }
+
+    pub fn suppressed(&self) -> u32 {
+        self.inner.suppressed.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryBudget;
+
+    #[test]
+    fn stops_granting_once_limit_is_reached() {
+        let budget = RetryBudget::new(Some(2));
+        let shared = budget.clone();
+        assert!(budget.try_consume());
+        assert!(shared.try_consume());
+        assert!(!budget.try_consume());
+        assert_eq!(budget.used(), 2);
+        assert_eq!(shared.suppressed(), 1);
+
+        let unlimited = RetryBudget::new(None);
+        assert!((0..100).all(|_| unlimited.try_consume()));
+        assert_eq!(unlimited.suppressed(), 0);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::plugin::prd_runner::core::provider::{Provider, ProviderTimeout, TokenUsage};
+
+/// Test provider that replays canned replies and records every prompt it receives.
+#[derive(Debug, Default)]
+pub struct ScriptedProvider {
+    replies: VecDeque<String>,
+    prompts: Arc<Mutex<Vec<String>>>,
+    pending: Option<String>,
+    timeouts_first: usize,
+    errors_first: VecDeque<String>,
+    usage_per_reply: Option<TokenUsage>,
+    last_usage: Option<TokenUsage>,
+}
+
+impl ScriptedProvider {
+    pub fn new(replies: &[&str]) -> Self {
+        Self {
+            replies: replies.iter().map(|v| v.to_string()).collect(),
+            prompts: Arc::new(Mutex::new(Vec::new())),
+            pending: None,
+            timeouts_first: 0,
+            errors_first: VecDeque::new(),
+            usage_per_reply: None,
+            last_usage: None,
+        }
+    }
+
+    /// Makes the first reads fail with these error messages, one per read.
+    pub fn failing_first(mut self, errors: &[&str]) -> Self {
+        self.errors_first = errors.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    /// Makes the first `count` reads sleep for the timeout and fail as timed out.
+    pub fn timing_out_first(mut self, count: usize) -> Self {
+        self.timeouts_first = count;
+        self
+    }
+
+    /// Reports this usage with every successful reply.
+    pub fn with_usage(mut self, usage: TokenUsage) -> Self {
+        self.usage_per_reply = Some(usage);
+        self
+    }
+
+    pub fn prompts(&self) -> Arc<Mutex<Vec<String>>> {
+        Arc::clone(&self.prompts)
+    }
+}
+
+impl Provider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn restart(&mut self) -> Result<()> {
+        self.pending = None;
+        Ok(())
+    }
+
+    fn send(&mut self, input: &str) -> Result<()> {
+        self.prompts
+            .lock()
+            .map_err(|_| anyhow::anyhow!("scripted prompts lock poisoned"))?
+            .push(input.to_string());
+        self.pending = Some(input.to_string());
+        Ok(())
+    }
+
+    fn read_output(&mut self, timeout: Duration) -> Result<String> {
+        self.pending
+            .take()
+            .context("scripted provider has no pending prompt")?;
+        if let Some(err) = self.errors_first.pop_front() {
+            bail!("{}", err);
+        }
+        if self.timeouts_first > 0 {
+            self.timeouts_first -= 1;
+            std::thread::sleep(timeout);
+            return Err(ProviderTimeout {
+                timeout,
+                stderr: None,
+            }
+            .into());
+        }
+        match self.replies.pop_front() {
+            Some(reply) => {
+                self.last_usage = self.usage_per_reply;
+                Ok(reply)
+            }
+            None => bail!("scripted provider has no replies left"),
+        }
+    }
+
+    fn is_waiting_for_input(&self, _output: &str) -> bool {
+        false
+    }
+
+    fn take_usage(&mut self) -> Option<TokenUsage> {
+        self.last_usage.take()
+    }
+}
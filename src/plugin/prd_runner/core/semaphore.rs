@@ -0,0 +1,96 @@
+use std::sync::{Condvar, Mutex};
+
+/// Provider calls in flight across every engine running in this process.
+pub static PROVIDER_SLOTS: Semaphore = Semaphore::new();
+
+/// Counting semaphore bounding simultaneous provider calls. Each caller passes its own
+/// limit, so engines with different `provider.max_concurrency` still share one count.
+#[derive(Debug)]
+pub struct Semaphore {
+    in_use: Mutex<usize>,
+    released: Condvar,
+}
+
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl Semaphore {
+    pub const fn new() -> Self {
+        Self {
+            in_use: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until fewer than `limit` (at least 1) permits are held.
+    pub fn acquire(&self, limit: usize) -> SemaphorePermit<'_> {
+        let mut in_use = self
+            .in_use
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        while *in_use >= limit.max(1) {
+            in_use = self
+                .released
+                .wait(in_use)
+                .unwrap_or_else(|poison| poison.into_inner());
+        }
+        *in_use += 1;
+        SemaphorePermit { semaphore: self }
+    }
+}
+
+impl Default for Semaphore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        let mut in_use = self
+            .semaphore
+            .in_use
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        *in_use -= 1;
+        // Waiters may hold different limits, so wake them all to re-check.
+        self.semaphore.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::Semaphore;
+
+    #[test]
+    fn bounds_concurrent_holders() {
+        let semaphore = Arc::new(Semaphore::new());
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..6)
+            .map(|_| {
+                let semaphore = Arc::clone(&semaphore);
+                let active = Arc::clone(&active);
+                let peak = Arc::clone(&peak);
+                std::thread::spawn(move || {
+                    let _permit = semaphore.acquire(2);
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().expect("worker thread");
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
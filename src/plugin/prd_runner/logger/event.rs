@@ -0,0 +1,89 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// One engine event as written to `events.log`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineEvent {
+    pub ts: String,
+    pub kind: String,
+    pub message: String,
+}
+
+impl EngineEvent {
+    pub fn new(kind: &str, message: &str) -> Self {
+        Self {
+            ts: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            kind: kind.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// `[ts] [KIND] message` line used by the text logs.
+    pub fn text_line(&self) -> String {
+        format!("[{}] [{}] {}\n", self.ts, self.kind, self.message)
+    }
+}
+
+pub type EventObserver = Arc<dyn Fn(&EngineEvent) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct EventObservers(Vec<EventObserver>);
+
+impl EventObservers {
+    pub fn push(&mut self, observer: EventObserver) {
+        self.0.push(observer);
+    }
+
+    pub fn notify(&self, event: &EngineEvent) {
+        for observer in &self.0 {
+            observer(event);
+        }
+    }
+}
+
+/// Opens the `--json-logs` target: `-`/`1` is stdout, `2` is stderr, any other number is
+/// an inherited fd, everything else is a file path appended to.
+pub fn open_json_log_sink(target: &str) -> Result<Box<dyn Write + Send>> {
+    match target {
+        "-" | "1" => return Ok(Box::new(io::stdout())),
+        "2" => return Ok(Box::new(io::stderr())),
+        _ => {}
+    }
+    let (path, append) = match target.parse::<u32>() {
+        Ok(fd) => (format!("/dev/fd/{}", fd), false),
+        Err(_) => (target.to_string(), true),
+    };
+    let file = OpenOptions::new()
+        .create(append)
+        .append(append)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open json log sink {}", path))?;
+    Ok(Box::new(file))
+}
+
+/// Serializes every event as one JSON line and flushes it so consumers see it live.
+pub fn json_lines_observer(writer: Box<dyn Write + Send>) -> EventObserver {
+    let writer = Mutex::new(writer);
+    Arc::new(move |event: &EngineEvent| {
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        if let Ok(mut writer) = writer.lock() {
+            let _ = writeln!(writer, "{}", line);
+            let _ = writer.flush();
+        }
+    })
+}
+
+impl fmt::Debug for EventObservers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventObservers({})", self.0.len())
+    }
+}
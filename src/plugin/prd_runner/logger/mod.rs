@@ -1,2 +1,4 @@
+pub mod event;
+pub mod redact;
 pub mod report;
 pub mod writer;
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::Path;
 
 use anyhow::{Context, Result};
@@ -10,12 +11,24 @@ pub struct IterationReport {
     pub iteration: u32,
     pub timestamp: DateTime<Utc>,
     pub duration_seconds: u64,
+    #[serde(default)]
+    pub timing: IterationTiming,
     pub req_status: BTreeMap<String, ReqReport>,
     pub overall_progress: f64,
     pub has_progress: bool,
     pub next_actions: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IterationTiming {
+    pub provider_ms: u64,
+    pub exec_ms: u64,
+    pub acceptance_ms: u64,
+    /// Lowest effective/configured provider timeout ratio seen this iteration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_timeout_ratio: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReqReport {
     pub status: String,
@@ -25,6 +38,11 @@ pub struct ReqReport {
     pub evidence: Option<EvidenceReport>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_reason: Option<String>,
+    /// Declared artifacts copied into the checkpoint's `artifacts/` directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,3 +57,14 @@ pub fn write_report(path: &Path, report: &IterationReport) -> Result<()> {
     std::fs::write(path, content)
         .with_context(|| format!("failed to write report file {}", path.display()))
 }
+
+pub fn append_history(path: &Path, report: &IterationReport) -> Result<()> {
+    let line = serde_json::to_string(report).context("failed to serialize history entry")?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open history file {}", path.display()))?;
+    writeln!(file, "{}", line)
+        .with_context(|| format!("failed to append history file {}", path.display()))
+}
@@ -3,7 +3,12 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tracing::warn;
+
+use super::event::{EngineEvent, EventObservers};
+use super::redact::Redactor;
 
 #[derive(Debug)]
 struct LogFile {
@@ -19,8 +24,21 @@ pub struct LogWriter {
     terminal_output: LogFile,
     events: LogFile,
     prd_snapshot: LogFile,
+    /// `events.ndjson`, mirroring the event, AI and terminal logs when `logging.json` is on.
+    json: Option<LogFile>,
+    rotation: Rotation,
+    /// Event kinds written to the sinks; empty writes everything.
+    event_filter: Vec<String>,
+    redactor: Redactor,
+    observers: EventObservers,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rotation {
     max_file_size_bytes: u64,
     max_rotated_files: usize,
+    /// Gzip files as they are rotated out, keeping them as `<name>.N.gz`.
+    compress: bool,
 }
 
 impl LogWriter {
@@ -46,73 +64,106 @@ impl LogWriter {
             terminal_output,
             events,
             prd_snapshot,
-            max_file_size_bytes,
-            max_rotated_files,
+            json: None,
+            rotation: Rotation {
+                max_file_size_bytes,
+                max_rotated_files,
+                compress: false,
+            },
+            event_filter: Vec::new(),
+            redactor: Redactor::default(),
+            observers: EventObservers::default(),
         })
     }
 
+    pub fn with_compressed_rotation(mut self, compress: bool) -> Self {
+        self.rotation.compress = compress;
+        self
+    }
+
+    pub fn with_json_sink(mut self, enabled: bool) -> Result<Self> {
+        self.json = if enabled {
+            Some(open_log_file(self.dir.join("events.ndjson"))?)
+        } else {
+            None
+        };
+        Ok(self)
+    }
+
+    pub fn with_event_filter(mut self, kinds: Vec<String>) -> Self {
+        self.event_filter = kinds;
+        self
+    }
+
+    /// Masks secrets in every line before it reaches a file, the JSON sink or observers.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    pub fn set_observers(&mut self, observers: EventObservers) {
+        self.observers = observers;
+    }
+
     pub fn log_session(&mut self, message: &str) -> Result<()> {
-        write_line(
-            &mut self.session,
-            "SESSION",
-            message,
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )
+        let record = self.record("SESSION", message);
+        write_record(&mut self.session, &record, self.rotation)
     }
 
     pub fn log_ai(&mut self, message: &str) -> Result<()> {
-        write_line(
-            &mut self.ai_output,
-            "AI",
-            message,
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )
+        let record = self.record("AI", message);
+        write_record(&mut self.ai_output, &record, self.rotation)?;
+        self.write_json(&record)
     }
 
     pub fn log_terminal(&mut self, message: &str) -> Result<()> {
-        write_line(
-            &mut self.terminal_output,
-            "TERMINAL",
-            message,
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )
+        let record = self.record("TERMINAL", message);
+        write_record(&mut self.terminal_output, &record, self.rotation)?;
+        self.write_json(&record)
     }
 
     pub fn log_event(&mut self, event: &str, message: &str) -> Result<()> {
-        write_line(
-            &mut self.events,
-            event,
-            message,
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )
+        let record = self.record(event, message);
+        if self.event_passes_filter(event) {
+            write_record(&mut self.events, &record, self.rotation)?;
+            self.write_json(&record)?;
+        }
+        self.observers.notify(&record);
+        Ok(())
+    }
+
+    fn record(&self, kind: &str, message: &str) -> EngineEvent {
+        EngineEvent::new(kind, &self.redactor.redact(message))
+    }
+
+    /// `STOP` and error events bypass the filter so a filtered log still explains the exit.
+    fn event_passes_filter(&self, kind: &str) -> bool {
+        self.event_filter.is_empty()
+            || kind == "STOP"
+            || kind.contains("ERROR")
+            || self.event_filter.iter().any(|allowed| allowed == kind)
+    }
+
+    fn write_json(&mut self, record: &EngineEvent) -> Result<()> {
+        let Some(json) = self.json.as_mut() else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(record).context("failed to serialize log record")?;
+        line.push('\n');
+        write_raw(json, &line, self.rotation)
     }
 
     pub fn save_prd_snapshot(&mut self, prd_markdown: &str) -> Result<()> {
-        write_line(
-            &mut self.prd_snapshot,
-            "PRD_SNAPSHOT_START",
-            "----------------------------------------",
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )?;
-        write_line(
-            &mut self.prd_snapshot,
-            "PRD_CONTENT",
-            prd_markdown,
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )?;
-        write_line(
-            &mut self.prd_snapshot,
-            "PRD_SNAPSHOT_END",
-            "----------------------------------------",
-            self.max_file_size_bytes,
-            self.max_rotated_files,
-        )
+        let separator = "----------------------------------------";
+        for (kind, message) in [
+            ("PRD_SNAPSHOT_START", separator),
+            ("PRD_CONTENT", prd_markdown),
+            ("PRD_SNAPSHOT_END", separator),
+        ] {
+            let record = self.record(kind, message);
+            write_record(&mut self.prd_snapshot, &record, self.rotation)?;
+        }
+        Ok(())
     }
 }
 
@@ -129,22 +180,12 @@ fn open_append(path: &Path) -> Result<File> {
         .with_context(|| format!("failed to open log file {}", path.display()))
 }
 
-fn write_line(
-    log_file: &mut LogFile,
-    kind: &str,
-    message: &str,
-    max_file_size_bytes: u64,
-    max_rotated_files: usize,
-) -> Result<()> {
-    let ts = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-    let line = format!("[{}] [{}] {}\n", ts, kind, message);
-
-    rotate_if_needed(
-        log_file,
-        line.len() as u64,
-        max_file_size_bytes,
-        max_rotated_files,
-    )?;
+fn write_record(log_file: &mut LogFile, record: &EngineEvent, rotation: Rotation) -> Result<()> {
+    write_raw(log_file, &record.text_line(), rotation)
+}
+
+fn write_raw(log_file: &mut LogFile, line: &str, rotation: Rotation) -> Result<()> {
+    rotate_if_needed(log_file, line.len() as u64, rotation)?;
 
     log_file
         .file
@@ -153,13 +194,8 @@ fn write_line(
     log_file.file.flush().context("failed to flush log file")
 }
 
-fn rotate_if_needed(
-    log_file: &mut LogFile,
-    incoming_len: u64,
-    max_file_size_bytes: u64,
-    max_rotated_files: usize,
-) -> Result<()> {
-    if max_file_size_bytes == 0 || max_rotated_files == 0 {
+fn rotate_if_needed(log_file: &mut LogFile, incoming_len: u64, rotation: Rotation) -> Result<()> {
+    if rotation.max_file_size_bytes == 0 || rotation.max_rotated_files == 0 {
         return Ok(());
     }
 
@@ -169,53 +205,98 @@ fn rotate_if_needed(
         .with_context(|| format!("failed to read metadata for {}", log_file.path.display()))?
         .len();
 
-    if current_size.saturating_add(incoming_len) <= max_file_size_bytes {
+    if current_size.saturating_add(incoming_len) <= rotation.max_file_size_bytes {
         return Ok(());
     }
 
-    rotate_file_chain(&log_file.path, max_rotated_files)?;
+    rotate_file_chain(
+        &log_file.path,
+        rotation.max_rotated_files,
+        rotation.compress,
+    )?;
     log_file.file = open_append(&log_file.path)?;
     Ok(())
 }
 
-fn rotate_file_chain(base_path: &Path, max_rotated_files: usize) -> Result<()> {
+/// Shifts `<name>.N` / `<name>.N.gz` up by one, dropping the oldest, then moves the live file
+/// to slot 1 (gzipped when `compress` is set).
+fn rotate_file_chain(base_path: &Path, max_rotated_files: usize, compress: bool) -> Result<()> {
     for index in (1..=max_rotated_files).rev() {
-        let dst = rotated_path(base_path, index);
-        if dst.exists() {
-            std::fs::remove_file(&dst)
-                .with_context(|| format!("failed to remove old rotated log {}", dst.display()))?;
+        for gz in [false, true] {
+            let dst = rotated_path(base_path, index, gz);
+            if dst.exists() {
+                std::fs::remove_file(&dst).with_context(|| {
+                    format!("failed to remove old rotated log {}", dst.display())
+                })?;
+            }
         }
 
-        let src = if index == 1 {
-            base_path.to_path_buf()
+        if index == 1 {
+            rename_if_exists(base_path, &rotated_path(base_path, 1, false))?;
         } else {
-            rotated_path(base_path, index - 1)
-        };
-
-        if src.exists() {
-            std::fs::rename(&src, &dst).with_context(|| {
-                format!(
-                    "failed to rotate log file {} -> {}",
-                    src.display(),
-                    dst.display()
-                )
-            })?;
+            for gz in [false, true] {
+                rename_if_exists(
+                    &rotated_path(base_path, index - 1, gz),
+                    &rotated_path(base_path, index, gz),
+                )?;
+            }
         }
     }
 
+    if compress {
+        let newest = rotated_path(base_path, 1, false);
+        if newest.exists() {
+            if let Err(err) = gzip_in_place(&newest) {
+                warn!(path = %newest.display(), "failed to compress rotated log: {:#}", err);
+            }
+        }
+    }
     Ok(())
 }
 
-fn rotated_path(base_path: &Path, index: usize) -> PathBuf {
-    PathBuf::from(format!("{}.{}", base_path.display(), index))
+fn rename_if_exists(src: &Path, dst: &Path) -> Result<()> {
+    if !src.exists() {
+        return Ok(());
+    }
+    std::fs::rename(src, dst).with_context(|| {
+        format!(
+            "failed to rotate log file {} -> {}",
+            src.display(),
+            dst.display()
+        )
+    })
+}
+
+/// Replaces `path` with a gzipped `path.gz`.
+fn gzip_in_place(path: &Path) -> Result<()> {
+    let gz_path = PathBuf::from(format!("{}.gz", path.display()));
+    let mut input =
+        File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let output = File::create(&gz_path)
+        .with_context(|| format!("failed to create {}", gz_path.display()))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    std::io::copy(&mut input, &mut encoder)
+        .and_then(|_| encoder.finish())
+        .with_context(|| format!("failed to compress {}", path.display()))?;
+    std::fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))
+}
+
+fn rotated_path(base_path: &Path, index: usize, gz: bool) -> PathBuf {
+    let suffix = if gz { ".gz" } else { "" };
+    PathBuf::from(format!("{}.{}{}", base_path.display(), index, suffix))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Read;
+
     use anyhow::Result;
+    use flate2::read::GzDecoder;
     use tempfile::TempDir;
 
     use super::LogWriter;
+    use crate::plugin::prd_runner::logger::event::EngineEvent;
+    use crate::plugin::prd_runner::logger::redact::{default_redact_patterns, Redactor};
 
     #[test]
     fn writes_expected_log_files() -> Result<()> {
@@ -235,6 +316,74 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn json_sink_writes_one_record_per_line() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let mut writer = LogWriter::new(tmp.path(), 1024 * 1024, 3)?.with_json_sink(true)?;
+        writer.log_event("ITERATION_START", "iteration=1")?;
+        writer.log_ai("line with \"quotes\"\nand a newline")?;
+        writer.log_terminal("$ cargo test")?;
+
+        let raw = std::fs::read_to_string(tmp.path().join("events.ndjson"))?;
+        let records = raw
+            .lines()
+            .map(serde_json::from_str::<EngineEvent>)
+            .collect::<Result<Vec<_>, _>>()?;
+        let kinds = records.iter().map(|r| r.kind.as_str()).collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["ITERATION_START", "AI", "TERMINAL"]);
+        assert_eq!(records[1].message, "line with \"quotes\"\nand a newline");
+        assert!(!records[0].ts.is_empty());
+
+        let events = std::fs::read_to_string(tmp.path().join("events.log"))?;
+        assert!(events.contains("[ITERATION_START] iteration=1"));
+        Ok(())
+    }
+
+    #[test]
+    fn event_filter_keeps_only_listed_kinds() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let mut writer = LogWriter::new(tmp.path(), 1024 * 1024, 3)?
+            .with_event_filter(vec!["REQ_FAILED".to_string()])
+            .with_json_sink(true)?;
+        writer.log_event("AI_PROVIDER_START", "req=REQ-001")?;
+        writer.log_event("REQ_FAILED", "req=REQ-001")?;
+        writer.log_event("AI_ERROR", "provider crashed")?;
+        writer.log_event("STOP", "reason=max_runtime")?;
+
+        let events = std::fs::read_to_string(tmp.path().join("events.log"))?;
+        assert!(!events.contains("AI_PROVIDER_START"));
+        assert!(events.contains("[REQ_FAILED] req=REQ-001"));
+        assert!(events.contains("[AI_ERROR]"));
+        assert!(events.contains("[STOP]"));
+        let json = std::fs::read_to_string(tmp.path().join("events.ndjson"))?;
+        assert!(!json.contains("AI_PROVIDER_START"));
+        assert_eq!(json.lines().count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn redacts_secrets_before_writing() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let redactor = Redactor::new(&default_redact_patterns())?;
+        let mut writer = LogWriter::new(tmp.path(), 1024 * 1024, 3)?
+            .with_redactor(redactor)
+            .with_json_sink(true)?;
+        writer.log_ai("export OPENAI_API_KEY=sk-ABCDEFGHIJKLMNOPQRSTUVWX && run")?;
+        writer.log_event(
+            "AI_CMD_EXEC",
+            "command='curl -H \"Authorization: Bearer abc.def\"'",
+        )?;
+
+        let ai = std::fs::read_to_string(tmp.path().join("ai_output.log"))?;
+        assert!(ai.contains("export OPENAI_API_KEY=*** && run"));
+        assert!(!ai.contains("sk-ABCDEF"));
+        let events = std::fs::read_to_string(tmp.path().join("events.log"))?;
+        assert!(events.contains("Authorization: ***"));
+        let json = std::fs::read_to_string(tmp.path().join("events.ndjson"))?;
+        assert!(!json.contains("sk-ABCDEF") && !json.contains("abc.def"));
+        Ok(())
+    }
+
     #[test]
     fn rotates_when_file_reaches_limit() -> Result<()> {
         let tmp = TempDir::new()?;
@@ -248,4 +397,28 @@ mod tests {
         assert!(tmp.path().join("events.log.1").exists());
         Ok(())
     }
+
+    #[test]
+    fn compresses_rotated_files() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let mut writer = LogWriter::new(tmp.path(), 120, 2)?.with_compressed_rotation(true);
+
+        for idx in 0..20 {
+            writer.log_event("TEST", &format!("line-{idx:02} abcdefghijklmnopqrstuvwxyz"))?;
+        }
+
+        let live = std::fs::read_to_string(tmp.path().join("events.log"))?;
+        assert!(live.contains("line-19"));
+        assert!(!tmp.path().join("events.log.1").exists());
+        assert!(!tmp.path().join("events.log.3.gz").exists());
+        let rotated = tmp.path().join("events.log.1.gz");
+        assert!(rotated.exists());
+        assert!(tmp.path().join("events.log.2.gz").exists());
+
+        let mut text = String::new();
+        GzDecoder::new(std::fs::File::open(&rotated)?).read_to_string(&mut text)?;
+        assert!(text.contains("[TEST] line-1"));
+        assert!(!text.contains("line-19"));
+        Ok(())
+    }
 }
@@ -2,12 +2,15 @@ use std::fmt;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use serde::Serialize;
 
 use crate::plugin::prd_runner::config::ConvergenceConfig;
 
 #[derive(Debug, Clone)]
 pub struct ConvergenceGuard {
     start: Instant,
+    /// Runtime already consumed before this process started (resumed runs).
+    prior: Duration,
     config: ConvergenceConfig,
     max_runtime: std::time::Duration,
 }
@@ -15,53 +18,239 @@ pub struct ConvergenceGuard {
 #[derive(Debug, Clone)]
 pub enum StopReason {
     MaxRuntime(String),
+    Interrupted,
+    InsufficientTime(String),
+    ProviderFatal(String),
+    /// The workspace itself is unusable (disk full, read-only, not writable).
+    ExecutionFatal(String),
+    MaxIterations(u32),
+    /// Consecutive iterations that completed no requirement.
+    Stagnation(u32),
+    /// Provider-reported tokens used so far, and the `max_total_tokens` limit.
+    TokenBudget {
+        used: u64,
+        limit: u64,
+    },
+    /// Every requirement is done and acceptance passed this iteration.
+    Completed,
 }
 
 impl fmt::Display for StopReason {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             StopReason::MaxRuntime(v) => write!(f, "reached max_runtime ({})", v),
+            StopReason::Interrupted => write!(f, "received Ctrl+C or SIGTERM"),
+            StopReason::InsufficientTime(v)
+            | StopReason::ProviderFatal(v)
+            | StopReason::ExecutionFatal(v) => write!(f, "{}", v),
+            StopReason::MaxIterations(n) => write!(f, "reached max_iterations ({})", n),
+            StopReason::Stagnation(n) => write!(f, "no progress for {} iterations", n),
+            StopReason::Completed => write!(f, "all requirements complete and acceptance passed"),
+            StopReason::TokenBudget { used, limit } => {
+                write!(
+                    f,
+                    "reached max_total_tokens ({} used, limit {})",
+                    used, limit
+                )
+            }
         }
     }
 }
 
+/// How a run ended, mapped to the process exit code documented in the README.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Converged,
+    Incomplete,
+    Interrupted,
+    ProviderFailed,
+}
+
+impl RunOutcome {
+    pub fn classify(completed: bool, reason: &StopReason) -> Self {
+        match reason {
+            StopReason::Interrupted => RunOutcome::Interrupted,
+            StopReason::ProviderFatal(_) => RunOutcome::ProviderFailed,
+            _ if completed => RunOutcome::Converged,
+            _ => RunOutcome::Incomplete,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunOutcome::Converged => "converged",
+            RunOutcome::Incomplete => "incomplete",
+            RunOutcome::Interrupted => "interrupted",
+            RunOutcome::ProviderFailed => "provider_failed",
+        }
+    }
+
+    pub fn exit_code(self) -> u8 {
+        match self {
+            RunOutcome::Converged => 0,
+            RunOutcome::Incomplete => 2,
+            RunOutcome::ProviderFailed => 3,
+            RunOutcome::Interrupted => 130,
+        }
+    }
+
+    /// Only an incomplete run can be resumed without user intervention.
+    pub fn resumable(self) -> bool {
+        self == RunOutcome::Incomplete
+    }
+}
+
 impl ConvergenceGuard {
     pub fn new(config: ConvergenceConfig) -> Result<Self> {
         let max_runtime = config.max_runtime_duration()?;
         Ok(Self {
             start: Instant::now(),
+            prior: Duration::ZERO,
             config,
             max_runtime,
         })
     }
 
+    /// Counts `prior` against `max_runtime`, so a resume continues the original budget.
+    pub fn with_prior_runtime(mut self, prior: Duration) -> Self {
+        self.prior = prior;
+        self
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.prior.saturating_add(self.start.elapsed())
+    }
+
     pub fn check(&self) -> Option<StopReason> {
-        if self.start.elapsed() >= self.max_runtime {
+        if self.elapsed() >= self.max_runtime {
             return Some(StopReason::MaxRuntime(self.config.max_runtime.clone()));
         }
 
         None
     }
 
+    /// Inactive until the provider reports usage; providers without usage data never trip it.
+    pub fn check_token_budget(&self, used: Option<u64>) -> Option<StopReason> {
+        let limit = self.config.max_total_tokens?;
+        let used = used?;
+        (used >= limit).then_some(StopReason::TokenBudget { used, limit })
+    }
+
     pub fn remaining(&self) -> Duration {
-        self.max_runtime.saturating_sub(self.start.elapsed())
+        self.max_runtime.saturating_sub(self.elapsed())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::plugin::prd_runner::config::ConvergenceConfig;
 
-    use super::ConvergenceGuard;
+    use super::{ConvergenceGuard, RunOutcome, StopReason};
 
     #[test]
     fn stops_by_runtime_threshold() {
         let guard = ConvergenceGuard::new(ConvergenceConfig {
             max_runtime: "0s".to_string(),
+            ..ConvergenceConfig::default()
         })
         .expect("guard should be created");
 
         let reason = guard.check().expect("expected to stop on max runtime");
         assert!(reason.to_string().contains("max_runtime"));
     }
+
+    #[test]
+    fn resumed_guard_continues_the_original_budget() {
+        let config = ConvergenceConfig {
+            max_runtime: "4h".to_string(),
+            ..ConvergenceConfig::default()
+        };
+        let resumed = ConvergenceGuard::new(config.clone())
+            .expect("guard should be created")
+            .with_prior_runtime(Duration::from_secs(3 * 3600 + 50 * 60));
+        let remaining = resumed.remaining();
+        assert!(remaining <= Duration::from_secs(10 * 60));
+        assert!(remaining > Duration::from_secs(9 * 60));
+        assert!(resumed.elapsed() >= Duration::from_secs(3 * 3600 + 50 * 60));
+        assert!(resumed.check().is_none());
+
+        let exhausted = ConvergenceGuard::new(config)
+            .expect("guard should be created")
+            .with_prior_runtime(Duration::from_secs(4 * 3600));
+        assert!(exhausted.remaining().is_zero());
+        assert!(matches!(exhausted.check(), Some(StopReason::MaxRuntime(_))));
+    }
+
+    #[test]
+    fn token_budget_trips_only_with_reported_usage() {
+        let guard = ConvergenceGuard::new(ConvergenceConfig {
+            max_total_tokens: Some(1_000),
+            ..ConvergenceConfig::default()
+        })
+        .expect("guard should be created");
+
+        assert!(guard.check_token_budget(None).is_none());
+        assert!(guard.check_token_budget(Some(999)).is_none());
+        let reason = guard
+            .check_token_budget(Some(1_200))
+            .expect("expected to stop on token budget");
+        assert!(matches!(
+            reason,
+            StopReason::TokenBudget {
+                used: 1_200,
+                limit: 1_000
+            }
+        ));
+        assert_eq!(RunOutcome::classify(false, &reason), RunOutcome::Incomplete);
+
+        let unlimited = ConvergenceGuard::new(ConvergenceConfig::default()).expect("guard");
+        assert!(unlimited.check_token_budget(Some(u64::MAX)).is_none());
+    }
+
+    #[test]
+    fn maps_each_stop_path_to_outcome_and_exit_code() {
+        let runtime = StopReason::MaxRuntime("10m".to_string());
+        let cases = [
+            (true, runtime.clone(), RunOutcome::Converged, 0, false),
+            (false, runtime, RunOutcome::Incomplete, 2, true),
+            (
+                false,
+                StopReason::InsufficientTime("fair share too small".to_string()),
+                RunOutcome::Incomplete,
+                2,
+                true,
+            ),
+            (
+                false,
+                StopReason::ProviderFatal("login required".to_string()),
+                RunOutcome::ProviderFailed,
+                3,
+                false,
+            ),
+            (
+                false,
+                StopReason::Interrupted,
+                RunOutcome::Interrupted,
+                130,
+                false,
+            ),
+            (
+                true,
+                StopReason::Interrupted,
+                RunOutcome::Interrupted,
+                130,
+                false,
+            ),
+        ];
+
+        for (completed, reason, outcome, code, resumable) in cases {
+            let classified = RunOutcome::classify(completed, &reason);
+            assert_eq!(classified, outcome, "{}", reason);
+            assert_eq!(classified.exit_code(), code);
+            assert_eq!(classified.resumable(), resumable);
+        }
+    }
 }
@@ -11,12 +11,34 @@ pub struct EvalResult {
     pub attempt_increment: u32,
 }
 
+/// Runs every validation command in order; the requirement is `Done` only when each one
+/// satisfies the pass condition, and the first failure's evidence is recorded.
 pub fn evaluate_requirement(
     req: &Requirement,
     executor: &CommandExecutor,
     output_summary_max_chars: usize,
 ) -> EvalResult {
-    match executor.run(&req.validate_command) {
+    let mut attempts = 0;
+    let mut last = None;
+    for command in req.validation_commands() {
+        let mut result = evaluate_command(req, command, executor, output_summary_max_chars);
+        attempts += result.attempt_increment;
+        result.attempt_increment = attempts;
+        if result.status != ReqStatus::Done {
+            return result;
+        }
+        last = Some(result);
+    }
+    last.expect("requirement has at least one validation command")
+}
+
+fn evaluate_command(
+    req: &Requirement,
+    command: &str,
+    executor: &CommandExecutor,
+    output_summary_max_chars: usize,
+) -> EvalResult {
+    match executor.run(command) {
         Ok(result) => {
             let condition = evaluate_pass_condition(&req.pass_condition, &result)
                 .map_err(|err| err.to_string());
@@ -64,7 +86,7 @@ pub fn evaluate_requirement_dry_run(req: &Requirement) -> EvalResult {
     EvalResult {
         status: ReqStatus::Done,
         evidence: Some(ReqEvidence {
-            command: format!("{} [dry-run]", req.validate_command),
+            command: format!("{} [dry-run]", req.validation_commands().join(" && ")),
             exit_code: 0,
             output_summary: "dry-run: validation skipped".to_string(),
         }),
@@ -90,8 +112,13 @@ mod tests {
             priority: Some("high".to_string()),
             description: "desc".to_string(),
             validate_command: command.to_string(),
+            validate_commands: Vec::new(),
             pass_condition: pass_condition.to_string(),
             tasks: vec![],
+            artifacts: Vec::new(),
+            provider_timeout: None,
+            depends_on: Vec::new(),
+            workdir: None,
         }
     }
 
@@ -101,6 +128,7 @@ mod tests {
         let config = ExecutionConfig {
             command_timeout: "1s".to_string(),
             max_retry: 0,
+            ..ExecutionConfig::default()
         };
 
         let executor = CommandExecutor::new(config, tmp.path()).expect("executor should be built");
@@ -109,6 +137,34 @@ mod tests {
         assert_eq!(result.status, ReqStatus::Done);
     }
 
+    #[test]
+    fn requires_every_validate_command_to_pass() {
+        let tmp = TempDir::new().expect("tmp dir should be created");
+        let config = ExecutionConfig {
+            command_timeout: "1s".to_string(),
+            max_retry: 0,
+            ..ExecutionConfig::default()
+        };
+        let executor = CommandExecutor::new(config, tmp.path()).expect("executor should be built");
+
+        let mut req = requirement("echo first", "退出码 = 0");
+        req.validate_commands = vec!["echo first".to_string(), "echo second".to_string()];
+        let result = evaluate_requirement(&req, &executor, 400);
+        assert_eq!(result.status, ReqStatus::Done);
+        assert_eq!(result.attempt_increment, 2);
+
+        req.validate_commands = vec![
+            "echo first".to_string(),
+            "exit 3".to_string(),
+            "echo never".to_string(),
+        ];
+        let result = evaluate_requirement(&req, &executor, 400);
+        assert_eq!(result.status, ReqStatus::Failed);
+        let evidence = result.evidence.expect("failure should carry evidence");
+        assert_eq!(evidence.command, "exit 3");
+        assert_eq!(evidence.exit_code, 3);
+    }
+
     #[test]
     fn returns_done_in_dry_run() {
         let req = requirement("echo no-op", "退出码 = 0");
@@ -2,4 +2,5 @@ pub mod convergence;
 pub mod driver;
 pub mod evaluator;
 pub mod pass_condition;
+pub mod single;
 pub mod state;
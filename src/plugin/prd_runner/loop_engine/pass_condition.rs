@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use regex::Regex;
 
@@ -9,18 +11,169 @@ pub struct ConditionEvaluation {
     pub reason: String,
 }
 
+const OR_SEPARATORS: [&str; 2] = [" || ", " 或 "];
+const AND_SEPARATORS: [&str; 2] = [" && ", " 且 "];
+
+/// Evaluates `condition`, where `&&`/`且` binds tighter than `||`/`或`.
 pub fn evaluate_pass_condition(
     condition: &str,
     result: &CommandResult,
 ) -> Result<ConditionEvaluation> {
+    evaluate_compound(condition, result, &OR_SEPARATORS, " || ", false)
+}
+
+fn evaluate_compound(
+    condition: &str,
+    result: &CommandResult,
+    separators: &[&str],
+    joiner: &str,
+    require_all: bool,
+) -> Result<ConditionEvaluation> {
+    let clauses = split_clauses(condition, separators);
+    let evaluate_clause = |clause: &str| {
+        if require_all {
+            evaluate_single(clause, result)
+        } else {
+            evaluate_compound(clause, result, &AND_SEPARATORS, " && ", true)
+        }
+    };
+    if clauses.len() == 1 {
+        return evaluate_clause(clauses[0]);
+    }
+
+    let evaluations = clauses
+        .into_iter()
+        .map(evaluate_clause)
+        .collect::<Result<Vec<_>>>()?;
+    let passed = if require_all {
+        evaluations.iter().all(|evaluation| evaluation.passed)
+    } else {
+        evaluations.iter().any(|evaluation| evaluation.passed)
+    };
+    let reason = evaluations
+        .iter()
+        .map(|evaluation| {
+            format!(
+                "[{}] {}",
+                if evaluation.passed { "pass" } else { "fail" },
+                evaluation.reason
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(joiner);
+    Ok(ConditionEvaluation { passed, reason })
+}
+
+/// Splits on `separators` outside quoted needles (`'…'`, `"…"`, `` `…` ``) and outside a
+/// `matches /…/` regex literal, so single conditions containing `&&`/`||` stay whole.
+fn split_clauses<'a>(condition: &'a str, separators: &[&str]) -> Vec<&'a str> {
+    let mut clauses = Vec::new();
+    let mut clause_start = 0;
+    let mut quote = None;
+    let mut in_regex = false;
+    let mut escaped = false;
+    let mut chars = condition.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        let rest = &condition[index + ch.len_utf8()..];
+        if let Some(open) = quote {
+            if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        if in_regex {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '/' && closes_regex(rest) {
+                in_regex = false;
+            }
+            continue;
+        }
+
+        let before = &condition[clause_start..index];
+        if matches!(ch, '\'' | '"' | '`') && before.chars().last().is_none_or(char::is_whitespace) {
+            quote = Some(ch);
+        } else if ch == '/' && opens_regex(before) {
+            in_regex = true;
+        } else if let Some(separator) = separators
+            .iter()
+            .find(|separator| condition[index..].starts_with(**separator))
+        {
+            clauses.push(before);
+            clause_start = index + separator.len();
+            while chars.peek().is_some_and(|(next, _)| *next < clause_start) {
+                chars.next();
+            }
+        }
+    }
+    clauses.push(&condition[clause_start..]);
+    clauses
+}
+
+pub(crate) fn opens_regex(before: &str) -> bool {
+    let before = before.trim_end().to_lowercase();
+    before.ends_with("matches") || before.ends_with("匹配")
+}
+
+/// A `/` ends the regex literal only at the end of the condition or before a separator.
+pub(crate) fn closes_regex(rest: &str) -> bool {
+    rest.trim().is_empty()
+        || OR_SEPARATORS
+            .iter()
+            .chain(AND_SEPARATORS.iter())
+            .any(|separator| rest.starts_with(separator))
+}
+
+fn evaluate_single(condition: &str, result: &CommandResult) -> Result<ConditionEvaluation> {
     let normalized = condition.trim();
     if normalized.is_empty() {
         return Ok(ConditionEvaluation {
             passed: result.success(),
-            reason: format!(
-                "default check (exit_code={}, timed_out={})",
-                result.exit_code, result.timed_out
-            ),
+            reason: match &result.aborted {
+                Some(reason) => {
+                    format!("default check (exit_code={}, {})", result.exit_code, reason)
+                }
+                None => format!(
+                    "default check (exit_code={}, timed_out={})",
+                    result.exit_code, result.timed_out
+                ),
+            },
+        });
+    }
+
+    if let Some(pattern) = parse_output_matches(normalized)? {
+        let merged = result.merged_output();
+        return Ok(match Regex::new(&pattern) {
+            Ok(regex) => ConditionEvaluation {
+                passed: regex.is_match(&merged),
+                reason: format!("expect output matches /{}/", pattern),
+            },
+            Err(err) => ConditionEvaluation {
+                passed: false,
+                reason: format!("invalid regex /{}/: {}", pattern, err),
+            },
+        });
+    }
+
+    if let Some(limit) = parse_duration_limit(normalized)? {
+        let passed = !result.timed_out && result.duration_ms <= limit.as_millis();
+        return Ok(ConditionEvaluation {
+            passed,
+            reason: if result.timed_out {
+                format!(
+                    "expect duration <= {}ms, command timed out after {}ms",
+                    limit.as_millis(),
+                    result.duration_ms
+                )
+            } else {
+                format!(
+                    "expect duration <= {}ms, took {}ms",
+                    limit.as_millis(),
+                    result.duration_ms
+                )
+            },
         });
     }
 
@@ -36,7 +189,7 @@ pub fn evaluate_pass_condition(
     }
 
     if let Some(coverage_required) = parse_coverage_threshold(normalized)? {
-        let actual = extract_coverage_value(&format!("{}\n{}", result.stdout, result.stderr));
+        let actual = extract_coverage_value(&result.merged_output());
         let passed = actual
             .map(|value| value + f64::EPSILON >= coverage_required)
             .unwrap_or(false);
@@ -56,7 +209,7 @@ pub fn evaluate_pass_condition(
     }
 
     if is_clippy_warning_rule(normalized) {
-        let merged = format!("{}\n{}", result.stdout, result.stderr).to_ascii_lowercase();
+        let merged = result.merged_output().to_ascii_lowercase();
         let passed = result.exit_code == 0 && !merged.contains("warning:");
         return Ok(ConditionEvaluation {
             passed,
@@ -64,8 +217,17 @@ pub fn evaluate_pass_condition(
         });
     }
 
+    if let Some(needle) = parse_output_not_contains(normalized)? {
+        let merged = result.merged_output();
+        let passed = !merged.contains(&needle);
+        return Ok(ConditionEvaluation {
+            passed,
+            reason: format!("expect output does not contain '{}'", needle),
+        });
+    }
+
     if let Some(needle) = parse_output_contains(normalized)? {
-        let merged = format!("{}\n{}", result.stdout, result.stderr);
+        let merged = result.merged_output();
         let passed = merged.contains(&needle);
         return Ok(ConditionEvaluation {
             passed,
@@ -96,6 +258,21 @@ fn parse_exit_code(condition: &str) -> Result<Option<i32>> {
     Ok(value)
 }
 
+/// Ignores the exit code, like the output rules; combine with `退出码 = 0` to require both.
+fn parse_duration_limit(condition: &str) -> Result<Option<Duration>> {
+    let regex = Regex::new(r"(?i)^(?:耗时|duration)\s*(?:<=|≤)\s*(.+)$")
+        .context("failed to compile duration regex")?;
+
+    regex
+        .captures(condition)
+        .and_then(|caps| caps.get(1).map(|m| m.as_str().trim()))
+        .map(|raw| {
+            humantime::parse_duration(raw)
+                .with_context(|| format!("failed to parse duration '{}' in pass condition", raw))
+        })
+        .transpose()
+}
+
 fn parse_coverage_threshold(condition: &str) -> Result<Option<f64>> {
     let regex = Regex::new(r"(?i)(?:覆盖率|coverage)\s*(?:>=|≥)\s*(\d+(?:\.\d+)?)")
         .context("failed to compile coverage regex")?;
@@ -114,10 +291,30 @@ fn parse_output_contains(condition: &str) -> Result<Option<String>> {
     let regex = Regex::new(r#"(?i)(?:包含|contains)\s*['"`]?(.+?)['"`]?$"#)
         .context("failed to compile contains regex")?;
 
-    let value = regex
+    Ok(capture_needle(&regex, condition))
+}
+
+fn parse_output_not_contains(condition: &str) -> Result<Option<String>> {
+    let regex = Regex::new(r#"(?i)(?:不包含|not\s+contains?)\s*['"`]?(.+?)['"`]?$"#)
+        .context("failed to compile not contains regex")?;
+
+    Ok(capture_needle(&regex, condition))
+}
+
+fn capture_needle(regex: &Regex, condition: &str) -> Option<String> {
+    regex
         .captures(condition)
         .and_then(|caps| caps.get(1).map(|m| m.as_str().trim().to_string()))
-        .filter(|text| !text.is_empty());
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_output_matches(condition: &str) -> Result<Option<String>> {
+    let regex = Regex::new(r"(?i)^(?:匹配|matches)\s*/(.+)/$")
+        .context("failed to compile matches regex")?;
+
+    let value = regex
+        .captures(condition)
+        .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()));
 
     Ok(value)
 }
@@ -149,7 +346,9 @@ mod tests {
             stderr: stderr.to_string(),
             duration_ms: 5,
             timed_out: false,
+            aborted: None,
             attempt: 1,
+            combined: None,
         }
     }
 
@@ -173,4 +372,127 @@ mod tests {
         let check = evaluate_pass_condition("无 clippy 警告", &result).expect("must evaluate");
         assert!(!check.passed);
     }
+
+    #[test]
+    fn checks_not_contains_rule() {
+        let result = sample_result("build finished", "", 1);
+        let check = evaluate_pass_condition("不包含 'panic'", &result).expect("must evaluate");
+        assert!(check.passed);
+        assert_eq!(check.reason, "expect output does not contain 'panic'");
+
+        let result = sample_result("", "thread 'main' panicked: error: boom", 0);
+        let check =
+            evaluate_pass_condition("not contains `error:`", &result).expect("must evaluate");
+        assert!(!check.passed);
+    }
+
+    #[test]
+    fn and_condition_fails_on_second_clause() {
+        let result = sample_result("FAILED", "", 0);
+        let check =
+            evaluate_pass_condition("退出码 = 0 && 包含 'PASSED'", &result).expect("must evaluate");
+        assert!(!check.passed);
+        assert_eq!(
+            check.reason,
+            "[pass] expect exit code 0, got 0 && [fail] expect output contains 'PASSED'"
+        );
+
+        let check =
+            evaluate_pass_condition("退出码 = 0 且 包含 'FAILED'", &result).expect("must evaluate");
+        assert!(check.passed);
+    }
+
+    #[test]
+    fn or_condition_passes_when_one_clause_passes() {
+        let result = sample_result("tests: 3 passed", "", 1);
+        let check = evaluate_pass_condition("exit code = 0 || contains 'passed'", &result)
+            .expect("must evaluate");
+        assert!(check.passed);
+        assert!(check
+            .reason
+            .starts_with("[fail] expect exit code 0, got 1 || [pass]"));
+
+        let check = evaluate_pass_condition("退出码 = 0 或 包含 'ok' && 退出码 = 1", &result)
+            .expect("must evaluate");
+        assert!(!check.passed);
+    }
+
+    #[test]
+    fn checks_regex_match_rule() {
+        let result = sample_result("running\ntests: 12 passed", "", 0);
+        let check =
+            evaluate_pass_condition(r"匹配 /tests: \d+ passed/", &result).expect("must evaluate");
+        assert!(check.passed);
+
+        let result = sample_result("", "error[E0308]: mismatched types", 1);
+        let check = evaluate_pass_condition(r"matches /tests: \d+ passed/", &result)
+            .expect("must evaluate");
+        assert!(!check.passed);
+    }
+
+    #[test]
+    fn reads_merged_output_when_streams_were_combined() {
+        let mut result = sample_result("", "", 0);
+        result.combined = Some("compiling\nwarning: unused\nfinished\n".to_string());
+        let check = evaluate_pass_condition(r"匹配 /warning: unused\nfinished/", &result)
+            .expect("must evaluate");
+        assert!(check.passed);
+        let check = evaluate_pass_condition("无 clippy 警告", &result).expect("must evaluate");
+        assert!(!check.passed);
+    }
+
+    #[test]
+    fn checks_duration_rule() {
+        let mut result = sample_result("ok", "", 0);
+        result.duration_ms = 850;
+        let check = evaluate_pass_condition("耗时 <= 2s", &result).expect("must evaluate");
+        assert!(check.passed);
+        assert_eq!(check.reason, "expect duration <= 2000ms, took 850ms");
+
+        result.duration_ms = 2300;
+        let check = evaluate_pass_condition("duration <= 2s", &result).expect("must evaluate");
+        assert!(!check.passed);
+
+        result.duration_ms = 400;
+        result.timed_out = true;
+        let check = evaluate_pass_condition("duration ≤ 500ms", &result).expect("must evaluate");
+        assert!(!check.passed);
+        assert!(check.reason.contains("timed out"));
+
+        assert!(evaluate_pass_condition("耗时 <= soon", &result).is_err());
+    }
+
+    #[test]
+    fn keeps_separators_inside_quotes_and_regex_literals() {
+        let result = sample_result("flags: x && y, x 且 y", "", 1);
+        let check = evaluate_pass_condition("contains 'x && y'", &result).expect("must evaluate");
+        assert!(check.passed);
+        assert_eq!(check.reason, "expect output contains 'x && y'");
+        let check = evaluate_pass_condition("包含 \"x 且 y\" || 退出码 = 0", &result)
+            .expect("must evaluate");
+        assert!(check.passed);
+        assert!(check
+            .reason
+            .starts_with("[pass] expect output contains 'x 且 y' || [fail]"));
+
+        let result = sample_result("left b", "", 0);
+        let check = evaluate_pass_condition("matches /a || b/", &result).expect("must evaluate");
+        assert!(check.passed);
+        assert_eq!(check.reason, "expect output matches /a || b/");
+        let check = evaluate_pass_condition(r"匹配 /left\/? b|x && y/ && 退出码 = 1", &result)
+            .expect("must evaluate");
+        assert!(!check.passed);
+        assert_eq!(
+            check.reason,
+            r"[pass] expect output matches /left\/? b|x && y/ && [fail] expect exit code 1, got 0"
+        );
+    }
+
+    #[test]
+    fn invalid_regex_fails_without_error() {
+        let result = sample_result("anything", "", 0);
+        let check = evaluate_pass_condition("matches /(unclosed/", &result).expect("must evaluate");
+        assert!(!check.passed);
+        assert!(check.reason.contains("invalid regex"));
+    }
 }
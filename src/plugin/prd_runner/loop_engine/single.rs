@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::plugin::prd_runner::config::prd::{PrdDocument, Requirement};
+use crate::plugin::prd_runner::config::AppConfig;
+use crate::plugin::prd_runner::core::executor::CommandExecutor;
+use crate::plugin::prd_runner::core::process::AiProcess;
+use crate::plugin::prd_runner::core::provider::Provider;
+use crate::plugin::prd_runner::loop_engine::evaluator::{evaluate_requirement, EvalResult};
+
+/// One provider round for a single requirement, without loop/log/checkpoint state.
+pub fn run_requirement(
+    workdir: &Path,
+    prd: &PrdDocument,
+    req: &Requirement,
+    config: &AppConfig,
+    provider: Box<dyn Provider>,
+) -> Result<EvalResult> {
+    let provider_timeout = config
+        .provider
+        .response_timeout_duration()
+        .context("failed to parse provider response timeout")?;
+    let executor = CommandExecutor::new(config.execution.clone(), workdir)?;
+    let mut ai_process = AiProcess::new(provider, provider_timeout, false)?;
+    ai_process.set_process_config(&config.process)?;
+    ai_process.load_prompt_template(workdir)?;
+
+    let prompt = ai_process.fit_prompt(&ai_process.build_prompt(prd, req, workdir));
+    let instruction = ai_process
+        .generate_instruction(&prompt.text)
+        .with_context(|| format!("provider failed for {}", req.id))?;
+
+    for command in &instruction.commands {
+        let _ = executor.run(command);
+    }
+
+    Ok(evaluate_requirement(
+        req,
+        &executor,
+        config.logging.output_summary_max_chars,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::run_requirement;
+    use crate::plugin::prd_runner::config::prd::{PrdDocument, Requirement};
+    use crate::plugin::prd_runner::config::AppConfig;
+    use crate::plugin::prd_runner::core::scripted::ScriptedProvider;
+    use crate::plugin::prd_runner::loop_engine::state::ReqStatus;
+
+    #[test]
+    fn runs_provider_commands_then_evaluates() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let req = Requirement {
+            id: "REQ-001".to_string(),
+            title: "marker".to_string(),
+            priority: None,
+            description: "create marker file".to_string(),
+            validate_command: "test -f marker.txt".to_string(),
+            validate_commands: Vec::new(),
+            pass_condition: "退出码 = 0".to_string(),
+            tasks: vec![],
+            artifacts: Vec::new(),
+            provider_timeout: None,
+            depends_on: Vec::new(),
+            workdir: None,
+        };
+        let prd = PrdDocument {
+            project_name: Some("demo".to_string()),
+            project_context: "ctx".to_string(),
+            requirements: vec![req.clone()],
+            acceptance_criteria: vec![],
+            raw_markdown: String::new(),
+        };
+        let provider = ScriptedProvider::new(&["```bash\necho hi > marker.txt\n```"]);
+        let prompts = provider.prompts();
+
+        let result = run_requirement(
+            tmp.path(),
+            &prd,
+            &req,
+            &AppConfig::default(),
+            Box::new(provider),
+        )?;
+
+        assert_eq!(result.status, ReqStatus::Done);
+        assert!(tmp.path().join("marker.txt").exists());
+        assert!(prompts.lock().expect("prompts lock")[0].contains("REQ-001"));
+        Ok(())
+    }
+}
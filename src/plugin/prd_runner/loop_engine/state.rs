@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
@@ -41,6 +42,8 @@ pub struct ReqRecord {
     pub evidence: Option<ReqEvidence>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_reason: Option<String>,
 }
 
 impl Default for ReqRecord {
@@ -50,6 +53,7 @@ impl Default for ReqRecord {
             attempts: 0,
             evidence: None,
             last_error: None,
+            blocked_reason: None,
         }
     }
 }
@@ -58,6 +62,9 @@ impl Default for ReqRecord {
 pub struct EngineState {
     pub iteration: u32,
     pub req_status: BTreeMap<String, ReqRecord>,
+    /// Runtime consumed by this run (including earlier resumed sessions) when last saved.
+    #[serde(default)]
+    pub accumulated_runtime_ms: u64,
 }
 
 impl EngineState {
@@ -70,15 +77,46 @@ impl EngineState {
         Self {
             iteration: 0,
             req_status,
+            accumulated_runtime_ms: 0,
         }
     }
 
+    pub fn accumulated_runtime(&self) -> Duration {
+        Duration::from_millis(self.accumulated_runtime_ms)
+    }
+
+    pub fn record_runtime(&mut self, elapsed: Duration) {
+        self.accumulated_runtime_ms = elapsed.as_millis().try_into().unwrap_or(u64::MAX);
+    }
+
     pub fn all_done(&self) -> bool {
         self.req_status
             .values()
             .all(|record| record.status == ReqStatus::Done)
     }
 
+    pub fn is_done(&self, req_id: &str) -> bool {
+        self.req_status
+            .get(req_id)
+            .is_some_and(|record| record.status == ReqStatus::Done)
+    }
+
+    pub fn failed_requirements(&self) -> Vec<String> {
+        self.ids_with_status(ReqStatus::Failed)
+    }
+
+    pub fn blocked_requirements(&self) -> Vec<String> {
+        self.ids_with_status(ReqStatus::Blocked)
+    }
+
+    fn ids_with_status(&self, status: ReqStatus) -> Vec<String> {
+        self.req_status
+            .iter()
+            .filter(|(_, record)| record.status == status)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
     pub fn overall_progress(&self) -> f64 {
         if self.req_status.is_empty() {
             return 0.0;
@@ -107,12 +145,42 @@ impl EngineState {
         })
     }
 
+    /// Aligns `req_status` with the current PRD, returning (added, removed) ids.
+    pub fn reconcile(&mut self, requirements: &[Requirement]) -> (Vec<String>, Vec<String>) {
+        let removed = self
+            .req_status
+            .keys()
+            .filter(|id| !requirements.iter().any(|req| &req.id == *id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in &removed {
+            self.req_status.remove(id);
+        }
+
+        let mut added = Vec::new();
+        for req in requirements {
+            if !self.req_status.contains_key(&req.id) {
+                self.req_status.insert(req.id.clone(), ReqRecord::default());
+                added.push(req.id.clone());
+            }
+        }
+
+        (added, removed)
+    }
+
     pub fn mark_in_progress(&mut self, req_id: &str) {
         if let Some(record) = self.req_status.get_mut(req_id) {
             record.status = ReqStatus::InProgress;
         }
     }
 
+    pub fn mark_blocked(&mut self, req_id: &str, reason: &str) {
+        if let Some(record) = self.req_status.get_mut(req_id) {
+            record.status = ReqStatus::Blocked;
+            record.blocked_reason = Some(reason.to_string());
+        }
+    }
+
     pub fn update(
         &mut self,
         req_id: &str,
@@ -126,6 +194,9 @@ impl EngineState {
             record.attempts = record.attempts.saturating_add(attempt_increment);
             record.evidence = evidence;
             record.last_error = error;
+            if status != ReqStatus::Blocked {
+                record.blocked_reason = None;
+            }
         }
     }
 }
@@ -144,8 +215,13 @@ mod tests {
                 priority: Some("high".to_string()),
                 description: "build project".to_string(),
                 validate_command: "echo ok".to_string(),
+                validate_commands: Vec::new(),
                 pass_condition: "退出码 = 0".to_string(),
                 tasks: vec!["do build".to_string()],
+                artifacts: Vec::new(),
+                provider_timeout: None,
+                depends_on: Vec::new(),
+                workdir: None,
             },
             Requirement {
                 id: "REQ-002".to_string(),
@@ -153,8 +229,13 @@ mod tests {
                 priority: Some("high".to_string()),
                 description: "run tests".to_string(),
                 validate_command: "echo ok".to_string(),
+                validate_commands: Vec::new(),
                 pass_condition: "退出码 = 0".to_string(),
                 tasks: vec!["do test".to_string()],
+                artifacts: Vec::new(),
+                provider_timeout: None,
+                depends_on: Vec::new(),
+                workdir: None,
             },
         ]
     }
@@ -181,6 +262,31 @@ mod tests {
         assert!(!state.all_done());
     }
 
+    #[test]
+    fn lists_failed_and_blocked_requirements() {
+        let mut reqs = sample_requirements();
+        let mut third = reqs[0].clone();
+        third.id = "REQ-003".to_string();
+        reqs.push(third);
+        let mut state = EngineState::new(&reqs);
+        assert!(state.failed_requirements().is_empty());
+        assert!(state.blocked_requirements().is_empty());
+
+        state.update("REQ-001", ReqStatus::Done, None, None, 1);
+        state.update(
+            "REQ-002",
+            ReqStatus::Failed,
+            None,
+            Some("boom".to_string()),
+            1,
+        );
+        state.mark_blocked("REQ-003", "depends on REQ-002");
+
+        assert_eq!(state.failed_requirements(), vec!["REQ-002".to_string()]);
+        assert_eq!(state.blocked_requirements(), vec!["REQ-003".to_string()]);
+        assert!(!state.all_done());
+    }
+
     #[test]
     fn detects_progress_between_snapshots() {
         let reqs = sample_requirements();
@@ -1,8 +1,9 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 pub mod app;
 pub mod checkpoint;
@@ -10,15 +11,36 @@ pub mod config;
 pub mod core;
 pub mod logger;
 pub mod loop_engine;
+pub mod template;
 
 use self::app::{
-    print_status, resume, run, validate_prd, PrdResumeOptions, PrdRunOptions, PrdRunOutput,
+    export_bundle, print_prd_diff, print_requirements, print_snapshot_diff, print_status,
+    restore_checkpoint, resume, run, validate_prd, PrdResumeOptions, PrdRunOptions, PrdRunOutput,
 };
+use self::template::PrdTemplate;
 use crate::provider::ProviderSelection;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct PluginDispatchContext {
     pub default_provider: ProviderSelection,
+    /// Global `--config` path; `None` uses `.autocode/config.toml`.
+    pub config_path: Option<PathBuf>,
+}
+
+/// clap `value_parser` for duration flags: rejects values `humantime` cannot parse
+/// before any setup work, while keeping the original text for the config override.
+pub fn parse_duration_arg(raw: &str) -> Result<String, String> {
+    humantime::parse_duration(raw)
+        .map(|_| raw.to_string())
+        .map_err(|err| err.to_string())
+}
+
+/// How `run`/`resume` report the final summary on stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
 }
 
 #[derive(Debug, Parser)]
@@ -36,38 +58,157 @@ enum PrdRunnerCommand {
     Resume(ResumeArgs),
     /// Show PRD checkpoint status
     Status(StatusArgs),
+    /// List requirements with their status from the latest checkpoint
+    Requirements(RequirementsArgs),
     /// Validate PRD.md format
-    Validate,
+    Validate(ValidateArgs),
     /// Initialize PRD.md template if not exists
-    Init,
+    Init(InitArgs),
+    /// Compare PRD.md against the PRD captured in a checkpoint
+    Diff(DiffArgs),
+    /// Package a run's logs, latest checkpoint and config into a .tar.gz for bug reports
+    ExportBundle(ExportBundleArgs),
+    /// Copy a checkpoint's code snapshot back over the workspace
+    Restore(RestoreArgs),
 }
 
 #[derive(Debug, Args)]
 struct RunArgs {
     #[arg(long)]
     provider: Option<String>,
-    #[arg(long, default_value = "10m")]
-    max_runtime: String,
-    #[arg(long)]
+    /// Arbitrary provider CLI run as `<cmd> <prompt>`; takes precedence over --provider
+    #[arg(long, value_name = "CMD")]
+    provider_command: Option<String>,
+    /// Overrides convergence.max_runtime from .autocode/config.toml (default 10m)
+    #[arg(long, value_parser = parse_duration_arg)]
+    max_runtime: Option<String>,
+    #[arg(long, value_parser = parse_duration_arg)]
     provider_timeout: Option<String>,
+    /// Overrides provider.model (e.g. claude --model)
+    #[arg(long)]
+    model: Option<String>,
     #[arg(long)]
     dry_run: bool,
+    /// PRD file to run instead of PRD.md (`-` reads stdin)
+    #[arg(long, value_name = "PATH")]
+    prd: Option<PathBuf>,
+    /// Stable run directory name instead of the run_YYYYMMDD_HHMMSS timestamp
+    #[arg(long)]
+    run_id: Option<String>,
+    /// Dry run that still queries the provider and logs the parsed commands (and whether the
+    /// guards would block them) without executing commands or acceptance checks
+    #[arg(long)]
+    preview_guards: bool,
+    /// Ask the provider once per requirement and log the planned commands; runs a single
+    /// pass with nothing executed and no checkpoints
+    #[arg(long, conflicts_with_all = ["dry_run", "preview_guards"])]
+    plan_only: bool,
+    /// Stream engine events as JSON lines to an fd (`-`/1 stdout, 2 stderr, N) or a file path
+    #[arg(long, value_name = "FD|PATH")]
+    json_logs: Option<String>,
+    /// Print a one-line progress summary at most this often when stdout is not a TTY
+    #[arg(long, value_name = "DUR", value_parser = parse_duration_arg)]
+    progress_interval: Option<String>,
+    /// Final summary format on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct ValidateArgs {
+    /// PRD file to validate instead of PRD.md (`-` reads stdin)
+    #[arg(value_name = "PATH")]
+    path: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
 struct ResumeArgs {
     #[arg(long)]
     provider: Option<String>,
-    #[arg(long, default_value = "10m")]
-    max_runtime: String,
-    #[arg(long)]
+    /// Arbitrary provider CLI run as `<cmd> <prompt>`; takes precedence over --provider
+    #[arg(long, value_name = "CMD")]
+    provider_command: Option<String>,
+    /// Overrides convergence.max_runtime from .autocode/config.toml (default 10m)
+    #[arg(long, value_parser = parse_duration_arg)]
+    max_runtime: Option<String>,
+    #[arg(long, value_parser = parse_duration_arg)]
     provider_timeout: Option<String>,
+    /// Overrides provider.model (e.g. claude --model)
+    #[arg(long)]
+    model: Option<String>,
     #[arg(long)]
     dry_run: bool,
     #[arg(long)]
     run_id: Option<String>,
     #[arg(long)]
     checkpoint_id: Option<String>,
+    /// Checkpoint number to resume from (e.g. 7 for checkpoint_007); negative counts from the latest (-1)
+    #[arg(long, allow_hyphen_values = true, conflicts_with = "checkpoint_id")]
+    checkpoint_index: Option<i64>,
+    /// Re-run requirements the checkpoint already marks done
+    #[arg(long)]
+    reverify: bool,
+    /// Restart the max_runtime clock instead of subtracting the runtime earlier sessions used
+    #[arg(long)]
+    reset_runtime: bool,
+    /// Stream engine events as JSON lines to an fd (`-`/1 stdout, 2 stderr, N) or a file path
+    #[arg(long, value_name = "FD|PATH")]
+    json_logs: Option<String>,
+    /// Print a one-line progress summary at most this often when stdout is not a TTY
+    #[arg(long, value_name = "DUR", value_parser = parse_duration_arg)]
+    progress_interval: Option<String>,
+    /// Final summary format on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct InitArgs {
+    /// Bundled template to scaffold from
+    #[arg(long, value_enum, default_value_t = PrdTemplate::RustCli)]
+    template: PrdTemplate,
+    /// Overwrite an existing PRD.md
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Debug, Args)]
+struct DiffArgs {
+    #[arg(long)]
+    run_id: Option<String>,
+    /// Checkpoint number to compare against; negative counts from the latest (default -1)
+    #[arg(long, allow_hyphen_values = true, conflicts_with = "from")]
+    checkpoint: Option<i64>,
+    /// Compare code snapshots starting from this checkpoint id or number
+    #[arg(long, requires = "to", allow_hyphen_values = true)]
+    from: Option<String>,
+    /// Checkpoint id or number to compare `--from` against
+    #[arg(long, requires = "from", allow_hyphen_values = true)]
+    to: Option<String>,
+    /// Print unified diffs for changed text files (with --from/--to)
+    #[arg(long, requires = "from")]
+    patch: bool,
+}
+
+#[derive(Debug, Args)]
+struct ExportBundleArgs {
+    /// Run to export (defaults to the latest)
+    #[arg(long = "run", visible_alias = "run-id")]
+    run_id: Option<String>,
+    /// Archive path to write
+    #[arg(long, value_name = "PATH")]
+    out: PathBuf,
+    /// Leave out the checkpoint code snapshot
+    #[arg(long)]
+    no_snapshot: bool,
+}
+
+#[derive(Debug, Args)]
+struct RestoreArgs {
+    #[arg(long)]
+    run_id: Option<String>,
+    #[arg(long)]
+    checkpoint_id: String,
 }
 
 #[derive(Debug, Args)]
@@ -76,11 +217,20 @@ struct StatusArgs {
     run_id: Option<String>,
 }
 
+#[derive(Debug, Args)]
+struct RequirementsArgs {
+    #[arg(long)]
+    run_id: Option<String>,
+    /// Print rows as JSON
+    #[arg(long)]
+    json: bool,
+}
+
 pub fn execute_from_tokens(
     workdir: &Path,
     tokens: &[String],
     context: PluginDispatchContext,
-) -> Result<()> {
+) -> Result<ExitCode> {
     let mut argv = vec!["prd-runner".to_string()];
     argv.extend(tokens.iter().cloned());
     let cli = PrdRunnerCli::try_parse_from(argv)
@@ -89,56 +239,128 @@ pub fn execute_from_tokens(
     match cli.command {
         PrdRunnerCommand::Run(args) => {
             let options = PrdRunOptions {
-                provider: resolve_provider_option(args.provider.as_deref(), context)?,
+                provider: resolve_provider_option(args.provider.as_deref(), &context)?,
+                provider_command: args.provider_command,
                 max_runtime: args.max_runtime,
                 provider_timeout: args.provider_timeout,
+                model: args.model,
                 dry_run: args.dry_run,
+                config_path: context.config_path.clone(),
+                prd: args.prd,
+                run_id: args.run_id,
+                preview_guards: args.preview_guards,
+                plan_only: args.plan_only,
+                json_logs: args.json_logs,
+                progress_interval: args.progress_interval,
+                no_progress: false,
+                output: args.output,
             };
             let output = run(workdir, options)?;
-            print_run_output(output);
+            return print_run_output(output, args.output);
         }
         PrdRunnerCommand::Resume(args) => {
             let options = PrdResumeOptions {
-                provider: resolve_provider_option(args.provider.as_deref(), context)?,
+                provider: parse_provider_flag(args.provider.as_deref())?,
+                default_provider: context.default_provider,
+                provider_command: args.provider_command,
                 max_runtime: args.max_runtime,
                 provider_timeout: args.provider_timeout,
+                model: args.model,
                 dry_run: args.dry_run,
+                config_path: context.config_path.clone(),
                 run_id: args.run_id,
                 checkpoint_id: args.checkpoint_id,
+                checkpoint_index: args.checkpoint_index,
+                json_logs: args.json_logs,
+                progress_interval: args.progress_interval,
+                reverify: args.reverify,
+                reset_runtime: args.reset_runtime,
+                output: args.output,
             };
             let output = resume(workdir, options)?;
-            print_run_output(output);
+            return print_run_output(output, args.output);
         }
         PrdRunnerCommand::Status(args) => {
             print_status(workdir, args.run_id.as_deref())?;
         }
-        PrdRunnerCommand::Validate => {
-            validate_prd(workdir)?;
+        PrdRunnerCommand::Requirements(args) => {
+            print_requirements(workdir, args.run_id.as_deref(), args.json)?;
+        }
+        PrdRunnerCommand::Validate(args) => {
+            validate_prd(workdir, args.path.as_deref())?;
+        }
+        PrdRunnerCommand::Diff(args) => match (args.from.as_deref(), args.to.as_deref()) {
+            (Some(from), Some(to)) => {
+                print_snapshot_diff(workdir, args.run_id.as_deref(), from, to, args.patch)?
+            }
+            _ => print_prd_diff(workdir, args.run_id.as_deref(), args.checkpoint)?,
+        },
+        PrdRunnerCommand::ExportBundle(args) => {
+            let out = workdir.join(&args.out);
+            export_bundle(
+                workdir,
+                args.run_id.as_deref(),
+                &out,
+                !args.no_snapshot,
+                context.config_path.as_deref(),
+            )?;
+            println!("Bundle written: {}", out.display());
         }
-        PrdRunnerCommand::Init => {
-            init_prd_template(workdir)?;
+        PrdRunnerCommand::Restore(args) => {
+            let checkpoint =
+                restore_checkpoint(workdir, args.run_id.as_deref(), &args.checkpoint_id)?;
+            println!("Workspace restored from {}", checkpoint.display());
+        }
+        PrdRunnerCommand::Init(args) => {
+            init_prd_from_template(workdir, args.template, args.force)?;
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 fn resolve_provider_option(
     input: Option<&str>,
-    context: PluginDispatchContext,
+    context: &PluginDispatchContext,
 ) -> Result<ProviderSelection> {
-    let Some(raw) = input else {
-        return Ok(context.default_provider);
-    };
+    Ok(parse_provider_flag(input)?.unwrap_or(context.default_provider))
+}
 
-    ProviderSelection::parse(raw)
-        .with_context(|| format!("invalid provider '{}', expected auto|claude|opencode", raw))
+fn parse_provider_flag(input: Option<&str>) -> Result<Option<ProviderSelection>> {
+    input
+        .map(|raw| {
+            ProviderSelection::parse(raw).with_context(|| {
+                format!(
+                    "invalid provider '{}', expected auto|claude|opencode|ollama",
+                    raw
+                )
+            })
+        })
+        .transpose()
 }
 
-fn print_run_output(output: PrdRunOutput) {
+fn print_run_output(output: PrdRunOutput, format: OutputFormat) -> Result<ExitCode> {
+    let exit_code = output.summary.outcome.exit_code();
+    if format == OutputFormat::Json {
+        println!("{}", output.to_json()?);
+        return Ok(ExitCode::from(exit_code));
+    }
     println!("Run finished.");
     println!("- completed: {}", output.summary.completed);
+    println!(
+        "- outcome: {} (exit code {})",
+        output.summary.outcome.as_str(),
+        exit_code
+    );
+    println!("- completion: {}", output.summary.completion_reason);
     println!("- iterations: {}", output.summary.iterations);
+    if output.summary.retries_suppressed > 0 {
+        println!(
+            "- retries: {} used, {} suppressed by execution.max_total_retries",
+            output.summary.retries_used, output.summary.retries_suppressed
+        );
+    }
+    println!("- provider: {}", output.summary.provider);
     if let Some(reason) = output.summary.stop_reason {
         println!("- stop reason: {}", reason);
     }
@@ -146,51 +368,65 @@ fn print_run_output(output: PrdRunOutput) {
         println!("- last checkpoint: {}", path.display());
     }
     println!("- runtime files: {}", output.log_root.display());
+    Ok(ExitCode::from(exit_code))
+}
+
+pub fn init_prd_template(workdir: &Path) -> Result<()> {
+    init_prd_from_template(workdir, PrdTemplate::RustCli, false)
 }
 
-fn init_prd_template(workdir: &Path) -> Result<()> {
+pub fn init_prd_from_template(workdir: &Path, template: PrdTemplate, force: bool) -> Result<()> {
     let prd_path = workdir.join("PRD.md");
-    if prd_path.exists() {
-        bail!("PRD.md already exists at {}", prd_path.display());
+    if prd_path.exists() && !force {
+        bail!(
+            "PRD.md already exists at {} (use --force to overwrite)",
+            prd_path.display()
+        );
     }
 
     let created_date = Utc::now().format("%Y-%m-%d").to_string();
-    let template = format!(
-        r#"# PRD: Project Name
-> 项目名称：project-name
-> 创建日期：{}
+    let template = template.render(&created_date);
 
-## 1. 项目上下文
-
-- **项目类型**: Rust CLI / Web / Service
-- **目标**: 简述本项目目标
-
-## 2. 需求列表
-
-### REQ-001: 最小可运行版本
+    std::fs::write(&prd_path, template)
+        .with_context(|| format!("failed to write {}", prd_path.display()))?;
+    println!("Created {}", prd_path.display());
+    Ok(())
+}
 
-| 字段 | 值 |
-|------|-----|
-| **优先级** | high |
-| **描述** | 搭建基础结构并可构建 |
-| **验证命令** | `cargo test` |
-| **通过条件** | 退出码 = 0 |
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
 
-- [ ] 初始化项目结构
-- [ ] 添加最小测试
+    use super::{PrdRunnerCli, PrdRunnerCommand};
 
-## 3. 验收标准
+    #[test]
+    fn rejects_invalid_durations_at_parse_time() {
+        for flag in ["--max-runtime", "--provider-timeout", "--progress-interval"] {
+            for command in ["run", "resume"] {
+                let err =
+                    PrdRunnerCli::try_parse_from(["prd-runner", command, flag, "4 fortnights"])
+                        .expect_err("invalid duration should be rejected");
+                assert!(err.to_string().contains(flag), "{}", err);
+            }
+        }
+        assert!(PrdRunnerCli::try_parse_from(["prd-runner", "run", "--max-runtime", "4h"]).is_ok());
+    }
 
-| 标准 | 验证命令 | 通过条件 |
-|------|----------|----------|
-| 工程可构建 | `cargo build` | 退出码 = 0 |
-| 全部测试通过 | `cargo test` | 退出码 = 0 |
-"#,
-        created_date
-    );
+    #[test]
+    fn plan_only_is_its_own_run_mode() {
+        let cli = PrdRunnerCli::try_parse_from(["prd-runner", "run", "--plan-only"])
+            .expect("--plan-only should parse");
+        let PrdRunnerCommand::Run(args) = cli.command else {
+            panic!("expected run command");
+        };
+        assert!(args.plan_only);
+        assert!(!args.preview_guards);
+        assert!(!args.dry_run);
 
-    std::fs::write(&prd_path, template)
-        .with_context(|| format!("failed to write {}", prd_path.display()))?;
-    println!("Created {}", prd_path.display());
-    Ok(())
+        for other in ["--preview-guards", "--dry-run"] {
+            assert!(
+                PrdRunnerCli::try_parse_from(["prd-runner", "run", "--plan-only", other]).is_err()
+            );
+        }
+    }
 }
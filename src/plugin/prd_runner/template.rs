@@ -0,0 +1,147 @@
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PrdTemplate {
+    RustCli,
+    Web,
+    Service,
+    Minimal,
+}
+
+impl PrdTemplate {
+    pub fn render(self, created_date: &str) -> String {
+        let (project_type, requirements, acceptance) = match self {
+            PrdTemplate::RustCli => (
+                "Rust CLI / Web / Service",
+                r#"### REQ-001: 最小可运行版本
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | high |
+| **描述** | 搭建基础结构并可构建 |
+| **验证命令** | `cargo test` |
+| **通过条件** | 退出码 = 0 |
+
+- [ ] 初始化项目结构
+- [ ] 添加最小测试
+"#,
+                r#"| 工程可构建 | `cargo build` | 退出码 = 0 |
+| 全部测试通过 | `cargo test` | 退出码 = 0 |
+"#,
+            ),
+            PrdTemplate::Web => (
+                "Web 前端（Node.js）",
+                r#"### REQ-001: 可构建的页面骨架
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | high |
+| **描述** | 初始化前端工程并可完成生产构建 |
+| **验证命令** | `npm run build` |
+| **通过条件** | 退出码 = 0 |
+
+- [ ] 初始化 package.json 与构建脚本
+- [ ] 添加首页
+
+### REQ-002: 基础测试
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | medium |
+| **描述** | 为首页添加单元测试 |
+| **验证命令** | `npm test` |
+| **通过条件** | 退出码 = 0 |
+
+- [ ] 配置测试框架
+- [ ] 添加首页渲染测试
+"#,
+                r#"| 生产构建成功 | `npm run build` | 退出码 = 0 |
+| 全部测试通过 | `npm test` | 退出码 = 0 |
+"#,
+            ),
+            PrdTemplate::Service => (
+                "后端服务（HTTP API）",
+                r#"### REQ-001: 服务可启动
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | high |
+| **描述** | 搭建服务骨架并可构建 |
+| **验证命令** | `make build` |
+| **通过条件** | 退出码 = 0 |
+
+- [ ] 初始化服务工程与 Makefile
+- [ ] 提供 /healthz 接口
+
+### REQ-002: 接口测试
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | medium |
+| **描述** | 为 /healthz 添加集成测试 |
+| **验证命令** | `make test` |
+| **通过条件** | 退出码 = 0 |
+
+- [ ] 添加健康检查测试
+"#,
+                r#"| 服务可构建 | `make build` | 退出码 = 0 |
+| 全部测试通过 | `make test` | 退出码 = 0 |
+"#,
+            ),
+            PrdTemplate::Minimal => (
+                "任意",
+                r#"### REQ-001: 第一个需求
+
+| 字段 | 值 |
+|------|-----|
+| **优先级** | high |
+| **描述** | 描述需要完成的内容 |
+| **验证命令** | `true` |
+| **通过条件** | 退出码 = 0 |
+"#,
+                r#"| 验收通过 | `true` | 退出码 = 0 |
+"#,
+            ),
+        };
+
+        format!(
+            r#"# PRD: Project Name
+> 项目名称：project-name
+> 创建日期：{}
+
+## 1. 项目上下文
+
+- **项目类型**: {}
+- **目标**: 简述本项目目标
+
+## 2. 需求列表
+
+{}
+## 3. 验收标准
+
+| 标准 | 验证命令 | 通过条件 |
+|------|----------|----------|
+{}"#,
+            created_date, project_type, requirements, acceptance
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+
+    use super::PrdTemplate;
+    use crate::plugin::prd_runner::config::parser::parse_prd_str;
+
+    #[test]
+    fn every_template_parses() {
+        for template in PrdTemplate::value_variants() {
+            let rendered = template.render("2024-01-01");
+            let doc = parse_prd_str(&rendered)
+                .unwrap_or_else(|err| panic!("{:?} template should parse: {:#}", template, err));
+            assert!(!doc.requirements.is_empty());
+            assert!(!doc.acceptance_criteria.is_empty());
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::process::ExitCode;
 
 use anyhow::{bail, Result};
 
@@ -33,7 +34,7 @@ impl PluginRegistry {
         plugin_id: &str,
         args: &[String],
         context: PluginDispatchContext,
-    ) -> Result<()> {
+    ) -> Result<ExitCode> {
         match plugin_id {
             "prd-runner" | "prd" => prd_runner::execute_from_tokens(workdir, args, context),
             _ => bail!("unknown plugin '{}'. run `autocode plugin list`", plugin_id),
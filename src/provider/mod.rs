@@ -1,11 +1,13 @@
+use std::path::Path;
 use std::process::{Command, Stdio};
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderKind {
     Claude,
     Opencode,
+    Ollama,
 }
 
 impl ProviderKind {
@@ -13,6 +15,7 @@ impl ProviderKind {
         match self {
             ProviderKind::Claude => "claude",
             ProviderKind::Opencode => "opencode",
+            ProviderKind::Ollama => "ollama",
         }
     }
 
@@ -20,6 +23,7 @@ impl ProviderKind {
         match self {
             ProviderKind::Claude => "claude",
             ProviderKind::Opencode => "opencode",
+            ProviderKind::Ollama => "ollama",
         }
     }
 }
@@ -29,6 +33,7 @@ pub enum ProviderSelection {
     Auto,
     Claude,
     Opencode,
+    Ollama,
 }
 
 impl ProviderSelection {
@@ -37,27 +42,74 @@ impl ProviderSelection {
             "auto" => Some(Self::Auto),
             "claude" => Some(Self::Claude),
             "opencode" => Some(Self::Opencode),
+            "ollama" => Some(Self::Ollama),
             _ => None,
         }
     }
 }
 
+pub trait ProviderProbe {
+    fn available(&self, provider: ProviderKind) -> bool;
+}
+
+/// Probes providers by running `<cmd> --help` on PATH.
+pub struct CommandProbe;
+
+impl ProviderProbe for CommandProbe {
+    fn available(&self, provider: ProviderKind) -> bool {
+        command_available(provider.command())
+    }
+}
+
+/// Probes an arbitrary provider CLI by running `<cmd> --help`.
+pub fn command_available(command: &str) -> bool {
+    let status = Command::new(command)
+        .arg("--help")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    status.map(|v| v.success()).unwrap_or(false)
+}
+
+impl<F> ProviderProbe for F
+where
+    F: Fn(ProviderKind) -> bool,
+{
+    fn available(&self, provider: ProviderKind) -> bool {
+        self(provider)
+    }
+}
+
 pub fn resolve_provider(selection: ProviderSelection) -> Result<ProviderKind> {
+    resolve_provider_with(selection, &CommandProbe)
+}
+
+pub fn resolve_provider_with(
+    selection: ProviderSelection,
+    probe: &dyn ProviderProbe,
+) -> Result<ProviderKind> {
     let resolved = match selection {
         ProviderSelection::Claude => ProviderKind::Claude,
         ProviderSelection::Opencode => ProviderKind::Opencode,
-        ProviderSelection::Auto => detect_default_provider()?,
+        ProviderSelection::Ollama => ProviderKind::Ollama,
+        ProviderSelection::Auto => detect_default_provider_with(probe)?,
     };
 
     Ok(resolved)
 }
 
 pub fn detect_default_provider() -> Result<ProviderKind> {
-    if provider_available(ProviderKind::Claude) {
+    detect_default_provider_with(&CommandProbe)
+}
+
+pub fn detect_default_provider_with(probe: &dyn ProviderProbe) -> Result<ProviderKind> {
+    if probe.available(ProviderKind::Claude) {
         return Ok(ProviderKind::Claude);
     }
 
-    if provider_available(ProviderKind::Opencode) {
+    if probe.available(ProviderKind::Opencode) {
         return Ok(ProviderKind::Opencode);
     }
 
@@ -67,13 +119,112 @@ pub fn detect_default_provider() -> Result<ProviderKind> {
 }
 
 pub fn provider_available(provider: ProviderKind) -> bool {
-    let command = provider.command();
-    let status = Command::new(command)
+    CommandProbe.available(provider)
+}
+
+/// Splits a provider command line such as `aider --yes` into program and arguments.
+/// An existing file path is kept whole, so Windows paths and paths with spaces work.
+pub fn split_provider_command(command: &str) -> Result<Vec<String>> {
+    let command = command.trim();
+    if Path::new(command).is_file() {
+        return Ok(vec![command.to_string()]);
+    }
+    let argv = shell_words::split(command)
+        .with_context(|| format!("failed to parse provider command '{}'", command))?;
+    if argv.is_empty() {
+        bail!("provider command is empty");
+    }
+    Ok(argv)
+}
+
+/// Validates a `--provider-command` value; it takes precedence over `--provider`.
+pub fn resolve_provider_command(command: &str) -> Result<String> {
+    let command = command.trim();
+    if command.is_empty() {
+        bail!("provider command is empty");
+    }
+    let argv = split_provider_command(command)?;
+    let probe = Command::new(&argv[0])
+        .args(&argv[1..])
         .arg("--help")
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status();
+    if !probe.is_ok_and(|status| status.success()) {
+        bail!(
+            "provider command '{}' is not executable (`{} --help` failed)",
+            command,
+            command
+        );
+    }
+    Ok(command.to_string())
+}
 
-    status.map(|v| v.success()).unwrap_or(false)
+#[cfg(test)]
+mod tests {
+    use super::{
+        detect_default_provider_with, resolve_provider_command, resolve_provider_with,
+        split_provider_command, ProviderKind, ProviderSelection,
+    };
+
+    #[test]
+    fn auto_detect_prefers_claude_then_opencode() {
+        let both = |_: ProviderKind| true;
+        assert_eq!(
+            detect_default_provider_with(&both).expect("claude"),
+            ProviderKind::Claude
+        );
+
+        let only_opencode = |kind: ProviderKind| kind == ProviderKind::Opencode;
+        assert_eq!(
+            detect_default_provider_with(&only_opencode).expect("opencode"),
+            ProviderKind::Opencode
+        );
+
+        let none = |_: ProviderKind| false;
+        assert!(detect_default_provider_with(&none).is_err());
+        assert_eq!(
+            resolve_provider_with(ProviderSelection::Claude, &none).expect("explicit"),
+            ProviderKind::Claude
+        );
+    }
+
+    #[test]
+    fn custom_provider_command_is_probed() {
+        assert_eq!(resolve_provider_command(" cat ").expect("cat"), "cat");
+        assert!(resolve_provider_command("autocode-missing-provider").is_err());
+        assert!(resolve_provider_command("  ").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn provider_command_may_carry_arguments() {
+        assert_eq!(
+            split_provider_command(" python wrapper.py --model 'big one' ").expect("split"),
+            vec!["python", "wrapper.py", "--model", "big one"]
+        );
+        assert_eq!(
+            resolve_provider_command("sh -c 'exit 0'").expect("sh -c probes"),
+            "sh -c 'exit 0'"
+        );
+        assert!(resolve_provider_command("sh -c 'exit 3'").is_err());
+        assert!(split_provider_command("agent 'unclosed").is_err());
+    }
+
+    #[test]
+    fn ollama_resolves_only_when_selected() {
+        assert_eq!(
+            ProviderSelection::parse("Ollama"),
+            Some(ProviderSelection::Ollama)
+        );
+        let none = |_: ProviderKind| false;
+        assert_eq!(
+            resolve_provider_with(ProviderSelection::Ollama, &none).expect("explicit"),
+            ProviderKind::Ollama
+        );
+
+        let only_ollama = |kind: ProviderKind| kind == ProviderKind::Ollama;
+        assert!(detect_default_provider_with(&only_ollama).is_err());
+    }
 }
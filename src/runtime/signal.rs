@@ -4,18 +4,66 @@ use anyhow::{Context, Result};
 
 static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
-pub fn install_ctrlc_handler() -> Result<()> {
+/// Sets the interrupt flag on Ctrl+C and, on Unix, also on SIGTERM/SIGHUP so the
+/// driver can write its STOP event and final checkpoint before exiting.
+pub fn install_termination_handlers() -> Result<()> {
     INTERRUPTED.store(false, Ordering::SeqCst);
     ctrlc::set_handler(|| {
         INTERRUPTED.store(true, Ordering::SeqCst);
     })
-    .context("failed to install Ctrl+C handler")
+    .context("failed to install Ctrl+C/SIGTERM handler")
 }
 
 pub fn interrupted() -> bool {
     INTERRUPTED.load(Ordering::SeqCst)
 }
 
+pub fn request_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
 pub fn reset_interrupted() {
     INTERRUPTED.store(false, Ordering::SeqCst);
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    use super::{install_termination_handlers, interrupted};
+
+    const CHILD_ENV: &str = "AUTOCODE_SIGTERM_TEST_CHILD";
+
+    /// The interrupt flag is process-global, so the signal is raised in a re-spawned
+    /// copy of this test binary instead of disturbing tests running in parallel.
+    #[test]
+    fn sigterm_sets_interrupted_flag() {
+        if std::env::var_os(CHILD_ENV).is_none() {
+            let status = Command::new(std::env::current_exe().expect("test binary"))
+                .args([
+                    "--exact",
+                    "runtime::signal::tests::sigterm_sets_interrupted_flag",
+                ])
+                .env(CHILD_ENV, "1")
+                .status()
+                .expect("spawn child test");
+            assert!(status.success());
+            return;
+        }
+
+        install_termination_handlers().expect("install handlers");
+        assert!(!interrupted());
+        let status = Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .expect("send SIGTERM");
+        assert!(status.success());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !interrupted() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(interrupted());
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+fn autocode(workdir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_autocode"))
+        .args(args)
+        .current_dir(workdir)
+        .env_remove("AUTOCODE_PROVIDER")
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .expect("failed to run autocode")
+}
+
+#[test]
+fn json_output_is_the_only_thing_on_stdout() {
+    let tmp = TempDir::new().expect("tempdir");
+    assert!(autocode(tmp.path(), &["prd", "init"]).status.success());
+
+    let output = autocode(
+        tmp.path(),
+        &[
+            "prd",
+            "run",
+            "--provider-command",
+            "cat",
+            "--dry-run",
+            "--max-runtime",
+            "1s",
+            "--progress-interval",
+            "1ms",
+            "--output",
+            "json",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let summary: serde_json::Value =
+        serde_json::from_str(&stdout).expect("stdout should be a single JSON document");
+    assert_eq!(summary["provider"], "cat");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("iter="));
+
+    let rejected = autocode(
+        tmp.path(),
+        &[
+            "prd",
+            "run",
+            "--provider-command",
+            "cat",
+            "--dry-run",
+            "--json-logs",
+            "-",
+            "--output",
+            "json",
+        ],
+    );
+    assert!(!rejected.status.success());
+    assert!(rejected.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("--json-logs"));
+}